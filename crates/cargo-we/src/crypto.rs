@@ -0,0 +1,253 @@
+//! Keys and signatures of Waves Enterprise accounts.
+//!
+//! Accounts have Curve25519 keys, transactions are signed with the Ed25519 form
+//! of the key as done by the node: the sign of the Edwards public key is kept
+//! in the last bit of the signature.
+
+use base58::{FromBase58, ToBase58};
+use blake2::{digest::consts::U32, Blake2b};
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use rand::{rngs::OsRng, seq::SliceRandom, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+use sha3::Keccak256;
+
+pub const SIGNATURE_LENGTH: usize = 64;
+/// Number of words in generated seed phrases.
+const SEED_WORDS: usize = 15;
+const ADDRESS_VERSION: u8 = 1;
+
+/// Prefix of the hash of the nonce, as in the reference implementation.
+const NONCE_PREFIX: [u8; 32] = {
+    let mut prefix = [0xff; 32];
+    prefix[0] = 0xfe;
+    prefix
+};
+
+/// Dictionary of the seed phrases.
+const WORDS: &str = include_str!("words.txt");
+
+pub struct PrivateKey([u8; 32]);
+
+impl PrivateKey {
+    /// Derives the key of the account with the nonce 0, as the node and the wallets do.
+    pub fn from_seed(seed_phrase: &str) -> Self {
+        let account_seed = secure_hash(&[&0u32.to_be_bytes(), seed_phrase.as_bytes()].concat());
+        let hash: [u8; 32] = Sha256::digest(account_seed).into();
+
+        PrivateKey(clamp(hash))
+    }
+
+    pub fn from_base58(value: &str) -> Result<Self, String> {
+        value
+            .from_base58()
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(PrivateKey)
+            .ok_or_else(|| "the private key is not 32 bytes in base58".to_string())
+    }
+
+    pub fn to_base58(&self) -> String {
+        self.0.to_base58()
+    }
+
+    /// Curve25519 public key of the account.
+    pub fn public_key(&self) -> [u8; 32] {
+        EdwardsPoint::mul_base_clamped(self.0)
+            .to_montgomery()
+            .to_bytes()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+        let mut random = [0u8; 64];
+        OsRng.fill_bytes(&mut random);
+
+        self.sign_with(message, &random)
+    }
+
+    fn sign_with(&self, message: &[u8], random: &[u8; 64]) -> [u8; SIGNATURE_LENGTH] {
+        let secret = Scalar::from_bytes_mod_order(clamp(self.0));
+        let public_key = EdwardsPoint::mul_base(&secret).compress().to_bytes();
+
+        let nonce = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(NONCE_PREFIX)
+                .chain_update(self.0)
+                .chain_update(message)
+                .chain_update(random),
+        );
+        let r = EdwardsPoint::mul_base(&nonce).compress().to_bytes();
+
+        let hash = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(r)
+                .chain_update(public_key)
+                .chain_update(message),
+        );
+        let s = hash * secret + nonce;
+
+        let mut signature = [0u8; SIGNATURE_LENGTH];
+        signature[..32].copy_from_slice(&r);
+        signature[32..].copy_from_slice(s.as_bytes());
+        signature[63] |= public_key[31] & 0x80;
+        signature
+    }
+}
+
+impl std::fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PrivateKey({})", self.public_key().to_base58())
+    }
+}
+
+/// Generates a seed phrase from the words of the dictionary.
+pub fn random_seed_phrase() -> String {
+    let words: Vec<&str> = WORDS.lines().collect();
+    words
+        .choose_multiple(&mut OsRng, SEED_WORDS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Address of the public key in the network with the chain id.
+pub fn address(public_key: &[u8], chain_id: u8) -> String {
+    let mut address = vec![ADDRESS_VERSION, chain_id];
+    address.extend_from_slice(&secure_hash(public_key)[..20]);
+    let checksum = secure_hash(&address);
+    address.extend_from_slice(&checksum[..4]);
+
+    address.to_base58()
+}
+
+/// Keccak256 of Blake2b256, the hash of addresses.
+fn secure_hash(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(Blake2b::<U32>::digest(bytes)).into()
+}
+
+fn clamp(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    bytes
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use curve25519_dalek::{edwards::CompressedEdwardsY, MontgomeryPoint};
+
+    /// Verifies the signature against the Curve25519 public key as the node does.
+    pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        let sign = signature[63] & 0x80;
+        let public_key = match MontgomeryPoint(public_key.try_into().unwrap()).to_edwards(sign >> 7)
+        {
+            Some(point) => point,
+            None => return false,
+        };
+
+        let r = CompressedEdwardsY(signature[..32].try_into().unwrap());
+        let mut s: [u8; 32] = signature[32..].try_into().unwrap();
+        s[31] &= 0x7f;
+        let s = match Option::<Scalar>::from(Scalar::from_canonical_bytes(s)) {
+            Some(s) => s,
+            None => return false,
+        };
+
+        let hash = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(r.as_bytes())
+                .chain_update(public_key.compress().as_bytes())
+                .chain_update(message),
+        );
+
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(&hash, &-public_key, &s).compress() == r
+    }
+
+    fn decode(value: &str) -> Vec<u8> {
+        value.from_base58().unwrap()
+    }
+
+    #[test]
+    fn public_key() {
+        let cases = [
+            (
+                "3j2aMHzh9azPphzuW7aF3cmUefGEQC9dcWYXYCyoPcJg",
+                "8cj6YzvQPhSHGvnjupNTW8zrADTT8CMAAd2xTuej84gB",
+            ),
+            (
+                "HCK7dUsScMH9mTCoyaV7bVhkTxwsyCHdbMBfb9TpVhPd",
+                "DTvCW1nzFr7mHrHkGf1apstRfwPp4yYL19YvjjLEAPBh",
+            ),
+            (
+                "6zFSymZAoaua3gtJPbAUwM584tRETdKYdEG9BeEnZaGW",
+                "4KxUVD9NtyRJjU3BCvPgJSttoJX7cb3DMdDTNucLN121",
+            ),
+        ];
+
+        for (private_key, public_key) in cases {
+            let private_key = PrivateKey::from_base58(private_key).unwrap();
+            assert_eq!(private_key.public_key().to_base58(), public_key);
+        }
+    }
+
+    #[test]
+    fn from_seed() {
+        let seed_phrase = "blame vacant regret company chase trip grant funny brisk innocent";
+        let private_key = PrivateKey::from_seed(seed_phrase);
+
+        assert_eq!(
+            private_key.to_base58(),
+            "3j2aMHzh9azPphzuW7aF3cmUefGEQC9dcWYXYCyoPcJg"
+        );
+        assert_eq!(
+            address(&private_key.public_key(), b'T'),
+            "3Ms87NGAAaPWZux233TB9A3TXps4LDkyJWN"
+        );
+    }
+
+    #[test]
+    fn seed_phrase() {
+        assert_eq!(WORDS.lines().count(), 2048);
+
+        let seed_phrase = random_seed_phrase();
+        assert_eq!(seed_phrase.split(' ').count(), SEED_WORDS);
+        assert_ne!(seed_phrase, random_seed_phrase());
+    }
+
+    #[test]
+    fn verify_known_signature() {
+        let public_key = decode("4KxUVD9NtyRJjU3BCvPgJSttoJX7cb3DMdDTNucLN121");
+        let signature = decode(
+            "B4ViRpS6wZ73hhTtP4hhrfV46rR3uoUn7jgsH5yfkKMpbJUxMmu48jf3QSdibRkQBN7Tkx9jReKDq1Rmp9acxPG",
+        );
+
+        assert!(verify(&public_key, b"uncle", &signature));
+        assert!(!verify(&public_key, b"uncles", &signature));
+    }
+
+    #[test]
+    fn sign() {
+        let private_key =
+            PrivateKey::from_base58("6zFSymZAoaua3gtJPbAUwM584tRETdKYdEG9BeEnZaGW").unwrap();
+        let public_key = private_key.public_key();
+
+        for message in [&b""[..], b"uncle", &[0xff; 1000]] {
+            let signature = private_key.sign(message);
+            assert!(verify(&public_key, message, &signature));
+        }
+
+        // The signature only depends on the random bytes
+        let random = [7; 64];
+        assert_eq!(
+            private_key.sign_with(b"uncle", &random),
+            private_key.sign_with(b"uncle", &random)
+        );
+        assert_ne!(private_key.sign(b"uncle"), private_key.sign(b"uncle"));
+    }
+
+    #[test]
+    fn invalid_private_key() {
+        assert!(PrivateKey::from_base58("0OIl").is_err());
+        assert!(PrivateKey::from_base58("3j2aMHzh9azPphzuW7aF3").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! Records of the contract deployed by `cargo we deploy`, one per network,
+//! kept in `deployments/<network>.json` of the project.
+
+use crate::metadata::Metadata;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DEPLOYMENTS: &str = "deployments";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    pub network: String,
+    pub contract_id: String,
+    /// Version of the contract, increased by every update.
+    pub version: u64,
+    pub bytecode_hash: String,
+    /// Id of the last CreateContract or UpdateContract transaction.
+    pub tx_id: String,
+    /// ABI of the deployed bytecode.
+    pub abi: Metadata,
+}
+
+impl Deployment {
+    fn path(dir: &Path, network: &str) -> PathBuf {
+        dir.join(format!("{}.json", network))
+    }
+
+    pub fn exists(network: &str) -> bool {
+        Self::path(Path::new(DEPLOYMENTS), network).exists()
+    }
+
+    pub fn load(network: &str) -> Result<Self, String> {
+        Self::load_from(Path::new(DEPLOYMENTS), network)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(Path::new(DEPLOYMENTS))
+    }
+
+    fn load_from(dir: &Path, network: &str) -> Result<Self, String> {
+        let path = Self::path(dir, network);
+        let json = fs::read_to_string(&path).map_err(|_| {
+            format!(
+                "no deployment on `{}` in {}, run `cargo we deploy`",
+                network,
+                path.display()
+            )
+        })?;
+
+        serde_json::from_str(&json)
+            .map_err(|error| format!("can't parse {}: {}", path.display(), error))
+    }
+
+    fn save_to(&self, dir: &Path) -> Result<(), String> {
+        let path = Self::path(dir, &self.network);
+        fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        let json = serde_json::to_string_pretty(self).expect("Unable to serialize struct to JSON");
+
+        fs::write(&path, json)
+            .map_err(|error| format!("can't write {}: {}", path.display(), error))?;
+        println!("Deployment recorded in {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn dir(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("we-deployments-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = dir("save");
+        let deployment = Deployment {
+            network: "testnet".to_string(),
+            contract_id: "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2".to_string(),
+            version: 2,
+            bytecode_hash: "abc".to_string(),
+            tx_id: "8cj6YzvQPhSHGvnjupNTW8zrADTT8CMAAd2xTuej84gB".to_string(),
+            abi: Metadata::from_json(
+                r#"{"name": "flipper", "abi": [
+                    {"name": "_constructor", "args": [{"name": "value", "type": "Boolean"}]},
+                    {"name": "flip", "args": []}
+                ], "errors": [
+                    {"code": 1000, "name": "FlipperError::Locked", "message": "Flipper is locked"}
+                ]}"#,
+            )
+            .unwrap(),
+        };
+
+        deployment.save_to(&dir).unwrap();
+        assert!(Deployment::path(&dir, "testnet").exists());
+
+        let loaded = Deployment::load_from(&dir, "testnet").unwrap();
+        assert_eq!(loaded.network, deployment.network);
+        assert_eq!(loaded.contract_id, deployment.contract_id);
+        assert_eq!(loaded.version, 2);
+        assert_eq!(loaded.bytecode_hash, deployment.bytecode_hash);
+        assert_eq!(loaded.tx_id, deployment.tx_id);
+        assert!(loaded.abi.diff(&deployment.abi).is_empty());
+        assert!(loaded.abi.func("flip").is_some());
+        assert_eq!(
+            loaded.abi.describe_error(1000),
+            "1000 FlipperError::Locked: Flipper is locked"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_and_broken() {
+        let dir = dir("missing");
+        let path = Deployment::path(&dir, "mainnet");
+
+        assert_eq!(
+            Deployment::load_from(&dir, "mainnet").unwrap_err(),
+            format!(
+                "no deployment on `mainnet` in {}, run `cargo we deploy`",
+                path.display()
+            )
+        );
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, r#"{"network": "mainnet"}"#).unwrap();
+        assert!(Deployment::load_from(&dir, "mainnet")
+            .unwrap_err()
+            .starts_with(&format!("can't parse {}: missing field", path.display())));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
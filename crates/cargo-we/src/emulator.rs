@@ -0,0 +1,390 @@
+pub mod host;
+pub mod scenario;
+
+use std::fmt;
+use wasmi::{
+    core::{Trap, ValueType},
+    errors::LinkerError,
+    Config, Engine, FuncType, Linker, Memory, MemoryType, Module, Store, Value,
+};
+use we_cdk::Error;
+use we_test_env::DataEntry;
+
+/// Memory limits of the contract in pages, as set by `cargo we build`.
+pub const MEMORY: (u32, u32) = (2, 16);
+/// Modules of the host functions, one per version of the API.
+pub const HOST_MODULES: [&str; 2] = ["env0", "env1"];
+/// Fuel available to a single execution of the contract.
+const FUEL_LIMIT: u64 = 1_000_000_000;
+
+/// State of the running contract used by the host functions.
+#[derive(Default)]
+pub struct Runtime {
+    memory: Option<Memory>,
+    heap_base: u32,
+}
+
+impl Runtime {
+    fn memory(&self) -> Result<Memory, Trap> {
+        self.memory
+            .ok_or_else(|| Trap::new(Error::MemoryNotFound.to_string()))
+    }
+}
+
+/// Failure reported by the virtual machine instead of the contract.
+#[derive(Debug)]
+pub struct Failure {
+    pub error: Error,
+    pub details: String,
+}
+
+impl Failure {
+    fn new(error: Error, details: impl fmt::Debug) -> Self {
+        Failure {
+            error,
+            details: format!("{:?}", details),
+        }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure {
+            error,
+            details: String::new(),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.details.is_empty() {
+            true => write!(f, "{}", self.error),
+            false => write!(f, "{} {}", self.error, self.details),
+        }
+    }
+}
+
+/// Contract loaded into the interpreter.
+///
+/// Every execution gets a fresh instance with its own memory,
+/// the state is kept by the world of `we_test_env`.
+pub struct Contract {
+    module: Module,
+    linker: Linker<Runtime>,
+}
+
+impl Contract {
+    /// Validates the bytecode with the features enabled on the node.
+    pub fn new(bytecode: &[u8]) -> Result<Self, Failure> {
+        let engine = Engine::new(&config());
+        let module = Module::new(&engine, bytecode)
+            .map_err(|error| Failure::new(Error::InvalidBytecode, error))?;
+
+        if module.get_export("_constructor").is_none() {
+            return Err(Error::ConstructorNotFound.into());
+        }
+
+        let mut linker = Linker::new(&engine);
+        host::define(&mut linker).map_err(|error| Failure::new(Error::LinkerError, error))?;
+
+        Ok(Contract { module, linker })
+    }
+
+    /// Executes the function with the arguments passed as by the node.
+    /// Returns the code returned by the contract.
+    pub fn execute(&self, func_name: &str, params: &[DataEntry]) -> Result<i32, Failure> {
+        let mut store = Store::new(self.module.engine(), Runtime::default());
+
+        let memory_type = MemoryType::new(MEMORY.0, Some(MEMORY.1))
+            .map_err(|error| Failure::new(Error::MemoryError, error))?;
+        let memory = Memory::new(&mut store, memory_type)
+            .map_err(|error| Failure::new(Error::MemoryLimits, error))?;
+        store
+            .add_fuel(FUEL_LIMIT)
+            .map_err(|error| Failure::new(Error::FuelMeteringDisabled, error))?;
+
+        let mut linker = self.linker.clone();
+        linker
+            .define("env", "memory", memory)
+            .map_err(|error| Failure::new(Error::LinkerError, error))?;
+
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|error| Failure::new(Error::InstantiateFailed, error))?;
+
+        let heap_base = match instance
+            .get_global(&store, "__heap_base")
+            .map(|global| global.get(&store))
+        {
+            Some(Value::I32(value)) => value as u32,
+            _ => return Err(Error::HeapBaseNotFound.into()),
+        };
+
+        let func = instance
+            .get_func(&store, func_name)
+            .ok_or(Error::FuncNotFound)?;
+
+        // Binary and string arguments are copied to the heap and passed as offset and length
+        let mut offset = heap_base as usize;
+        let mut values: Vec<i64> = vec![];
+        for param in params {
+            match param {
+                DataEntry::Integer(value) => values.push(*value),
+                DataEntry::Boolean(value) => values.push(*value as i64),
+                DataEntry::Binary(value) => {
+                    values.extend(write(&mut store, memory, &mut offset, value)?)
+                }
+                DataEntry::String(value) => {
+                    values.extend(write(&mut store, memory, &mut offset, value.as_bytes())?)
+                }
+            }
+        }
+
+        store.data_mut().memory = Some(memory);
+        store.data_mut().heap_base = offset as u32;
+
+        let func_type = func.ty(&store);
+        if func_type.params().len() != values.len() {
+            return Err(Error::InvalidNumArgs.into());
+        }
+
+        let args = func_type
+            .params()
+            .iter()
+            .zip(values)
+            .map(|(ty, value)| match ty {
+                ValueType::I32 => i32::try_from(value)
+                    .map(Value::I32)
+                    .map_err(|error| Failure::new(Error::FailedParseFuncArgs, error)),
+                ValueType::I64 => Ok(Value::I64(value)),
+                ty => Err(Failure::new(Error::FailedParseFuncArgs, ty)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut results: Vec<Value> = func_type
+            .results()
+            .iter()
+            .copied()
+            .map(Value::default)
+            .collect();
+
+        func.call(&mut store, &args, &mut results)
+            .map_err(|error| Failure::new(Error::FailedExec, error))?;
+
+        match results.as_slice() {
+            [Value::I32(code)] => Ok(*code),
+            _ => Err(Error::InvalidResult.into()),
+        }
+    }
+}
+
+/// Signature of the host function, `None` if the node does not define it.
+pub fn host_function(module: &str, name: &str) -> Option<FuncType> {
+    let engine = Engine::new(&config());
+    let mut linker = Linker::<Runtime>::new(&engine);
+    host::define(&mut linker).expect("Host functions are defined once");
+
+    // Imports the function without params, the linker reports the defined signature
+    let probe = format!(r#"(module (import "{}" "{}" (func)))"#, module, name);
+    let probe = wat::parse_str(probe).ok()?;
+    let probe = Module::new(&engine, probe.as_slice()).ok()?;
+
+    let mut store = Store::new(&engine, Runtime::default());
+    match linker.instantiate(&mut store, &probe) {
+        Ok(_) => Some(FuncType::new([], [])),
+        Err(wasmi::Error::Linker(LinkerError::FuncTypeMismatch { found, .. })) => Some(found),
+        Err(_) => None,
+    }
+}
+
+/// Features of WebAssembly enabled on the node.
+fn config() -> Config {
+    let mut config = Config::default();
+    config
+        .wasm_mutable_global(false)
+        .wasm_sign_extension(true)
+        .wasm_saturating_float_to_int(false)
+        .wasm_multi_value(true)
+        .wasm_bulk_memory(true)
+        .wasm_reference_types(false)
+        .wasm_tail_call(false)
+        .wasm_extended_const(false)
+        .floats(false)
+        .consume_fuel(true);
+    config
+}
+
+/// Copies the argument to the memory of the contract.
+fn write(
+    store: &mut Store<Runtime>,
+    memory: Memory,
+    offset: &mut usize,
+    bytes: &[u8],
+) -> Result<[i64; 2], Failure> {
+    let start = *offset;
+    memory
+        .data_mut(store)
+        .get_mut(start..start + bytes.len())
+        .ok_or(Error::MemoryError)?
+        .copy_from_slice(bytes);
+    *offset += bytes.len();
+
+    Ok([start as i64, bytes.len() as i64])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use we_test_env as test_env;
+
+    const COUNTER: &str = r#"(module
+        (import "env" "memory" (memory 2 16))
+        (import "env0" "get_storage_int" (func $get_int (param i32 i32 i32 i32) (result i32 i64)))
+        (import "env0" "set_storage_int" (func $set_int (param i32 i32 i64) (result i32)))
+        (import "env0" "parse_int" (func $parse_int (param i32 i32) (result i32 i64)))
+        (global (export "__heap_base") i32 (i32.const 1024))
+        (data (i32.const 0) "count")
+        (func (export "_constructor") (param $value i64) (result i32)
+            (call $set_int (i32.const 0) (i32.const 5) (local.get $value)))
+        (func (export "increment") (result i32)
+            (local $error i32) (local $value i64)
+            (call $get_int (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 5))
+            (local.set $value)
+            (local.set $error)
+            (if (local.get $error) (then (return (local.get $error))))
+            (call $set_int (i32.const 0) (i32.const 5) (i64.add (local.get $value) (i64.const 1))))
+        (func (export "set") (param $offset i32) (param $length i32) (result i32)
+            (local $error i32) (local $value i64)
+            (call $parse_int (local.get $offset) (local.get $length))
+            (local.set $value)
+            (local.set $error)
+            (if (local.get $error) (then (return (local.get $error))))
+            (call $set_int (i32.const 0) (i32.const 5) (local.get $value)))
+        (func (export "fail") (result i32) (i32.const 1001))
+        (func (export "trap") (result i32) unreachable))"#;
+
+    fn load(wat: &str) -> Result<Contract, Failure> {
+        Contract::new(&wat::parse_str(wat).unwrap())
+    }
+
+    fn count(contract_id: &[u8]) -> Option<DataEntry> {
+        test_env::with(|world| world.storage(contract_id, "count").cloned())
+    }
+
+    #[test]
+    fn execute() {
+        test_env::reset();
+        test_env::with(|world| {
+            world.set_contract_id(b"counter");
+        });
+        let contract = load(COUNTER).unwrap();
+
+        assert_eq!(
+            contract
+                .execute("_constructor", &[DataEntry::Integer(41)])
+                .unwrap(),
+            0
+        );
+        assert_eq!(contract.execute("increment", &[]).unwrap(), 0);
+        assert_eq!(count(b"counter"), Some(DataEntry::Integer(42)));
+
+        let params = [DataEntry::String("7".to_string())];
+        assert_eq!(contract.execute("set", &params).unwrap(), 0);
+        assert_eq!(count(b"counter"), Some(DataEntry::Integer(7)));
+    }
+
+    #[test]
+    fn errors() {
+        test_env::reset();
+        let contract = load(COUNTER).unwrap();
+
+        // Codes of the contract and of the host are returned as is
+        assert_eq!(contract.execute("fail", &[]).unwrap(), 1001);
+        let params = [DataEntry::String("seven".to_string())];
+        assert_eq!(contract.execute("set", &params).unwrap(), 308);
+
+        let failure = |func_name: &str, params: &[DataEntry]| {
+            contract.execute(func_name, params).unwrap_err().error
+        };
+        assert_eq!(failure("trap", &[]), Error::FailedExec);
+        assert_eq!(failure("unknown", &[]), Error::FuncNotFound);
+        assert_eq!(failure("increment", &params), Error::InvalidNumArgs);
+        assert_eq!(
+            failure(
+                "_constructor",
+                &[DataEntry::Boolean(true), DataEntry::Integer(1)]
+            ),
+            Error::InvalidNumArgs
+        );
+
+        let new = |wat: &str| load(wat).err().unwrap().error;
+        assert_eq!(
+            new(r#"(module (func (export "main") (result i32) i32.const 0))"#),
+            Error::ConstructorNotFound
+        );
+        assert_eq!(
+            new(
+                r#"(module (func (export "_constructor") (result i32) f32.const 1 i32.trunc_f32_s))"#
+            ),
+            Error::InvalidBytecode
+        );
+        assert_eq!(
+            load(
+                r#"(module
+                (import "env" "memory" (memory 2 16))
+                (func (export "_constructor") (result i32) i32.const 0))"#
+            )
+            .unwrap()
+            .execute("_constructor", &[])
+            .unwrap_err()
+            .error,
+            Error::HeapBaseNotFound
+        );
+    }
+
+    #[test]
+    fn call_contract() {
+        test_env::reset();
+
+        let counter = Rc::new(load(COUNTER).unwrap());
+        let caller = load(
+            r#"(module
+                (import "env" "memory" (memory 2 16))
+                (import "env0" "call_arg_int" (func $arg_int (param i64)))
+                (import "env0" "call_contract" (func $call (param i32 i32 i32 i32) (result i32)))
+                (global (export "__heap_base") i32 (i32.const 1024))
+                (data (i32.const 0) "counter_constructorfail")
+                (func (export "_constructor") (param $value i64) (result i32)
+                    (call $arg_int (local.get $value))
+                    (call $call (i32.const 0) (i32.const 7) (i32.const 7) (i32.const 12)))
+                (func (export "fail") (result i32)
+                    (call $call (i32.const 0) (i32.const 7) (i32.const 19) (i32.const 4))))"#,
+        )
+        .unwrap();
+
+        test_env::with(|world| {
+            world.set_contract_id(b"caller").register_contract(
+                b"counter",
+                move |call| match counter.execute(&call.func_name, &call.params) {
+                    Ok(code) => code,
+                    Err(failure) => failure.error.code(),
+                },
+            );
+        });
+
+        let params = [DataEntry::Integer(5)];
+        assert_eq!(caller.execute("_constructor", &params).unwrap(), 0);
+        assert_eq!(count(b"counter"), Some(DataEntry::Integer(5)));
+        assert_eq!(count(b"caller"), None);
+
+        // The code of the called contract is returned to the caller
+        assert_eq!(caller.execute("fail", &[]).unwrap(), 1001);
+        test_env::with(|world| {
+            assert_eq!(world.contract_id(), b"caller");
+            assert_eq!(world.calls().len(), 2);
+        });
+    }
+}
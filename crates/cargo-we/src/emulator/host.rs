@@ -0,0 +1,910 @@
+//! Host functions imported by the contract, backed by the world of `we_test_env`.
+//!
+//! The data passed by the contract is copied out of its memory for the bindings of `we_test_env`,
+//! and the results are copied back to the heap of the contract, as the node does.
+use super::Runtime;
+use wasmi::{core::Trap, errors::LinkerError, Caller, Linker};
+use we_cdk::Error;
+use we_test_env::{v0, v1};
+
+type Result<T> = std::result::Result<T, Trap>;
+
+// Shapes of the bindings sharing a host function
+type BytesResult = (i32, *const u8, usize);
+type Convert = unsafe fn(*const u8, usize) -> BytesResult;
+type Compare = unsafe fn(*const u8, usize, *const u8, usize) -> (i32, bool);
+type Slice = unsafe fn(*const u8, usize, i64) -> BytesResult;
+type Search = unsafe fn(*const u8, usize, *const u8, usize) -> (i32, i64);
+type Get = unsafe fn(*const u8, usize, *const u8, usize) -> BytesResult;
+type Set = unsafe fn(*const u8, usize, *const u8, usize) -> i32;
+
+pub fn define(linker: &mut Linker<Runtime>) -> std::result::Result<(), LinkerError> {
+    define_v0(linker)?;
+    define_v1(linker)
+}
+
+fn define_v0(linker: &mut Linker<Runtime>) -> std::result::Result<(), LinkerError> {
+    // Asset
+    linker.func_wrap(
+        "env0",
+        "get_balance",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         offset_address: u32,
+         length_address: u32|
+         -> Result<(i32, i64)> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            let address = read(&caller, offset_address, length_address)?;
+            Ok(unsafe {
+                v0::get_balance(
+                    asset_id.as_ptr(),
+                    asset_id.len(),
+                    address.as_ptr(),
+                    address.len(),
+                )
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "transfer",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         offset_recipient: u32,
+         length_recipient: u32,
+         amount: i64|
+         -> Result<i32> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            let recipient = read(&caller, offset_recipient, length_recipient)?;
+            Ok(unsafe {
+                v0::transfer(
+                    asset_id.as_ptr(),
+                    asset_id.len(),
+                    recipient.as_ptr(),
+                    recipient.len(),
+                    amount,
+                )
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "issue",
+        |mut caller: Caller<'_, Runtime>,
+         offset_name: u32,
+         length_name: u32,
+         offset_description: u32,
+         length_description: u32,
+         quantity: i64,
+         decimals: i32,
+         is_reissuable: i32|
+         -> Result<(i32, u32, u32)> {
+            let name = read(&caller, offset_name, length_name)?;
+            let description = read(&caller, offset_description, length_description)?;
+            let result = unsafe {
+                v0::issue(
+                    name.as_ptr(),
+                    name.len(),
+                    description.as_ptr(),
+                    description.len(),
+                    quantity,
+                    decimals,
+                    is_reissuable != 0,
+                )
+            };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "burn",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         amount: i64|
+         -> Result<i32> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            Ok(unsafe { v0::burn(asset_id.as_ptr(), asset_id.len(), amount) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "reissue",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         amount: i64,
+         is_reissuable: i32|
+         -> Result<i32> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            Ok(unsafe {
+                v0::reissue(
+                    asset_id.as_ptr(),
+                    asset_id.len(),
+                    amount,
+                    is_reissuable != 0,
+                )
+            })
+        },
+    )?;
+
+    // Block
+    linker.func_wrap("env0", "get_block_timestamp", || unsafe {
+        v0::get_block_timestamp()
+    })?;
+    linker.func_wrap("env0", "get_block_height", || unsafe {
+        v0::get_block_height()
+    })?;
+
+    // Call contract
+    linker.func_wrap("env0", "call_arg_int", |value: i64| unsafe {
+        v0::call_arg_int(value)
+    })?;
+    linker.func_wrap("env0", "call_arg_bool", |value: i32| unsafe {
+        v0::call_arg_bool(value != 0)
+    })?;
+    linker.func_wrap(
+        "env0",
+        "call_arg_binary",
+        |caller: Caller<'_, Runtime>, offset_value: u32, length_value: u32| -> Result<i32> {
+            let value = read(&caller, offset_value, length_value)?;
+            Ok(unsafe { v0::call_arg_binary(value.as_ptr(), value.len()) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "call_arg_string",
+        |caller: Caller<'_, Runtime>, offset_value: u32, length_value: u32| -> Result<i32> {
+            let value = read(&caller, offset_value, length_value)?;
+            Ok(unsafe { v0::call_arg_string(value.as_ptr(), value.len()) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "call_payment",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         amount: i64|
+         -> Result<i32> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            Ok(unsafe { v0::call_payment(asset_id.as_ptr(), asset_id.len(), amount) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "call_contract",
+        |caller: Caller<'_, Runtime>,
+         offset_contract_id: u32,
+         length_contract_id: u32,
+         offset_func_name: u32,
+         length_func_name: u32|
+         -> Result<i32> {
+            let contract_id = read(&caller, offset_contract_id, length_contract_id)?;
+            let func_name = read(&caller, offset_func_name, length_func_name)?;
+            Ok(unsafe {
+                v0::call_contract(
+                    contract_id.as_ptr(),
+                    contract_id.len(),
+                    func_name.as_ptr(),
+                    func_name.len(),
+                )
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "call_contract_params",
+        |caller: Caller<'_, Runtime>,
+         offset_contract_id: u32,
+         length_contract_id: u32,
+         offset_func_name: u32,
+         length_func_name: u32,
+         offset_params: u32,
+         length_params: u32|
+         -> Result<i32> {
+            let contract_id = read(&caller, offset_contract_id, length_contract_id)?;
+            let func_name = read(&caller, offset_func_name, length_func_name)?;
+            let params = read(&caller, offset_params, length_params)?;
+            Ok(unsafe {
+                v0::call_contract_params(
+                    contract_id.as_ptr(),
+                    contract_id.len(),
+                    func_name.as_ptr(),
+                    func_name.len(),
+                    params.as_ptr(),
+                    params.len(),
+                )
+            })
+        },
+    )?;
+
+    // Converts
+    linker.func_wrap(
+        "env0",
+        "parse_int",
+        |caller: Caller<'_, Runtime>, offset: u32, length: u32| -> Result<(i32, i64)> {
+            let value = read(&caller, offset, length)?;
+            Ok(unsafe { v0::parse_int(value.as_ptr(), value.len()) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "parse_bool",
+        |caller: Caller<'_, Runtime>,
+         offset_string: u32,
+         length_string: u32|
+         -> Result<(i32, i32)> {
+            let value = read(&caller, offset_string, length_string)?;
+            Ok(boolean(unsafe {
+                v0::parse_bool(value.as_ptr(), value.len())
+            }))
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "to_bytes",
+        |mut caller: Caller<'_, Runtime>, value: i64| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v0::to_bytes(value) };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "to_int",
+        |caller: Caller<'_, Runtime>, offset: u32, length: u32| -> Result<(i32, i64)> {
+            let value = read(&caller, offset, length)?;
+            Ok(unsafe { v0::to_int(value.as_ptr(), value.len()) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "to_string_bool",
+        |mut caller: Caller<'_, Runtime>, value: i32| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v0::to_string_bool(value != 0) };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "to_string_int",
+        |mut caller: Caller<'_, Runtime>, value: i64| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v0::to_string_int(value) };
+            write(&mut caller, result)
+        },
+    )?;
+
+    // Crypto
+    let hashes: [(&str, Convert); 5] = [
+        ("fast_hash", v0::fast_hash),
+        ("secure_hash", v0::secure_hash),
+        ("blake2b256", v0::blake2b256),
+        ("keccak256", v0::keccak256),
+        ("sha256", v0::sha256),
+    ];
+    for (name, hash) in hashes {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |mut caller: Caller<'_, Runtime>,
+                  offset_bytes: u32,
+                  length_bytes: u32|
+                  -> Result<(i32, u32, u32)> {
+                let bytes = read(&caller, offset_bytes, length_bytes)?;
+                let result = unsafe { hash(bytes.as_ptr(), bytes.len()) };
+                write(&mut caller, result)
+            },
+        )?;
+    }
+    linker.func_wrap(
+        "env0",
+        "sig_verify",
+        |caller: Caller<'_, Runtime>,
+         offset_message: u32,
+         length_message: u32,
+         offset_signature: u32,
+         length_signature: u32,
+         offset_public_key: u32,
+         length_public_key: u32|
+         -> Result<(i32, i32)> {
+            let message = read(&caller, offset_message, length_message)?;
+            let signature = read(&caller, offset_signature, length_signature)?;
+            let public_key = read(&caller, offset_public_key, length_public_key)?;
+            Ok(boolean(unsafe {
+                v0::sig_verify(
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr(),
+                    signature.len(),
+                    public_key.as_ptr(),
+                    public_key.len(),
+                )
+            }))
+        },
+    )?;
+
+    // Lease
+    linker.func_wrap(
+        "env0",
+        "lease_address",
+        |mut caller: Caller<'_, Runtime>,
+         offset_address: u32,
+         length_address: u32,
+         amount: i64|
+         -> Result<(i32, u32, u32)> {
+            let address = read(&caller, offset_address, length_address)?;
+            let result = unsafe { v0::lease_address(address.as_ptr(), address.len(), amount) };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "lease_alias",
+        |mut caller: Caller<'_, Runtime>,
+         offset_alias: u32,
+         length_alias: u32,
+         amount: i64|
+         -> Result<(i32, u32, u32)> {
+            let alias = read(&caller, offset_alias, length_alias)?;
+            let result = unsafe { v0::lease_alias(alias.as_ptr(), alias.len(), amount) };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "cancel_lease",
+        |caller: Caller<'_, Runtime>, offset_lease_id: u32, length_lease_id: u32| -> Result<i32> {
+            let lease_id = read(&caller, offset_lease_id, length_lease_id)?;
+            Ok(unsafe { v0::cancel_lease(lease_id.as_ptr(), lease_id.len()) })
+        },
+    )?;
+
+    // Memory
+    let comparisons: [(&str, Compare); 3] = [
+        ("binary_equals", v0::binary_equals),
+        ("string_equals", v0::string_equals),
+        ("contains", v0::contains),
+    ];
+    for (name, compare) in comparisons {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |caller: Caller<'_, Runtime>,
+                  offset_left: u32,
+                  length_left: u32,
+                  offset_right: u32,
+                  length_right: u32|
+                  -> Result<(i32, i32)> {
+                let left = read(&caller, offset_left, length_left)?;
+                let right = read(&caller, offset_right, length_right)?;
+                Ok(boolean(unsafe {
+                    compare(left.as_ptr(), left.len(), right.as_ptr(), right.len())
+                }))
+            },
+        )?;
+    }
+    linker.func_wrap(
+        "env0",
+        "join",
+        |mut caller: Caller<'_, Runtime>,
+         offset_left: u32,
+         length_left: u32,
+         offset_right: u32,
+         length_right: u32|
+         -> Result<(i32, u32, u32)> {
+            let left = read(&caller, offset_left, length_left)?;
+            let right = read(&caller, offset_right, length_right)?;
+            let result =
+                unsafe { v0::join(left.as_ptr(), left.len(), right.as_ptr(), right.len()) };
+            write(&mut caller, result)
+        },
+    )?;
+    let slices: [(&str, Slice); 4] = [
+        ("drop", v0::drop),
+        ("drop_right", v0::drop_right),
+        ("take", v0::take),
+        ("take_right", v0::take_right),
+    ];
+    for (name, slice) in slices {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |mut caller: Caller<'_, Runtime>,
+                  offset_bytes: u32,
+                  length_bytes: u32,
+                  n: i64|
+                  -> Result<(i32, u32, u32)> {
+                let bytes = read(&caller, offset_bytes, length_bytes)?;
+                let result = unsafe { slice(bytes.as_ptr(), bytes.len(), n) };
+                write(&mut caller, result)
+            },
+        )?;
+    }
+    let searches: [(&str, Search); 2] = [
+        ("index_of", v0::index_of),
+        ("last_index_of", v0::last_index_of),
+    ];
+    for (name, search) in searches {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |caller: Caller<'_, Runtime>,
+                  offset_string: u32,
+                  length_string: u32,
+                  offset_substring: u32,
+                  length_substring: u32|
+                  -> Result<(i32, i64)> {
+                let string = read(&caller, offset_string, length_string)?;
+                let substring = read(&caller, offset_substring, length_substring)?;
+                Ok(unsafe {
+                    search(
+                        string.as_ptr(),
+                        string.len(),
+                        substring.as_ptr(),
+                        substring.len(),
+                    )
+                })
+            },
+        )?;
+    }
+
+    // Storage
+    linker.func_wrap(
+        "env0",
+        "contains_key",
+        |caller: Caller<'_, Runtime>,
+         offset_address: u32,
+         length_address: u32,
+         offset_key: u32,
+         length_key: u32|
+         -> Result<(i32, i32)> {
+            let address = read(&caller, offset_address, length_address)?;
+            let key = read(&caller, offset_key, length_key)?;
+            Ok(boolean(unsafe {
+                v0::contains_key(address.as_ptr(), address.len(), key.as_ptr(), key.len())
+            }))
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "get_storage_int",
+        |caller: Caller<'_, Runtime>,
+         offset_address: u32,
+         length_address: u32,
+         offset_key: u32,
+         length_key: u32|
+         -> Result<(i32, i64)> {
+            let address = read(&caller, offset_address, length_address)?;
+            let key = read(&caller, offset_key, length_key)?;
+            Ok(unsafe {
+                v0::get_storage_int(address.as_ptr(), address.len(), key.as_ptr(), key.len())
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "get_storage_bool",
+        |caller: Caller<'_, Runtime>,
+         offset_address: u32,
+         length_address: u32,
+         offset_key: u32,
+         length_key: u32|
+         -> Result<(i32, i32)> {
+            let address = read(&caller, offset_address, length_address)?;
+            let key = read(&caller, offset_key, length_key)?;
+            Ok(boolean(unsafe {
+                v0::get_storage_bool(address.as_ptr(), address.len(), key.as_ptr(), key.len())
+            }))
+        },
+    )?;
+    let getters: [(&str, Get); 2] = [
+        ("get_storage_binary", v0::get_storage_binary),
+        ("get_storage_string", v0::get_storage_string),
+    ];
+    for (name, get) in getters {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |mut caller: Caller<'_, Runtime>,
+                  offset_address: u32,
+                  length_address: u32,
+                  offset_key: u32,
+                  length_key: u32|
+                  -> Result<(i32, u32, u32)> {
+                let address = read(&caller, offset_address, length_address)?;
+                let key = read(&caller, offset_key, length_key)?;
+                let result =
+                    unsafe { get(address.as_ptr(), address.len(), key.as_ptr(), key.len()) };
+                write(&mut caller, result)
+            },
+        )?;
+    }
+    linker.func_wrap(
+        "env0",
+        "set_storage_int",
+        |caller: Caller<'_, Runtime>,
+         offset_key: u32,
+         length_key: u32,
+         value: i64|
+         -> Result<i32> {
+            let key = read(&caller, offset_key, length_key)?;
+            Ok(unsafe { v0::set_storage_int(key.as_ptr(), key.len(), value) })
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "set_storage_bool",
+        |caller: Caller<'_, Runtime>,
+         offset_key: u32,
+         length_key: u32,
+         value: i32|
+         -> Result<i32> {
+            let key = read(&caller, offset_key, length_key)?;
+            Ok(unsafe { v0::set_storage_bool(key.as_ptr(), key.len(), value != 0) })
+        },
+    )?;
+    let setters: [(&str, Set); 2] = [
+        ("set_storage_binary", v0::set_storage_binary),
+        ("set_storage_string", v0::set_storage_string),
+    ];
+    for (name, set) in setters {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |caller: Caller<'_, Runtime>,
+                  offset_key: u32,
+                  length_key: u32,
+                  offset_value: u32,
+                  length_value: u32|
+                  -> Result<i32> {
+                let key = read(&caller, offset_key, length_key)?;
+                let value = read(&caller, offset_value, length_value)?;
+                Ok(unsafe { set(key.as_ptr(), key.len(), value.as_ptr(), value.len()) })
+            },
+        )?;
+    }
+
+    // Tx
+    linker.func_wrap(
+        "env0",
+        "get_tx_sender",
+        |mut caller: Caller<'_, Runtime>| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v0::get_tx_sender() };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap("env0", "get_payments", || unsafe { v0::get_payments() })?;
+    linker.func_wrap(
+        "env0",
+        "get_payment_asset_id",
+        |mut caller: Caller<'_, Runtime>, number: i32| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v0::get_payment_asset_id(number) };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap("env0", "get_payment_amount", |number: i32| unsafe {
+        v0::get_payment_amount(number)
+    })?;
+
+    // Utils
+    let encodings: [(&str, Convert); 3] = [
+        ("base_58", v0::base_58),
+        ("to_base_58_string", v0::to_base_58_string),
+        ("to_le_bytes", v0::to_le_bytes),
+    ];
+    for (name, encode) in encodings {
+        linker.func_wrap(
+            "env0",
+            name,
+            move |mut caller: Caller<'_, Runtime>,
+                  offset_bytes: u32,
+                  length_bytes: u32|
+                  -> Result<(i32, u32, u32)> {
+                let bytes = read(&caller, offset_bytes, length_bytes)?;
+                let result = unsafe { encode(bytes.as_ptr(), bytes.len()) };
+                write(&mut caller, result)
+            },
+        )?;
+    }
+    linker.func_wrap(
+        "env0",
+        "caller",
+        |mut caller: Caller<'_, Runtime>| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v0::caller() };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        "env0",
+        "require",
+        |caller: Caller<'_, Runtime>, offset_message: u32, length_message: u32| -> Result<i32> {
+            let message = read(&caller, offset_message, length_message)?;
+            Ok(unsafe { v0::require(message.as_ptr(), message.len()) })
+        },
+    )?;
+
+    Ok(())
+}
+
+fn define_v1(linker: &mut Linker<Runtime>) -> std::result::Result<(), LinkerError> {
+    // Asset
+    linker.func_wrap(
+        "env1",
+        "get_balance",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         offset_holder: u32,
+         length_holder: u32,
+         type_: u32,
+         version: u32|
+         -> Result<(i32, i64)> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            let holder = read(&caller, offset_holder, length_holder)?;
+            Ok(unsafe {
+                v1::get_balance(
+                    asset_id.as_ptr(),
+                    asset_id.len(),
+                    holder.as_ptr(),
+                    holder.len(),
+                    type_,
+                    version,
+                )
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "env1",
+        "transfer",
+        |caller: Caller<'_, Runtime>,
+         offset_asset_id: u32,
+         length_asset_id: u32,
+         offset_recipient: u32,
+         length_recipient: u32,
+         type_: u32,
+         version: u32,
+         amount: i64|
+         -> Result<i32> {
+            let asset_id = read(&caller, offset_asset_id, length_asset_id)?;
+            let recipient = read(&caller, offset_recipient, length_recipient)?;
+            Ok(unsafe {
+                v1::transfer(
+                    asset_id.as_ptr(),
+                    asset_id.len(),
+                    recipient.as_ptr(),
+                    recipient.len(),
+                    type_,
+                    version,
+                    amount,
+                )
+            })
+        },
+    )?;
+    linker.func_wrap(
+        "env1",
+        "issue",
+        |mut caller: Caller<'_, Runtime>,
+         offset_name: u32,
+         length_name: u32,
+         offset_description: u32,
+         length_description: u32,
+         quantity: i64,
+         decimals: i64,
+         is_reissuable: i32|
+         -> Result<(i32, u32, u32)> {
+            let name = read(&caller, offset_name, length_name)?;
+            let description = read(&caller, offset_description, length_description)?;
+            let result = unsafe {
+                v1::issue(
+                    name.as_ptr(),
+                    name.len(),
+                    description.as_ptr(),
+                    description.len(),
+                    quantity,
+                    decimals,
+                    is_reissuable != 0,
+                )
+            };
+            write(&mut caller, result)
+        },
+    )?;
+
+    // Block
+    linker.func_wrap(
+        "env1",
+        "block",
+        |mut caller: Caller<'_, Runtime>,
+         offset_field: u32,
+         length_field: u32|
+         -> Result<(i32, u32, u32)> {
+            let field = read(&caller, offset_field, length_field)?;
+            let result = unsafe { v1::block(field.as_ptr(), field.len()) };
+            write(&mut caller, result)
+        },
+    )?;
+
+    // Tx
+    linker.func_wrap("env1", "get_payments", || unsafe { v1::get_payments() })?;
+    linker.func_wrap(
+        "env1",
+        "get_payment_asset_id",
+        |mut caller: Caller<'_, Runtime>, number: i64| -> Result<(i32, u32, u32)> {
+            let result = unsafe { v1::get_payment_asset_id(number) };
+            write(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap("env1", "get_payment_amount", |number: i64| unsafe {
+        v1::get_payment_amount(number)
+    })?;
+    linker.func_wrap(
+        "env1",
+        "tx",
+        |mut caller: Caller<'_, Runtime>,
+         offset_field: u32,
+         length_field: u32|
+         -> Result<(i32, u32, u32)> {
+            let field = read(&caller, offset_field, length_field)?;
+            let result = unsafe { v1::tx(field.as_ptr(), field.len()) };
+            write(&mut caller, result)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Copies the data passed by the contract out of its memory.
+fn read(caller: &Caller<'_, Runtime>, offset: u32, length: u32) -> Result<Vec<u8>> {
+    let memory = caller.data().memory()?;
+    let (start, end) = (offset as usize, offset as usize + length as usize);
+    memory
+        .data(caller)
+        .get(start..end)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| Trap::new(Error::MemoryError.to_string()))
+}
+
+/// Copies the data returned by the binding to the heap of the contract.
+/// The heap base is moved past the data, so that the previous results stay valid.
+fn write(
+    caller: &mut Caller<'_, Runtime>,
+    (error, offset, length): BytesResult,
+) -> Result<(i32, u32, u32)> {
+    if error != 0 {
+        return Ok((error, 0, 0));
+    }
+
+    let bytes = match length {
+        0 => &[][..],
+        _ => unsafe { std::slice::from_raw_parts(offset, length) },
+    };
+    let memory = caller.data().memory()?;
+    let heap_base = caller.data().heap_base;
+    let start = heap_base as usize;
+    memory
+        .data_mut(&mut *caller)
+        .get_mut(start..start + length)
+        .ok_or_else(|| Trap::new(Error::MemoryError.to_string()))?
+        .copy_from_slice(bytes);
+    caller.data_mut().heap_base = heap_base + length as u32;
+
+    Ok((0, heap_base, length as u32))
+}
+
+fn boolean((error, value): (i32, bool)) -> (i32, i32) {
+    (error, value as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::Contract;
+    use we_test_env::{self as test_env, DataEntry, Holder};
+
+    const WALLET: &str = r#"(module
+        (import "env" "memory" (memory 2 16))
+        (import "env0" "set_storage_binary" (func $set_binary (param i32 i32 i32 i32) (result i32)))
+        (import "env0" "set_storage_string" (func $set_string (param i32 i32 i32 i32) (result i32)))
+        (import "env0" "get_storage_binary" (func $get_binary (param i32 i32 i32 i32) (result i32 i32 i32)))
+        (import "env0" "set_storage_int" (func $set_int (param i32 i32 i64) (result i32)))
+        (import "env0" "get_balance" (func $balance (param i32 i32 i32 i32) (result i32 i64)))
+        (import "env0" "transfer" (func $transfer (param i32 i32 i32 i32 i64) (result i32)))
+        (import "env1" "transfer" (func $transfer_v1 (param i32 i32 i32 i32 i32 i32 i64) (result i32)))
+        (import "env0" "require" (func $require (param i32 i32) (result i32)))
+        (global (export "__heap_base") i32 (i32.const 1024))
+        (data (i32.const 0) "ownernamecopybalanceNot enough")
+        (func (export "_constructor") (param i32 i32 i32 i32) (result i32)
+            (local $error i32)
+            (local.set $error (call $set_binary (i32.const 0) (i32.const 5) (local.get 0) (local.get 1)))
+            (if (local.get $error) (then (return (local.get $error))))
+            (call $set_string (i32.const 5) (i32.const 4) (local.get 2) (local.get 3)))
+        (func (export "copy") (result i32)
+            (local $error i32) (local $offset i32) (local $length i32)
+            (call $get_binary (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 5))
+            (local.set $length)
+            (local.set $offset)
+            (local.set $error)
+            (if (local.get $error) (then (return (local.get $error))))
+            (call $set_binary (i32.const 9) (i32.const 4) (local.get $offset) (local.get $length)))
+        (func (export "balance") (result i32)
+            (local $error i32) (local $balance i64)
+            (call $balance (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0))
+            (local.set $balance)
+            (local.set $error)
+            (if (local.get $error) (then (return (local.get $error))))
+            (call $set_int (i32.const 13) (i32.const 7) (local.get $balance)))
+        (func (export "pay") (param i32 i32 i64) (result i32)
+            (call $transfer (i32.const 0) (i32.const 0) (local.get 0) (local.get 1) (local.get 2)))
+        (func (export "pay_contract") (param i32 i32 i64) (result i32)
+            (call $transfer_v1 (i32.const 0) (i32.const 0) (local.get 0) (local.get 1) (i32.const 1) (i32.const 1) (local.get 2)))
+        (func (export "reject") (result i32)
+            (drop (call $require (i32.const 20) (i32.const 10)))
+            (i32.const 300)))"#;
+
+    fn load() -> Contract {
+        test_env::reset();
+        test_env::with(|world| {
+            world.set_contract_id(b"wallet");
+        });
+        Contract::new(&wat::parse_str(WALLET).unwrap()).unwrap()
+    }
+
+    fn storage(key: &str) -> Option<DataEntry> {
+        test_env::with(|world| world.storage(b"wallet", key).cloned())
+    }
+
+    #[test]
+    fn storage_bindings() {
+        let contract = load();
+        let params = [
+            DataEntry::Binary(b"owner-address".to_vec()),
+            DataEntry::String("wallet".to_string()),
+        ];
+
+        assert_eq!(contract.execute("_constructor", &params).unwrap(), 0);
+        assert_eq!(
+            storage("name"),
+            Some(DataEntry::String("wallet".to_string()))
+        );
+
+        // The value is copied to the heap of the contract and written back
+        assert_eq!(contract.execute("copy", &[]).unwrap(), 0);
+        assert_eq!(storage("copy"), Some(params[0].clone()));
+    }
+
+    #[test]
+    fn transfers() {
+        let contract = load();
+        test_env::with(|world| {
+            world.set_balance(Holder::Contract(b"wallet".to_vec()), &[], 100);
+        });
+
+        let pay = |func_name: &str, recipient: &[u8], amount: i64| {
+            let params = [
+                DataEntry::Binary(recipient.to_vec()),
+                DataEntry::Integer(amount),
+            ];
+            contract.execute(func_name, &params).unwrap()
+        };
+        assert_eq!(pay("pay", b"alice", 30), 0);
+        assert_eq!(pay("pay_contract", b"vault", 50), 0);
+        assert_eq!(pay("pay", b"alice", 21), 300);
+
+        assert_eq!(contract.execute("balance", &[]).unwrap(), 0);
+        assert_eq!(storage("balance"), Some(DataEntry::Integer(20)));
+        test_env::with(|world| {
+            assert_eq!(world.balance(&Holder::Address(b"alice".to_vec()), &[]), 30);
+            assert_eq!(world.balance(&Holder::Contract(b"vault".to_vec()), &[]), 50);
+        });
+    }
+
+    #[test]
+    fn require() {
+        let contract = load();
+
+        assert_eq!(contract.execute("reject", &[]).unwrap(), 300);
+        test_env::with(|world| {
+            assert_eq!(world.require_messages(), ["Not enough"]);
+        });
+    }
+}
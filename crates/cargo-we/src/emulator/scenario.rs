@@ -0,0 +1,495 @@
+use super::Contract;
+use base58::FromBase58;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::PathBuf, rc::Rc};
+use we_cdk::Error;
+use we_test_env::{self as test_env, Block, DataEntry, Holder, Tx};
+
+const TARGET_WE: &str = "target/we";
+
+/// Contracts to load and actions to run against them, with the expected results.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    contracts: Vec<ContractConfig>,
+    #[serde(default)]
+    balances: Vec<Balance>,
+    #[serde(default)]
+    storage: Vec<StorageEntry>,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContractConfig {
+    /// Name used to refer to the contract in the scenario.
+    name: String,
+    /// Path to the bytecode, `target/we/<name>.wasm` by default.
+    path: Option<PathBuf>,
+    /// ContractId in base58, the bytes of the name by default.
+    contract_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Step {
+    contract: String,
+    function: String,
+    #[serde(default)]
+    params: Vec<Param>,
+    #[serde(default)]
+    payments: Vec<Payment>,
+    sender: Option<String>,
+    sender_public_key: Option<String>,
+    tx_id: Option<String>,
+    block: Option<BlockConfig>,
+    #[serde(default)]
+    expect: Expect,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Expect {
+    #[serde(default)]
+    code: i32,
+    #[serde(default)]
+    storage: Vec<StorageEntry>,
+    #[serde(default)]
+    balances: Vec<Balance>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "type")]
+enum Param {
+    Integer { key: String, value: i64 },
+    Boolean { key: String, value: bool },
+    Binary { key: String, value: String },
+    String { key: String, value: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Payment {
+    asset_id: Option<String>,
+    amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockConfig {
+    height: i64,
+    timestamp: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageEntry {
+    contract: String,
+    #[serde(flatten)]
+    entry: Param,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Balance {
+    #[serde(flatten)]
+    holder: HolderConfig,
+    asset_id: Option<String>,
+    amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum HolderConfig {
+    Address(String),
+    Alias(String),
+    Contract(String),
+}
+
+/// Contracts loaded by the scenario, referred to by name or by base58 ContractId.
+struct Contracts {
+    ids: HashMap<String, Vec<u8>>,
+    loaded: HashMap<Vec<u8>, Rc<Contract>>,
+}
+
+impl Contracts {
+    fn resolve(&self, contract: &str) -> Vec<u8> {
+        match self.ids.get(contract) {
+            Some(contract_id) => contract_id.clone(),
+            None => decode_base58(contract),
+        }
+    }
+}
+
+impl Scenario {
+    pub fn new(json: &str) -> Self {
+        serde_json::from_str(json).expect("Can't parse scenario")
+    }
+
+    /// Runs the steps in order against a fresh world and prints the outcome of each.
+    /// Returns the number of failed steps.
+    pub fn run(&self) -> usize {
+        test_env::reset();
+
+        let mut contracts = Contracts {
+            ids: HashMap::new(),
+            loaded: HashMap::new(),
+        };
+
+        for config in self.contracts.iter() {
+            let path = config
+                .path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(format!("{}/{}.wasm", TARGET_WE, config.name)));
+            let bytecode = fs::read(&path)
+                .unwrap_or_else(|_| panic!("Can't read contract {}", path.display()));
+            let contract = match Contract::new(&bytecode) {
+                Ok(contract) => Rc::new(contract),
+                Err(failure) => {
+                    println!("contract {} ... FAILED\n    {}", config.name, failure);
+                    return self.steps.len();
+                }
+            };
+
+            let contract_id = config.id();
+            let handler = contract.clone();
+            // Called contracts run in the frame set up by `call_contract!`
+            test_env::with(|world| {
+                world.register_contract(&contract_id, move |call| {
+                    match handler.execute(&call.func_name, &call.params) {
+                        Ok(code) => code,
+                        Err(failure) => failure.error.code(),
+                    }
+                });
+            });
+
+            contracts
+                .ids
+                .insert(config.name.clone(), contract_id.clone());
+            contracts.loaded.insert(contract_id, contract);
+        }
+
+        test_env::with(|world| {
+            for balance in self.balances.iter() {
+                let holder = balance.holder.resolve(&contracts);
+                world.set_balance(holder, &asset_id(&balance.asset_id), balance.amount);
+            }
+            for storage in self.storage.iter() {
+                let (key, value) = storage.entry.resolve();
+                world.set_storage(&contracts.resolve(&storage.contract), key, value);
+            }
+        });
+
+        let mut failed = 0;
+        for (number, step) in self.steps.iter().enumerate() {
+            print!(
+                "step {} {}::{} ... ",
+                number + 1,
+                step.contract,
+                step.function
+            );
+
+            let errors = step.run(&contracts);
+            if errors.is_empty() {
+                println!("ok");
+            } else {
+                println!("FAILED");
+                for error in errors {
+                    println!("    {}", error);
+                }
+                failed += 1;
+            }
+        }
+
+        failed
+    }
+}
+
+impl Step {
+    /// Executes the step and checks the expectations, returns the mismatches.
+    /// As on the node, the changes are rolled back if the execution failed.
+    fn run(&self, contracts: &Contracts) -> Vec<String> {
+        let contract_id = contracts.resolve(&self.contract);
+        let contract = match contracts.loaded.get(&contract_id) {
+            Some(contract) => contract,
+            None => return vec![format!("contract {} is not loaded", self.contract)],
+        };
+
+        let (snapshot, messages) = test_env::with(|world| {
+            world
+                .set_contract_id(&contract_id)
+                .set_caller(&[])
+                .set_tx(Tx {
+                    tx_id: self.tx_id.as_deref().map(decode_base58).unwrap_or_default(),
+                    sender: self
+                        .sender
+                        .as_deref()
+                        .map(decode_base58)
+                        .unwrap_or_default(),
+                    sender_public_key: self
+                        .sender_public_key
+                        .as_deref()
+                        .map(decode_base58)
+                        .unwrap_or_default(),
+                })
+                .clear_payments();
+            if let Some(block) = &self.block {
+                world.set_block(Block {
+                    height: block.height,
+                    timestamp: block.timestamp,
+                });
+            }
+
+            let snapshot = world.clone();
+            for payment in self.payments.iter() {
+                world.add_payment(&asset_id(&payment.asset_id), payment.amount);
+            }
+            (snapshot, world.require_messages().len())
+        });
+
+        let params: Vec<DataEntry> = self.params.iter().map(|param| param.resolve().1).collect();
+        let result = contract.execute(&self.function, &params);
+
+        let mut errors = vec![];
+        let code = match result {
+            Ok(code) => {
+                if code != self.expect.code {
+                    errors.push(format!(
+                        "expected code {}, got {}",
+                        self.expect.code,
+                        describe(code)
+                    ));
+                }
+                code
+            }
+            Err(failure) => {
+                if failure.error.code() != self.expect.code {
+                    errors.push(format!(
+                        "expected code {}, got {}",
+                        self.expect.code, failure
+                    ));
+                }
+                failure.error.code()
+            }
+        };
+
+        test_env::with(|world| {
+            let messages = world.require_messages()[messages..].to_vec();
+
+            if code != 0 {
+                *world = snapshot;
+            }
+
+            for expected in self.expect.storage.iter() {
+                let (key, value) = expected.entry.resolve();
+                let actual = world.storage(&contracts.resolve(&expected.contract), key);
+                if actual != Some(&value) {
+                    errors.push(format!(
+                        "expected {:?} at `{}` of {}, got {:?}",
+                        value, key, expected.contract, actual
+                    ));
+                }
+            }
+
+            for expected in self.expect.balances.iter() {
+                let holder = expected.holder.resolve(contracts);
+                let actual = world.balance(&holder, &asset_id(&expected.asset_id));
+                if actual != expected.amount {
+                    errors.push(format!(
+                        "expected balance {} of {:?}, got {}",
+                        expected.amount, expected.holder, actual
+                    ));
+                }
+            }
+
+            // Messages of `require!` explain the failure
+            if !errors.is_empty() {
+                errors.extend(
+                    messages
+                        .iter()
+                        .map(|message| format!("require: {}", message)),
+                );
+            }
+        });
+
+        errors
+    }
+}
+
+impl ContractConfig {
+    fn id(&self) -> Vec<u8> {
+        match &self.contract_id {
+            Some(contract_id) => decode_base58(contract_id),
+            None => self.name.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl Param {
+    fn resolve(&self) -> (&str, DataEntry) {
+        match self {
+            Param::Integer { key, value } => (key, DataEntry::Integer(*value)),
+            Param::Boolean { key, value } => (key, DataEntry::Boolean(*value)),
+            Param::Binary { key, value } => {
+                // Binary values are encoded as on the node
+                let value = value.strip_prefix("base64:").unwrap_or(value);
+                let value = general_purpose::STANDARD
+                    .decode(value)
+                    .unwrap_or_else(|_| panic!("Can't decode base64 `{}`", value));
+                (key, DataEntry::Binary(value))
+            }
+            Param::String { key, value } => (key, DataEntry::String(value.clone())),
+        }
+    }
+}
+
+impl HolderConfig {
+    fn resolve(&self, contracts: &Contracts) -> Holder {
+        match self {
+            HolderConfig::Address(address) => Holder::Address(decode_base58(address)),
+            HolderConfig::Alias(alias) => Holder::Alias(alias.clone()),
+            HolderConfig::Contract(contract) => Holder::Contract(contracts.resolve(contract)),
+        }
+    }
+}
+
+/// An empty asset id stands for the system token.
+fn asset_id(asset_id: &Option<String>) -> Vec<u8> {
+    asset_id.as_deref().map(decode_base58).unwrap_or_default()
+}
+
+/// Names the codes of the host, the codes of the contract are printed as is.
+fn describe(code: i32) -> String {
+    match Error::from(code) {
+        Error::Other(code) => code.to_string(),
+        error => error.to_string(),
+    }
+}
+
+fn decode_base58(value: &str) -> Vec<u8> {
+    value
+        .from_base58()
+        .unwrap_or_else(|_| panic!("Can't decode base58 `{}`", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const COUNTER: &str = r#"(module
+        (import "env" "memory" (memory 2 16))
+        (import "env0" "set_storage_int" (func $set_int (param i32 i32 i64) (result i32)))
+        (global (export "__heap_base") i32 (i32.const 1024))
+        (data (i32.const 0) "count")
+        (func (export "_constructor") (param $value i64) (result i32)
+            (drop (call $set_int (i32.const 0) (i32.const 5) (local.get $value)))
+            (if (result i32) (i64.lt_s (local.get $value) (i64.const 0))
+                (then (i32.const 1000))
+                (else (i32.const 0)))))"#;
+
+    fn scenario(steps: &str) -> Scenario {
+        let path = env::temp_dir().join(format!("we-scenario-{}.wasm", std::process::id()));
+        fs::write(&path, wat::parse_str(COUNTER).unwrap()).unwrap();
+
+        Scenario::new(&format!(
+            r#"{{
+                "contracts": [{{ "name": "counter", "path": {:?} }}],
+                "balances": [{{ "contract": "counter", "amount": 10 }}],
+                "steps": {}
+            }}"#,
+            path, steps
+        ))
+    }
+
+    #[test]
+    fn parse() {
+        let scenario = scenario(
+            r#"[{
+                "contract": "counter",
+                "function": "_constructor",
+                "params": [{ "type": "integer", "key": "value", "value": 1 }],
+                "payments": [{ "amount": 5 }],
+                "sender": "3NBVqYXrapgJP9atQccdBPAgJPwHDKkh6A8",
+                "block": { "height": 10, "timestamp": 1000 },
+                "expect": {
+                    "storage": [{ "contract": "counter", "type": "integer", "key": "count", "value": 1 }],
+                    "balances": [{ "contract": "counter", "amount": 15 }]
+                }
+            }]"#,
+        );
+
+        assert_eq!(scenario.contracts[0].id(), b"counter");
+        assert_eq!(
+            scenario.steps[0].params[0].resolve(),
+            ("value", DataEntry::Integer(1))
+        );
+        assert_eq!(scenario.steps[0].expect.code, 0);
+        assert!(matches!(
+            scenario.balances[0].holder,
+            HolderConfig::Contract(ref name) if name == "counter"
+        ));
+        assert_eq!(scenario.run(), 0);
+    }
+
+    #[test]
+    fn binary_params() {
+        let param: Param =
+            serde_json::from_str(r#"{ "type": "binary", "key": "id", "value": "base64:AQI=" }"#)
+                .unwrap();
+        assert_eq!(param.resolve(), ("id", DataEntry::Binary(vec![1, 2])));
+    }
+
+    #[test]
+    fn failed_assertions() {
+        let scenario = scenario(
+            r#"[
+                {
+                    "contract": "counter",
+                    "function": "_constructor",
+                    "params": [{ "type": "integer", "key": "value", "value": 2 }],
+                    "expect": {
+                        "storage": [{ "contract": "counter", "type": "integer", "key": "count", "value": 3 }],
+                        "balances": [{ "contract": "counter", "amount": 0 }]
+                    }
+                },
+                {
+                    "contract": "counter",
+                    "function": "_constructor",
+                    "params": [{ "type": "integer", "key": "value", "value": -1 }],
+                    "expect": {
+                        "code": 1000,
+                        "storage": [{ "contract": "counter", "type": "integer", "key": "count", "value": 2 }]
+                    }
+                },
+                {
+                    "contract": "counter",
+                    "function": "_constructor",
+                    "params": [{ "type": "integer", "key": "value", "value": -1 }]
+                },
+                {
+                    "contract": "counter",
+                    "function": "unknown"
+                },
+                {
+                    "contract": "missing",
+                    "function": "_constructor"
+                }
+            ]"#,
+        );
+
+        let contracts = Contracts {
+            ids: HashMap::new(),
+            loaded: HashMap::new(),
+        };
+        assert_eq!(
+            scenario.steps[4].run(&contracts),
+            ["contract missing is not loaded"]
+        );
+
+        // The second step fails as expected and its changes are rolled back
+        assert_eq!(scenario.run(), 4);
+    }
+}
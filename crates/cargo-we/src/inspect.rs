@@ -0,0 +1,403 @@
+//! Inspection of the contract bytecode by `cargo we inspect`.
+//!
+//! The host functions are checked against the ones of the emulator,
+//! which defines them with the signatures of the node.
+
+use crate::{emulator, metadata::Metadata};
+use std::{collections::HashMap, fmt};
+use wasmparser::{
+    ExternalKind, FuncType, Name, NameSectionReader, Parser, Payload, TypeRef, ValType,
+};
+
+/// Module and name of the memory provided by the node.
+const MEMORY_IMPORT: (&str, &str) = ("env", "memory");
+/// Global exported by the linker, the node writes the arguments after it.
+const HEAP_BASE: &str = "__heap_base";
+
+/// Content of the bytecode and the problems the node would reject it for.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub exports: Vec<String>,
+    pub imports: Vec<String>,
+    pub memory: Option<String>,
+    /// Name and size in bytes.
+    pub custom_sections: Vec<(String, usize)>,
+    /// Name and size in bytes of the body, the largest first.
+    pub functions: Vec<(String, usize)>,
+    pub problems: Vec<String>,
+}
+
+/// Signature of a function, rendered as `(i32, i64) -> i32`.
+struct Signature<'a>(&'a [ValType], &'a [ValType]);
+
+impl fmt::Display for Signature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let types = |types: &[ValType]| {
+            types
+                .iter()
+                .map(|ty| format!("{:?}", ty).to_lowercase())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        write!(f, "({})", types(self.0))?;
+        match self.1 {
+            [] => Ok(()),
+            [result] => write!(f, " -> {}", types(&[*result])),
+            results => write!(f, " -> ({})", types(results)),
+        }
+    }
+}
+
+impl Report {
+    /// Inspects the bytecode, the exported functions are matched against the ABI.
+    pub fn new(bytecode: &[u8], metadata: &Metadata) -> Result<Self, String> {
+        let mut report = Report::default();
+        let mut types: Vec<FuncType> = vec![];
+        // Type of the imported functions, then of the defined ones
+        let mut funcs: Vec<u32> = vec![];
+        let mut imported_funcs = 0;
+        let mut names: HashMap<u32, String> = HashMap::new();
+        let mut exports: Vec<(String, ExternalKind, u32)> = vec![];
+        let mut bodies: Vec<(u32, usize)> = vec![];
+        let mut floats: Vec<(u32, String)> = vec![];
+
+        for payload in Parser::new(0).parse_all(bytecode) {
+            match payload.map_err(|error| error.to_string())? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader.into_iter_err_on_gc_types() {
+                        types.push(ty.map_err(|error| error.to_string())?);
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import.map_err(|error| error.to_string())?;
+                        match import.ty {
+                            TypeRef::Func(ty) => {
+                                funcs.push(ty);
+                                imported_funcs += 1;
+                                report.import(import.module, import.name, &types[ty as usize]);
+                            }
+                            TypeRef::Memory(memory)
+                                if (import.module, import.name) == MEMORY_IMPORT =>
+                            {
+                                report.memory(memory.initial, memory.maximum, true);
+                            }
+                            ty => {
+                                report
+                                    .imports
+                                    .push(format!("{}.{} {:?}", import.module, import.name, ty));
+                                report.problems.push(format!(
+                                    "`{}.{}` is not a function, the node only provides functions and `{}.{}`",
+                                    import.module, import.name, MEMORY_IMPORT.0, MEMORY_IMPORT.1
+                                ));
+                            }
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for ty in reader {
+                        funcs.push(ty.map_err(|error| error.to_string())?);
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        let memory = memory.map_err(|error| error.to_string())?;
+                        report.memory(memory.initial, memory.maximum, false);
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export.map_err(|error| error.to_string())?;
+                        exports.push((export.name.to_string(), export.kind, export.index));
+                    }
+                }
+                Payload::StartSection { func, .. } => report.problems.push(format!(
+                    "function #{} is a start function, the node does not run one",
+                    func
+                )),
+                Payload::CodeSectionEntry(body) => {
+                    let index = imported_funcs + bodies.len() as u32;
+                    bodies.push((index, body.range().len()));
+
+                    let mut reader = body
+                        .get_operators_reader()
+                        .map_err(|error| error.to_string())?;
+                    while !reader.eof() {
+                        let operator =
+                            format!("{:?}", reader.read().map_err(|error| error.to_string())?);
+                        if operator.contains("F32") || operator.contains("F64") {
+                            let operator = operator.split([' ', '{']).next().unwrap_or_default();
+                            floats.push((index, operator.to_string()));
+                            break;
+                        }
+                    }
+                }
+                Payload::CustomSection(section) => {
+                    report
+                        .custom_sections
+                        .push((section.name().to_string(), section.data().len()));
+
+                    if section.name() == "name" {
+                        let reader = NameSectionReader::new(section.data(), section.data_offset());
+                        for name in reader.into_iter().flatten() {
+                            if let Name::Function(map) = name {
+                                for naming in map.into_iter().flatten() {
+                                    names.insert(naming.index, naming.name.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // Stripped bytecode has no names section, the exported functions are still named
+        for (export, kind, index) in exports.iter() {
+            if *kind == ExternalKind::Func {
+                names.entry(*index).or_insert_with(|| export.clone());
+            }
+        }
+
+        let name = |index: u32| match names.get(&index) {
+            Some(name) => name.clone(),
+            None => format!("#{}", index),
+        };
+        let signature = |index: u32| {
+            let ty = &types[funcs[index as usize] as usize];
+            Signature(ty.params(), ty.results()).to_string()
+        };
+
+        for (export, kind, index) in exports.iter() {
+            match kind {
+                ExternalKind::Func => {
+                    let abi = match metadata.func(export) {
+                        Some(_) => "",
+                        None => ", not in the ABI",
+                    };
+                    report
+                        .exports
+                        .push(format!("func {}{}{}", export, signature(*index), abi));
+                    let ty = &types[funcs[*index as usize] as usize];
+                    report.check_export(export, ty, metadata);
+                }
+                ExternalKind::Memory => {
+                    report.exports.push(format!("memory {}", export));
+                    report.problems.push(format!(
+                        "memory is exported as `{}`, it must be imported from `{}.{}`",
+                        export, MEMORY_IMPORT.0, MEMORY_IMPORT.1
+                    ));
+                }
+                kind => report
+                    .exports
+                    .push(format!("{:?} {}", kind, export).to_lowercase()),
+            }
+        }
+
+        for func in metadata.funcs() {
+            if !exports
+                .iter()
+                .any(|(export, kind, _)| *kind == ExternalKind::Func && *export == func.name)
+            {
+                report
+                    .problems
+                    .push(format!("`{}` of the ABI is not exported", func));
+            }
+        }
+        if !exports.iter().any(|(export, _, _)| export == HEAP_BASE) {
+            report
+                .problems
+                .push(format!("`{}` is not exported", HEAP_BASE));
+        }
+        if report.memory.is_none() {
+            report.problems.push(format!(
+                "memory is not imported from `{}.{}`",
+                MEMORY_IMPORT.0, MEMORY_IMPORT.1
+            ));
+        }
+
+        for (index, operator) in floats {
+            report.problems.push(format!(
+                "function `{}` uses floating-point instruction `{}`",
+                name(index),
+                operator
+            ));
+        }
+
+        bodies.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        report.functions = bodies
+            .into_iter()
+            .map(|(index, size)| (name(index), size))
+            .collect();
+
+        Ok(report)
+    }
+
+    /// Checks that the host function is defined by the node with the same signature.
+    fn import(&mut self, module: &str, name: &str, ty: &FuncType) {
+        let signature = Signature(ty.params(), ty.results()).to_string();
+        self.imports
+            .push(format!("{}.{}{}", module, name, signature));
+
+        let host = |module: &str| {
+            emulator::host_function(module, name).map(|host| {
+                let params: Vec<ValType> = host.params().iter().map(val_type).collect();
+                let results: Vec<ValType> = host.results().iter().map(val_type).collect();
+                Signature(&params, &results).to_string()
+            })
+        };
+
+        match host(module) {
+            Some(host) if host == signature => (),
+            Some(host) => self.problems.push(format!(
+                "`{}.{}` is imported as {}, the node defines it as {}",
+                module, name, signature, host
+            )),
+            None => {
+                let problem = match emulator::HOST_MODULES
+                    .iter()
+                    .find(|other| **other != module && host(other).is_some())
+                {
+                    Some(other) => format!(
+                        "`{}.{}` is not a host function, it is defined in `{}`",
+                        module, name, other
+                    ),
+                    None => format!("`{}.{}` is not a host function", module, name),
+                };
+                self.problems.push(problem);
+            }
+        }
+    }
+
+    /// The node passes the arguments declared in the ABI and expects a code of `i32`.
+    fn check_export(&mut self, export: &str, ty: &FuncType, metadata: &Metadata) {
+        let func = match metadata.func(export) {
+            Some(func) => func,
+            None => return,
+        };
+
+        let params: usize = func
+            .args
+            .iter()
+            .map(|arg| match arg.ty.as_str() {
+                "Binary" | "String" => 2,
+                _ => 1,
+            })
+            .sum();
+        if ty.params().len() != params || ty.results() != [ValType::I32] {
+            self.problems.push(format!(
+                "`{}` is exported as {}, which does not match `{}` of the ABI",
+                export,
+                Signature(ty.params(), ty.results()),
+                func
+            ));
+        }
+    }
+
+    /// Memory limits in pages, the node provides the memory of `cargo we build`.
+    fn memory(&mut self, initial: u64, maximum: Option<u64>, imported: bool) {
+        let maximum_pages = maximum.map_or("none".to_string(), |maximum| maximum.to_string());
+        self.memory = Some(format!(
+            "{} pages initial, {} maximum",
+            initial, maximum_pages
+        ));
+
+        if !imported {
+            self.problems.push(format!(
+                "memory is defined by the contract, it must be imported from `{}.{}`",
+                MEMORY_IMPORT.0, MEMORY_IMPORT.1
+            ));
+        }
+        let (node_initial, node_maximum) = emulator::MEMORY;
+        if initial > node_initial as u64
+            || maximum.is_some_and(|maximum| maximum < node_maximum as u64)
+        {
+            self.problems.push(format!(
+                "memory limits do not fit the {} to {} pages provided by the node",
+                node_initial, node_maximum
+            ));
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn val_type(ty: &wasmi::core::ValueType) -> ValType {
+    match ty {
+        wasmi::core::ValueType::I32 => ValType::I32,
+        wasmi::core::ValueType::I64 => ValType::I64,
+        wasmi::core::ValueType::F32 => ValType::F32,
+        wasmi::core::ValueType::F64 => ValType::F64,
+        wasmi::core::ValueType::FuncRef => ValType::FUNCREF,
+        wasmi::core::ValueType::ExternRef => ValType::EXTERNREF,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspect(wat: &str) -> Report {
+        let bytecode = wat::parse_str(wat).unwrap();
+        let metadata = Metadata::new("test", &bytecode).unwrap();
+        Report::new(&bytecode, &metadata).unwrap()
+    }
+
+    #[test]
+    fn valid() {
+        let report = inspect(
+            r#"(module
+                (import "env" "memory" (memory 2 16))
+                (import "env0" "get_block_height" (func (result i32 i64)))
+                (func (export "_constructor") (result i32) i32.const 0)
+                (global (export "__heap_base") i32 (i32.const 1024)))"#,
+        );
+
+        assert!(report.is_valid(), "{:?}", report.problems);
+        assert_eq!(report.imports, ["env0.get_block_height() -> (i32, i64)"]);
+        assert_eq!(
+            report.exports,
+            [
+                "func _constructor() -> i32, not in the ABI",
+                "global __heap_base"
+            ]
+        );
+        assert_eq!(
+            report.memory.as_deref(),
+            Some("2 pages initial, 16 maximum")
+        );
+        assert_eq!(report.functions, [("_constructor".to_string(), 4)]);
+    }
+
+    #[test]
+    fn rejected() {
+        let report = inspect(
+            r#"(module
+                (import "env0" "get_block_height" (func (result i32)))
+                (import "env1" "get_block_height" (func (result i32 i64)))
+                (import "env0" "unknown" (func))
+                (memory (export "memory") 32)
+                (func $float (result i32) f32.const 1 i32.trunc_f32_s)
+                (start $start)
+                (func $start))"#,
+        );
+
+        assert_eq!(
+            report.problems,
+            [
+                "`env0.get_block_height` is imported as () -> i32, the node defines it as () -> (i32, i64)",
+                "`env1.get_block_height` is not a host function, it is defined in `env0`",
+                "`env0.unknown` is not a host function",
+                "memory is defined by the contract, it must be imported from `env.memory`",
+                "memory limits do not fit the 2 to 16 pages provided by the node",
+                "function #4 is a start function, the node does not run one",
+                "memory is exported as `memory`, it must be imported from `env.memory`",
+                "`__heap_base` is not exported",
+                "function `float` uses floating-point instruction `F32Const`",
+            ]
+        );
+    }
+}
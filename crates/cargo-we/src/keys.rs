@@ -0,0 +1,254 @@
+//! Accounts of the developer, kept in a keystore on disk.
+//!
+//! The keys are encrypted with ChaCha20-Poly1305 under a key derived
+//! from the password with Argon2id, the addresses are kept in clear to be listed.
+
+use crate::crypto::{self, PrivateKey};
+use argon2::Argon2;
+use base58::ToBase58;
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::{aead::Aead, ChaCha20Poly1305, KeyInit};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    path::PathBuf,
+};
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+/// Account stored in the keystore.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub name: String,
+    pub chain_id: char,
+    pub address: String,
+    pub public_key: String,
+    salt: String,
+    nonce: String,
+    secret: String,
+}
+
+/// Encrypted part of the account.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Secret {
+    /// Missing for the accounts imported by private key.
+    pub seed_phrase: Option<String>,
+    pub private_key: String,
+}
+
+impl Secret {
+    pub fn from_seed(seed_phrase: String) -> Self {
+        Secret {
+            private_key: PrivateKey::from_seed(&seed_phrase).to_base58(),
+            seed_phrase: Some(seed_phrase),
+        }
+    }
+
+    pub fn from_private_key(private_key: String) -> Result<Self, String> {
+        PrivateKey::from_base58(&private_key)?;
+
+        Ok(Secret {
+            seed_phrase: None,
+            private_key,
+        })
+    }
+
+    pub fn private_key(&self) -> PrivateKey {
+        PrivateKey::from_base58(&self.private_key).expect("Keystore holds a valid key")
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Keystore {
+    accounts: Vec<Account>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Keystore {
+    /// Opens the keystore at `WE_KEYSTORE`, `~/.cargo-we/keystore.json` by default.
+    pub fn open() -> Self {
+        let path = match env::var_os("WE_KEYSTORE") {
+            Some(path) => PathBuf::from(path),
+            None => dirs::home_dir()
+                .expect("Unable to find the home directory")
+                .join(".cargo-we")
+                .join("keystore.json"),
+        };
+
+        let mut keystore: Keystore = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).expect("Can't parse keystore"),
+            Err(_) => Keystore::default(),
+        };
+        keystore.path = path;
+        keystore
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Account, String> {
+        self.accounts
+            .iter()
+            .find(|account| account.name == name)
+            .ok_or_else(|| format!("no account `{}` in {}", name, self.path.display()))
+    }
+
+    /// Encrypts and saves the account.
+    pub fn add(
+        &mut self,
+        name: &str,
+        chain_id: char,
+        secret: &Secret,
+        password: &str,
+    ) -> Result<&Account, String> {
+        if self.get(name).is_ok() {
+            return Err(format!("account `{}` already exists", name));
+        }
+        if !chain_id.is_ascii() {
+            return Err(format!("chain id `{}` is not a byte", chain_id));
+        }
+
+        let public_key = secret.private_key().public_key();
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let plaintext = serde_json::to_vec(secret).expect("Unable to serialize struct to JSON");
+        let secret = cipher(password, &salt)?
+            .encrypt(&nonce.into(), plaintext.as_slice())
+            .map_err(|_| "unable to encrypt the key".to_string())?;
+
+        self.accounts.push(Account {
+            name: name.to_string(),
+            chain_id,
+            address: crypto::address(&public_key, chain_id as u8),
+            public_key: public_key.to_base58(),
+            salt: general_purpose::STANDARD.encode(salt),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            secret: general_purpose::STANDARD.encode(secret),
+        });
+        self.save()?;
+
+        Ok(self.accounts.last().expect("Account is added"))
+    }
+
+    /// Decrypts the keys of the account.
+    pub fn unlock(&self, name: &str, password: &str) -> Result<Secret, String> {
+        let account = self.get(name)?;
+        let decode = |value: &str| {
+            general_purpose::STANDARD
+                .decode(value)
+                .map_err(|_| format!("account `{}` is corrupted", name))
+        };
+
+        let nonce: [u8; NONCE_LENGTH] = decode(&account.nonce)?
+            .try_into()
+            .map_err(|_| format!("account `{}` is corrupted", name))?;
+        let plaintext = cipher(password, &decode(&account.salt)?)?
+            .decrypt(&nonce.into(), decode(&account.secret)?.as_slice())
+            .map_err(|_| format!("wrong password of account `{}`", name))?;
+
+        serde_json::from_slice(&plaintext).map_err(|_| format!("account `{}` is corrupted", name))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).expect("Unable to serialize struct to JSON");
+
+        fs::write(&self.path, json)
+            .map_err(|error| format!("can't write {}: {}", self.path.display(), error))
+    }
+}
+
+fn cipher(password: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|error| error.to_string())?;
+
+    Ok(ChaCha20Poly1305::new(&key.into()))
+}
+
+/// Reads the password from `WE_KEYSTORE_PASSWORD` or the terminal.
+pub fn password(prompt: &str) -> String {
+    match env::var("WE_KEYSTORE_PASSWORD") {
+        Ok(password) => password,
+        Err(_) => read_secret(prompt),
+    }
+}
+
+/// Reads a line without echo from the terminal, or from the piped input.
+pub fn read_secret(prompt: &str) -> String {
+    if io::stdin().is_terminal() {
+        return rpassword::prompt_password(prompt).expect("Can't read from the terminal");
+    }
+
+    let mut line = String::new();
+    io::stdin()
+        .read_line(&mut line)
+        .expect("Can't read from the input");
+    line.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED_PHRASE: &str = "blame vacant regret company chase trip grant funny brisk innocent";
+
+    #[test]
+    fn add_and_unlock() {
+        let path = env::temp_dir().join(format!("we-keystore-{}.json", std::process::id()));
+        let mut keystore = Keystore {
+            accounts: vec![],
+            path: path.clone(),
+        };
+
+        let account = keystore
+            .add(
+                "bob",
+                'T',
+                &Secret::from_seed(SEED_PHRASE.to_string()),
+                "secret",
+            )
+            .unwrap();
+        assert_eq!(account.address, "3Ms87NGAAaPWZux233TB9A3TXps4LDkyJWN");
+        assert!(keystore
+            .add(
+                "bob",
+                'T',
+                &Secret::from_seed(SEED_PHRASE.to_string()),
+                "secret"
+            )
+            .is_err());
+
+        // The keys are only readable with the password
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(!json.contains("blame"));
+        assert!(!json.contains("3j2aMHzh9azPphzuW7aF3cmUefGEQC9dcWYXYCyoPcJg"));
+
+        let secret = keystore.unlock("bob", "secret").unwrap();
+        assert_eq!(secret.seed_phrase.as_deref(), Some(SEED_PHRASE));
+        assert_eq!(
+            secret.private_key,
+            "3j2aMHzh9azPphzuW7aF3cmUefGEQC9dcWYXYCyoPcJg"
+        );
+        assert_eq!(
+            keystore.unlock("bob", "wrong").unwrap_err(),
+            "wrong password of account `bob`"
+        );
+        assert!(keystore.unlock("alice", "secret").is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,11 +1,26 @@
+mod crypto;
+mod deployment;
+mod emulator;
+mod inspect;
+mod keys;
 mod metadata;
+mod network;
 mod node;
+mod optimize;
+mod state;
+mod toolchain;
 
 use base64::{engine::general_purpose, Engine as _};
 use cargo_metadata::{Message, MetadataCommand};
 use clap::{Args, Parser, Subcommand};
-use metadata::Metadata;
+use crypto::PrivateKey;
+use deployment::Deployment;
+use emulator::scenario::Scenario;
+use keys::{Keystore, Secret};
+use metadata::{Change, Metadata};
+use network::{Network, Networks};
 use node::transactions::*;
+use node::{Auth, ContractStatus, Node};
 use sha256::digest;
 use std::{
     env,
@@ -14,9 +29,16 @@ use std::{
     io::{Error, Write},
     path::{Path, PathBuf},
     process::{Command, Stdio},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use toolchain::BuildConfig;
 
-const TARGET_WE: &str = "target/we";
+/// Directory of the built contracts in the target directory.
+const TARGET_WE: &str = "we";
+const DEFAULT_NODE_URL: &str = "http://localhost:6862";
+/// Functions listed by `cargo we inspect` without `--all`.
+const INSPECT_FUNCTIONS: usize = 20;
+const DEFAULT_CALL_FEE: u64 = 10000000;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -32,6 +54,176 @@ struct WeArgs {
     action: Action,
 }
 
+#[derive(Args, Debug)]
+struct WaitArgs {
+    /// Wait for the transaction to be mined and report the result of the execution.
+    #[clap(long, default_value_t = false)]
+    wait: bool,
+    /// Seconds to wait for the transaction.
+    #[clap(long, default_value_t = 60)]
+    timeout: u64,
+}
+
+#[derive(Args, Debug)]
+struct SignerArgs {
+    /// Private key of the sender in base58, to sign the transaction instead of the node.
+    #[clap(long, env = "WE_PRIVATE_KEY", hide_env_values = true)]
+    private_key: Option<String>,
+    /// Account of the keystore signing the transaction, it is the sender.
+    #[clap(long, conflicts_with = "private_key")]
+    account: Option<String>,
+}
+
+impl SignerArgs {
+    /// Returns the key signing the transaction locally, with the address of the sender if known.
+    /// The account of the network is used without a key or an account.
+    fn resolve(self, network: Option<&Network>) -> (Option<PrivateKey>, Option<String>) {
+        let chain_id = network.and_then(|network| network.chain_id);
+
+        if let Some(private_key) = self.private_key {
+            let private_key =
+                PrivateKey::from_base58(&private_key).unwrap_or_else(|error| fail(error));
+            let address =
+                chain_id.map(|chain_id| crypto::address(&private_key.public_key(), chain_id as u8));
+            return (Some(private_key), address);
+        }
+
+        let account = self
+            .account
+            .or_else(|| network.and_then(|network| network.account.clone()));
+
+        match account {
+            Some(name) => {
+                let keystore = Keystore::open();
+                let account = keystore.get(&name).unwrap_or_else(|error| fail(error));
+                if let Some(chain_id) = chain_id.filter(|chain_id| *chain_id != account.chain_id) {
+                    fail(format!(
+                        "account `{}` is on chain `{}`, the network is on chain `{}`",
+                        name, account.chain_id, chain_id
+                    ));
+                }
+
+                let password = keys::password(&format!("Password of `{}`: ", name));
+                let secret = keystore
+                    .unlock(&name, &password)
+                    .unwrap_or_else(|error| fail(error));
+                (Some(secret.private_key()), Some(account.address.clone()))
+            }
+            None => (None, None),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct DeployArgs {
+    /// Package of the contract, by default the one in the current directory.
+    #[clap(short, long)]
+    package: Option<String>,
+    /// Network of `we.toml` receiving the transaction, the default one otherwise.
+    #[clap(long)]
+    network: Option<String>,
+    /// Address of the sender, its key is used by the node to sign the transaction.
+    #[clap(long)]
+    sender: Option<String>,
+    /// Password of the key in the keystore of the node.
+    #[clap(long)]
+    password: Option<String>,
+    /// Fee of the transaction, by default the one of the network.
+    #[clap(long)]
+    fee: Option<u64>,
+    #[clap(long)]
+    fee_asset_id: Option<String>,
+    #[clap(flatten)]
+    signer: SignerArgs,
+    /// Seconds to wait for the transaction.
+    #[clap(long, default_value_t = 60)]
+    timeout: u64,
+}
+
+/// Sender of a transaction of `deploy` or `upgrade`.
+struct Deployer {
+    node: Node,
+    sender: String,
+    password: Option<String>,
+    private_key: Option<PrivateKey>,
+    fee: Fee,
+    timeout: u64,
+}
+
+impl DeployArgs {
+    fn deployer(self, network: &Network, type_id: u64) -> Deployer {
+        let node = connect(Some(network), None, None).expect("Network has a node");
+        let (private_key, address) = self.signer.resolve(Some(network));
+        let sender = self.sender.or(address).unwrap_or_else(|| {
+            fail("the sender is not set, use --sender or --account".to_string())
+        });
+        let fee = self
+            .fee
+            .or_else(|| network.fee.get(type_id))
+            .unwrap_or_else(|| {
+                fail("the fee is not set, use --fee or the fee of the network".to_string())
+            });
+
+        Deployer {
+            node,
+            sender,
+            password: self.password,
+            private_key,
+            fee: Fee {
+                amount: fee,
+                asset_id: self.fee_asset_id,
+            },
+            timeout: self.timeout,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum AbiAction {
+    /// Compare two ABI JSON files, fails if the new one breaks the old one.
+    #[clap(name = "diff")]
+    Diff {
+        #[clap(value_parser)]
+        old: PathBuf,
+        #[clap(value_parser)]
+        new: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum KeysAction {
+    /// Generate an account from a new seed phrase.
+    #[clap(name = "new")]
+    New {
+        name: String,
+        /// Chain id of the network of the address.
+        #[clap(long, default_value_t = 'V')]
+        chain_id: char,
+    },
+    /// Import an account from a seed phrase, or a private key in base58.
+    #[clap(name = "import")]
+    Import {
+        name: String,
+        /// Chain id of the network of the address.
+        #[clap(long, default_value_t = 'V')]
+        chain_id: char,
+        /// Read a private key instead of a seed phrase.
+        #[clap(long, default_value_t = false)]
+        private_key: bool,
+    },
+    /// List the accounts of the keystore.
+    #[clap(name = "list")]
+    List,
+    /// Show the address and the public key of an account.
+    #[clap(name = "show")]
+    Show { name: String },
+    /// Print the seed phrase and the private key of an account.
+    #[clap(name = "export")]
+    Export { name: String },
+}
+
+// Parsed once, the size of the variants does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Subcommand)]
 enum Action {
     /// Initialize a new project.
@@ -45,7 +237,17 @@ enum Action {
     },
     /// Compiles the contract.
     #[clap(name = "build")]
-    Build,
+    Build {
+        /// Keep the names of the functions and skip the optimization, for troubleshooting.
+        #[clap(long, default_value_t = false)]
+        debug: bool,
+        /// Build every contract of the workspace.
+        #[clap(long, default_value_t = false, conflicts_with = "packages")]
+        workspace: bool,
+        /// Build the contract of the package, may be repeated.
+        #[clap(short, long = "package")]
+        packages: Vec<String>,
+    },
     /// Converts from the text format to the binary format.
     #[clap(name = "wat2wasm")]
     Wat2Wasm {
@@ -62,14 +264,160 @@ enum Action {
         #[clap(short, long, value_parser)]
         output: Option<PathBuf>,
     },
+    /// Lists the content of the bytecode and checks it against the rules of the node.
+    #[clap(name = "inspect")]
+    Inspect {
+        /// Path to the wasm file, by default the one built for the project.
+        wasm: Option<PathBuf>,
+        /// The ABI JSON matched against the exports, by default the one in the bytecode.
+        #[clap(long, value_parser)]
+        abi: Option<PathBuf>,
+        /// List every function instead of the largest ones.
+        #[clap(long, default_value_t = false)]
+        all: bool,
+        /// Package of the contract, by default the one in the current directory.
+        #[clap(short, long, conflicts_with = "wasm")]
+        package: Option<String>,
+    },
+    /// Runs scenarios against the built contracts in a local interpreter.
+    #[clap(name = "test")]
+    Test {
+        /// Paths to the scenario JSON files
+        #[clap(required = true)]
+        scenarios: Vec<PathBuf>,
+    },
+    /// Calls an action of a deployed contract, the arguments are parsed with the ABI of the project.
+    #[clap(name = "call")]
+    Call {
+        /// The ContractId of the deployed contract.
+        contract_id: String,
+        /// The name of the action.
+        action: String,
+        /// The arguments of the action in the order of the ABI, binary values in base64.
+        args: Vec<String>,
+        /// The ABI JSON of the contract, by default the one of the project.
+        #[clap(long, value_parser)]
+        abi: Option<PathBuf>,
+        /// Package of the contract, by default the one in the current directory.
+        #[clap(short, long, conflicts_with = "abi")]
+        package: Option<String>,
+        #[clap(long, default_value_t = 1)]
+        contract_version: u64,
+        /// Payment attached to the call, `<amount>` of the system token or `<asset_id>:<amount>`.
+        #[clap(long = "payment")]
+        payments: Vec<ContractTransferInV1>,
+        /// Network of `we.toml` receiving the transaction, the default one otherwise.
+        #[clap(long)]
+        network: Option<String>,
+        /// URL of the node, instead of the one of the network.
+        #[clap(long)]
+        node_url: Option<String>,
+        #[clap(long)]
+        api_key: Option<String>,
+        /// Address of the sender, its key is used by the node to sign the transaction.
+        #[clap(long)]
+        sender: Option<String>,
+        /// Password of the key in the keystore of the node.
+        #[clap(long)]
+        password: Option<String>,
+        /// Fee of the transaction, by default the one of the network.
+        #[clap(long)]
+        fee: Option<u64>,
+        #[clap(long)]
+        fee_asset_id: Option<String>,
+        /// Send the transaction flag
+        #[clap(short, long, default_value_t = false)]
+        send: bool,
+        #[clap(flatten)]
+        signer: SignerArgs,
+        #[clap(flatten)]
+        wait: WaitArgs,
+    },
     /// Send tx by using Sign and Broadcast.
     #[clap(name = "tx")]
     Tx {
         /// Path to the transaction JSON configuration file
         path_json: PathBuf,
+        /// The ABI JSON checked against the params, by default the one of the project.
+        #[clap(long, value_parser)]
+        abi: Option<PathBuf>,
+        /// Package of the contract, by default the one in the current directory.
+        #[clap(short, long)]
+        package: Option<String>,
+        /// Network of `we.toml` receiving the transaction, instead of the `nodeUrl` of the file.
+        #[clap(long)]
+        network: Option<String>,
+        /// Send an UpdateContract even if it breaks the ABI of the deployed contract.
+        #[clap(long, default_value_t = false)]
+        allow_breaking: bool,
         /// Send the transaction flag
         #[clap(short, long, default_value_t = false)]
         send: bool,
+        #[clap(flatten)]
+        signer: SignerArgs,
+        #[clap(flatten)]
+        wait: WaitArgs,
+    },
+    /// Builds the contract, creates it on the network and records the deployment.
+    #[clap(name = "deploy")]
+    Deploy {
+        /// The arguments of the constructor in the order of the ABI, binary values in base64.
+        args: Vec<String>,
+        /// Payment attached to the creation, `<amount>` of the system token or `<asset_id>:<amount>`.
+        #[clap(long = "payment")]
+        payments: Vec<ContractTransferInV1>,
+        #[clap(flatten)]
+        options: DeployArgs,
+    },
+    /// Builds the contract and updates the deployed one with the new bytecode.
+    #[clap(name = "upgrade")]
+    Upgrade {
+        /// Send the update even if it breaks the ABI of the deployed contract.
+        #[clap(long, default_value_t = false)]
+        allow_breaking: bool,
+        #[clap(flatten)]
+        options: DeployArgs,
+    },
+    /// Reads the state of a deployed contract from the node.
+    #[clap(name = "state")]
+    State {
+        /// Keys to read, all keys of the contract by default.
+        keys: Vec<String>,
+        /// The ContractId, by default the one deployed on the network.
+        #[clap(long)]
+        contract_id: Option<String>,
+        /// Read only the keys starting with the prefix.
+        #[clap(long, conflicts_with = "keys")]
+        prefix: Option<String>,
+        /// Print only the keys.
+        #[clap(long, default_value_t = false)]
+        list: bool,
+        /// Print the entries as a JSON object sorted by key.
+        #[clap(long, default_value_t = false, conflicts_with = "list")]
+        json: bool,
+        /// Encoding of the binary values.
+        #[clap(long, value_enum, default_value_t = state::Encoding::Base58)]
+        encoding: state::Encoding,
+        /// Network of `we.toml` to read from, the default one otherwise.
+        #[clap(long)]
+        network: Option<String>,
+        /// URL of the node, instead of the one of the network.
+        #[clap(long)]
+        node_url: Option<String>,
+        #[clap(long)]
+        api_key: Option<String>,
+    },
+    /// Work with the ABI of the contract.
+    #[clap(name = "abi")]
+    Abi {
+        #[clap(subcommand)]
+        action: AbiAction,
+    },
+    /// Manage the accounts of the keystore.
+    #[clap(name = "keys")]
+    Keys {
+        #[clap(subcommand)]
+        action: KeysAction,
     },
 }
 
@@ -79,10 +427,164 @@ async fn main() -> Result<(), Error> {
 
     match args.action {
         Action::New { name, target_dir } => new(name, target_dir),
-        Action::Build => build(),
+        Action::Build {
+            debug,
+            workspace,
+            packages,
+        } => build(debug, workspace, packages),
         Action::Wat2Wasm { filename, output } => wat2wasm(filename, output),
         Action::Wasm2Wat { filename, output } => wasm2wat(filename, output),
-        Action::Tx { path_json, send } => tx(path_json, send).await,
+        Action::Test { scenarios } => test(scenarios),
+        Action::Inspect {
+            wasm,
+            abi,
+            all,
+            package,
+        } => inspect(wasm, abi, all, package),
+        Action::Tx {
+            path_json,
+            abi,
+            package,
+            network,
+            allow_breaking,
+            send,
+            signer,
+            wait,
+        } => {
+            tx(
+                path_json,
+                abi,
+                package,
+                network,
+                allow_breaking,
+                send,
+                signer,
+                wait,
+            )
+            .await
+        }
+        Action::Call {
+            contract_id,
+            action,
+            args,
+            abi,
+            package,
+            contract_version,
+            payments,
+            network,
+            node_url,
+            api_key,
+            sender,
+            password,
+            fee,
+            fee_asset_id,
+            send,
+            signer,
+            wait,
+        } => {
+            let networks = Networks::load().unwrap_or_else(|error| fail(error));
+            let network = networks
+                .select(network.as_deref())
+                .unwrap_or_else(|error| fail(error));
+            // The node of the project used before `we.toml`
+            let node = connect(network, node_url, api_key)
+                .unwrap_or_else(|| Node::new(DEFAULT_NODE_URL.to_string(), Auth::None));
+
+            let (private_key, address) = signer.resolve(network);
+            let sender = sender.or(address).unwrap_or_else(|| {
+                fail("the sender is not set, use --sender or --account".to_string())
+            });
+            let fee = fee
+                .or_else(|| network.and_then(|network| network.fee.call))
+                .unwrap_or(DEFAULT_CALL_FEE);
+            let path_abi = abi.unwrap_or_else(|| Contract::select(package.as_deref()).abi());
+            let metadata = read_abi(&path_abi).unwrap_or_else(|error| fail(error));
+            let params = call_params(&metadata, &action, &args).unwrap_or_else(|error| fail(error));
+            let call = CallContract {
+                contract_id,
+                contract_version,
+                call_func: action,
+                params,
+                payments,
+            };
+            let fee = Fee {
+                amount: fee,
+                asset_id: fee_asset_id,
+            };
+            let transaction = ContractTransaction::call(sender, password, call, fee);
+            check_params(&metadata, &transaction).unwrap_or_else(|error| fail(error));
+            send_transaction(node, transaction, Some(&metadata), send, private_key, wait).await
+        }
+        Action::Deploy {
+            args,
+            payments,
+            options,
+        } => deploy(args, payments, options).await,
+        Action::Upgrade {
+            allow_breaking,
+            options,
+        } => upgrade(options, allow_breaking).await,
+        Action::Abi { action } => abi(action),
+        Action::State {
+            keys,
+            contract_id,
+            prefix,
+            list,
+            json,
+            encoding,
+            network,
+            node_url,
+            api_key,
+        } => {
+            let networks = Networks::load().unwrap_or_else(|error| fail(error));
+            let network = networks
+                .select(network.as_deref())
+                .unwrap_or_else(|error| fail(error));
+            let node = connect(network, node_url, api_key)
+                .unwrap_or_else(|| Node::new(DEFAULT_NODE_URL.to_string(), Auth::None));
+            let contract_id = contract_id.unwrap_or_else(|| match network {
+                Some(network) => {
+                    Deployment::load(&network.name)
+                        .unwrap_or_else(|error| fail(error))
+                        .contract_id
+                }
+                None => fail("the contract is not set, use --contract-id".to_string()),
+            });
+
+            let mut entries = vec![];
+            if keys.is_empty() {
+                entries = node
+                    .contract_state(&contract_id)
+                    .await
+                    .unwrap_or_else(|error| fail(error.to_string()));
+                if let Some(prefix) = prefix {
+                    entries.retain(|entry| entry.key().starts_with(&prefix));
+                }
+            }
+            for key in keys.iter() {
+                match node
+                    .contract_key(&contract_id, key)
+                    .await
+                    .unwrap_or_else(|error| fail(error.to_string()))
+                {
+                    Some(entry) => entries.push(entry),
+                    None => eprintln!("warning: key `{}` is not set", key),
+                }
+            }
+
+            if json {
+                println!("{}", state::to_json(&entries, encoding));
+            } else {
+                for entry in entries.iter() {
+                    match list {
+                        true => println!("{}", entry.key()),
+                        false => println!("{}", state::render(entry, encoding)),
+                    }
+                }
+            }
+            Ok(())
+        }
+        Action::Keys { action } => keys(action),
     }
 }
 
@@ -196,71 +698,280 @@ Cargo.lock
 "#
     )?;
 
+    let mut we_toml = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(out_dir.join(network::WE_TOML))?;
+
+    write!(
+        we_toml,
+        r#"# Networks receiving the transactions of `cargo we tx` and `cargo we call`
+default-network = "local"
+
+[networks.local]
+url = "{DEFAULT_NODE_URL}"
+chain-id = "V"
+# API key read from the environment
+api-key-env = "WE_API_KEY"
+
+[networks.local.fee]
+create = 100000000
+call = {DEFAULT_CALL_FEE}
+update = 100000000
+"#
+    )?;
+
     println!("Created contract {name}");
 
     Ok(())
 }
 
-fn build() -> Result<(), Error> {
-    let metadata = MetadataCommand::new()
-        .manifest_path("Cargo.toml")
-        .exec()
-        .expect("Unable to runs `cargo metadata`");
+fn build(debug: bool, workspace: bool, packages: Vec<String>) -> Result<(), Error> {
+    let metadata = cargo_metadata();
+    let contracts = contracts(&metadata, workspace, &packages).unwrap_or_else(|error| fail(error));
+
+    build_contracts(&metadata, contracts, debug)
+}
+
+fn build_contracts(
+    metadata: &cargo_metadata::Metadata,
+    contracts: Vec<&cargo_metadata::Package>,
+    debug: bool,
+) -> Result<(), Error> {
+    let target_we = metadata.target_directory.join(TARGET_WE);
 
-    let project_name = metadata
-        .root_package()
-        .expect("Unable to get root package")
-        .name
-        .as_str();
+    fs::create_dir_all(&target_we)?;
 
-    fs::create_dir_all(TARGET_WE)?;
+    // Each contract is built with its own parameters
+    for package in contracts {
+        let config = BuildConfig::from_metadata(&metadata.workspace_metadata, &package.metadata)
+            .unwrap_or_else(|error| fail(error));
+        build_contract(package, &config, target_we.as_std_path(), debug)?;
+    }
+
+    Ok(())
+}
+
+fn build_contract(
+    package: &cargo_metadata::Package,
+    config: &BuildConfig,
+    target_we: &Path,
+    debug: bool,
+) -> Result<(), Error> {
+    let project_name = package.name.as_str();
+    config.check_toolchain().unwrap_or_else(|error| fail(error));
 
     let mut command = Command::new("cargo")
-            .args([
-                "+nightly",
-                "build",
-                "--release",
-                "--message-format=json-render-diagnostics",
-                "-Zbuild-std=std,panic_abort",
-                "--target=wasm32-unknown-unknown",
-                "--config=target.wasm32-unknown-unknown.rustflags = [\"-C\", \"target-feature=+bulk-memory,+multivalue\", \"-C\", \"link-args=--no-entry --import-memory -zstack-size=16 --initial-memory=131072 --max-memory=1048576\"]"
-            ])
-            .stdout(Stdio::piped())
-            .spawn()?;
+        .args(config.cargo_args(debug))
+        .arg(format!("--manifest-path={}", package.manifest_path))
+        .stdout(Stdio::piped())
+        .spawn()?;
 
     let reader =
         std::io::BufReader::new(command.stdout.take().expect("Failed to get a read handle"));
 
+    let mut path_wasm: Option<PathBuf> = None;
+
     for message in cargo_metadata::Message::parse_stream(reader) {
         match message.expect("Unable to get message") {
-            Message::CompilerArtifact(artifact) => {
-                if artifact.target.name == project_name && !artifact.filenames.is_empty() {
-                    if let Some(file_name) = artifact.filenames[0].file_name() {
-                        fs::rename(
-                            &artifact.filenames[0],
-                            format!("{}/{}", TARGET_WE, file_name),
-                        )?;
+            Message::CompilerArtifact(artifact)
+                if artifact.package_id == package.id && is_contract(&artifact.target) =>
+            {
+                if let Some(wasm) = artifact
+                    .filenames
+                    .iter()
+                    .find(|path| path.extension() == Some("wasm"))
+                {
+                    let path = target_we.join(format!("{}.wasm", project_name));
+                    // The artifact is a hard link to the cache of cargo, it is copied to be optimized
+                    if path.exists() {
+                        fs::remove_file(&path)?;
                     }
+                    fs::copy(wasm, &path)?;
+                    path_wasm = Some(path);
                 }
             }
-            Message::BuildFinished(finished) => {
-                if finished.success {
-                    let json = Metadata::new(project_name).as_json();
+            Message::BuildFinished(finished) if finished.success => {
+                // The ABI is read from the artifact, so it matches the exported functions
+                let path_wasm = path_wasm.as_ref().expect("Contract artifact not found");
+                let mut bytecode = fs::read(path_wasm)?;
+                let metadata = Metadata::new(project_name, &bytecode).unwrap_or_else(|error| {
+                    fail(format!(
+                        "can't read ABI of {}: {}",
+                        path_wasm.display(),
+                        error
+                    ))
+                });
+                let json = metadata.as_json();
 
-                    let mut metadata_file = fs::OpenOptions::new()
-                        .create(true)
-                        .write(true)
-                        .truncate(true)
-                        .open(format!("{}/{}.json", TARGET_WE, project_name))?;
+                // The bytecode is sent in the transactions, its fee grows with the size
+                if debug {
+                    println!("Debug build, the contract is not optimized");
+                } else {
+                    match optimize::optimize(&bytecode, &metadata) {
+                        Ok(optimized) => {
+                            println!(
+                                "Optimized {}: {} -> {} bytes",
+                                path_wasm.display(),
+                                bytecode.len(),
+                                optimized.len()
+                            );
+                            fs::write(path_wasm, &optimized)?;
+                            bytecode = optimized;
+                        }
+                        Err(error) => {
+                            eprintln!("warning: the contract is not optimized: {}", error)
+                        }
+                    }
+                }
 
-                    write!(metadata_file, "{}", json)?;
+                // The node checks the bytecode only when the contract is created
+                if let Ok(report) = inspect::Report::new(&bytecode, &metadata) {
+                    for problem in report.problems.iter() {
+                        eprintln!("warning: {}", problem);
+                    }
                 }
+
+                let mut metadata_file = fs::OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .truncate(true)
+                    .open(target_we.join(format!("{}.json", project_name)))?;
+
+                write!(metadata_file, "{}", json)?;
             }
             _ => (),
         }
     }
 
-    command.wait()?;
+    if !command.wait()?.success() {
+        fail(format!("the contract `{}` was not built", project_name));
+    }
+
+    Ok(())
+}
+
+/// Packages of the contracts selected by `--workspace` and `-p`.
+/// By default the package in the current directory, or every member of a virtual workspace.
+fn contracts<'a>(
+    metadata: &'a cargo_metadata::Metadata,
+    workspace: bool,
+    packages: &[String],
+) -> Result<Vec<&'a cargo_metadata::Package>, String> {
+    let members: Vec<&cargo_metadata::Package> = metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .collect();
+    let is_contract = |package: &&cargo_metadata::Package| package.targets.iter().any(is_contract);
+
+    if !packages.is_empty() {
+        return packages
+            .iter()
+            .map(|name| {
+                let package = members
+                    .iter()
+                    .find(|package| package.name == *name)
+                    .ok_or_else(|| format!("no package `{}` in the workspace", name))?;
+                match is_contract(package) {
+                    true => Ok(*package),
+                    false => Err(format!("package `{}` has no cdylib target", name)),
+                }
+            })
+            .collect();
+    }
+
+    match metadata.root_package() {
+        Some(package) if !workspace => match is_contract(&package) {
+            true => Ok(vec![package]),
+            false => Err(format!("package `{}` has no cdylib target", package.name)),
+        },
+        _ => {
+            let contracts: Vec<_> = members.into_iter().filter(is_contract).collect();
+            match contracts.is_empty() {
+                true => Err("no contracts in the workspace".to_string()),
+                false => Ok(contracts),
+            }
+        }
+    }
+}
+
+/// Contracts are built from the `cdylib` target, wherever its source is.
+fn is_contract(target: &cargo_metadata::Target) -> bool {
+    target.crate_types.iter().any(|ty| ty == "cdylib")
+}
+
+fn inspect(
+    wasm: Option<PathBuf>,
+    abi: Option<PathBuf>,
+    all: bool,
+    package: Option<String>,
+) -> Result<(), Error> {
+    // The name is only looked up in the package when the contract is taken from the target
+    let (wasm, name) = match wasm {
+        Some(wasm) => {
+            let name = wasm
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            (wasm, name)
+        }
+        None => {
+            let contract = Contract::select(package.as_deref());
+            (contract.wasm(), contract.name)
+        }
+    };
+    let bytecode = fs::read(&wasm).unwrap_or_else(|_| {
+        fail(format!(
+            "can't read {}, run `cargo we build`",
+            wasm.display()
+        ))
+    });
+    let metadata = match abi {
+        Some(abi) => read_abi(&abi).unwrap_or_else(|error| fail(error)),
+        None => Metadata::new(&name, &bytecode).unwrap_or_else(|error| {
+            fail(format!("can't read ABI of {}: {}", wasm.display(), error))
+        }),
+    };
+    let report = inspect::Report::new(&bytecode, &metadata).unwrap_or_else(|error| fail(error));
+
+    println!("Exports:");
+    for export in report.exports.iter() {
+        println!("  {}", export);
+    }
+    println!("Imports:");
+    for import in report.imports.iter() {
+        println!("  {}", import);
+    }
+    if let Some(memory) = &report.memory {
+        println!("Memory: {}", memory);
+    }
+    println!("Custom sections:");
+    for (name, size) in report.custom_sections.iter() {
+        println!("  {} {} bytes", name, size);
+    }
+
+    let total: usize = report.functions.iter().map(|(_, size)| size).sum();
+    let shown = if all {
+        report.functions.len()
+    } else {
+        INSPECT_FUNCTIONS
+    };
+    println!("Functions: {} bytes of {} bytes", total, bytecode.len());
+    for (name, size) in report.functions.iter().take(shown) {
+        println!("  {:>8} {}", size, name);
+    }
+    if report.functions.len() > shown {
+        println!("  ... {} more, use --all", report.functions.len() - shown);
+    }
+
+    for problem in report.problems.iter() {
+        eprintln!("error: {}", problem);
+    }
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
 
     Ok(())
 }
@@ -310,40 +1021,1054 @@ fn wasm2wat(filename: PathBuf, output: Option<PathBuf>) -> Result<(), Error> {
     Ok(())
 }
 
-async fn tx(path_json: PathBuf, send: bool) -> Result<(), Error> {
+fn test(scenarios: Vec<PathBuf>) -> Result<(), Error> {
+    let mut failed = 0;
+
+    for path in scenarios {
+        println!("\nrunning {}", path.display());
+        let file = fs::read_to_string(path).expect("Can't read file");
+        failed += Scenario::new(&file).run();
+    }
+
+    if failed > 0 {
+        println!("\ntest result: FAILED. {} steps failed", failed);
+        std::process::exit(1);
+    }
+
+    println!("\ntest result: ok");
+    Ok(())
+}
+
+// The arguments of the subcommand are passed as they are
+#[allow(clippy::too_many_arguments)]
+async fn tx(
+    path_json: PathBuf,
+    abi: Option<PathBuf>,
+    package: Option<String>,
+    network: Option<String>,
+    allow_breaking: bool,
+    send: bool,
+    signer: SignerArgs,
+    wait: WaitArgs,
+) -> Result<(), Error> {
     let file = fs::read_to_string(path_json).expect("Can't read file");
     let mut config: Config = serde_json::from_str::<Config>(&file).expect("Can't parse json");
 
-    let metadata = MetadataCommand::new()
-        .manifest_path("Cargo.toml")
-        .exec()
-        .expect("Unable to runs `cargo metadata`");
+    let networks = Networks::load().unwrap_or_else(|error| fail(error));
+    let (node_url, api_key) = match network {
+        Some(_) => (None, None),
+        None => (config.node_url.take(), config.api_key.take()),
+    };
+    let network = networks
+        .select(network.as_deref())
+        .unwrap_or_else(|error| fail(error));
+    let node = connect(network, node_url, api_key).unwrap_or_else(|| {
+        fail(format!(
+            "no node to send the transaction, set `nodeUrl` or add a network to {}",
+            network::WE_TOML
+        ))
+    });
+
+    // The project is only read for its ABI or its bytecode
+    let contract = match (&abi, config.transaction.is_call()) {
+        (Some(_), true) => None,
+        _ => Some(Contract::select(package.as_deref())),
+    };
+    let path_abi = abi.or_else(|| contract.as_ref().map(Contract::abi));
 
-    let project_name = metadata
-        .root_package()
-        .expect("Unable to get root package")
-        .name
-        .as_str();
+    let (private_key, address) = signer.resolve(network);
+    if let Some(fee) = network.and_then(|network| network.fee.get(config.transaction.type_id())) {
+        config.transaction.set_default_fee(fee);
+    }
 
-    let path_wasm = format!("{}/{}.wasm", TARGET_WE, project_name);
+    // The params of the called action are checked against the ABI,
+    // an update is checked against the ABI of the deployment as by `cargo we upgrade`
+    let metadata = match config.transaction.updated_contract() {
+        Some(contract_id) => {
+            let metadata = path_abi.and_then(|path| read_abi(&path).ok());
+            let deployment = network
+                .filter(|network| Deployment::exists(&network.name))
+                .map(|network| Deployment::load(&network.name).unwrap_or_else(|error| fail(error)))
+                .filter(|deployment| deployment.contract_id == contract_id);
+            check_update(
+                contract_id,
+                deployment.as_ref(),
+                metadata.as_ref(),
+                allow_breaking,
+            )
+            .unwrap_or_else(|error| fail(error));
+            metadata
+        }
+        None => {
+            let path_abi = path_abi.expect("ABI of a call");
+            let metadata = read_abi(&path_abi).unwrap_or_else(|error| fail(error));
+            check_params(&metadata, &config.transaction).unwrap_or_else(|error| fail(error));
+            Some(metadata)
+        }
+    };
 
-    let bytecode = fs::read(path_wasm).expect("Can't read file");
-    let bytecode_hash = digest(bytecode.clone());
+    if let Some(address) = address {
+        config.transaction.set_sender(address);
+    }
 
-    let stored_contract = StoredContractWasm {
-        bytecode: general_purpose::STANDARD.encode(&bytecode),
+    if let Some(contract) = contract.filter(|_| !config.transaction.is_call()) {
+        config
+            .transaction
+            .set_stored_contract(contract.stored_contract());
+    }
+
+    send_transaction(
+        node,
+        config.transaction,
+        metadata.as_ref(),
+        send,
+        private_key,
+        wait,
+    )
+    .await
+}
+
+async fn deploy(
+    args: Vec<String>,
+    payments: Vec<ContractTransferInV1>,
+    options: DeployArgs,
+) -> Result<(), Error> {
+    let metadata = cargo_metadata();
+    let package =
+        select_contract(&metadata, options.package.as_deref()).unwrap_or_else(|error| fail(error));
+    let networks = Networks::load().unwrap_or_else(|error| fail(error));
+    let network = select_network(&networks, options.network.as_deref());
+    if Deployment::exists(&network.name) {
+        fail(format!(
+            "the contract is already deployed on `{}`, run `cargo we upgrade`",
+            network.name
+        ));
+    }
+    let deployer = options.deployer(network, CREATE_CONTRACT);
+
+    build_contracts(&metadata, vec![package], false)?;
+    let contract = Contract::new(&metadata, package);
+    let metadata = read_abi(&contract.abi()).unwrap_or_else(|error| fail(error));
+    let params = call_params(&metadata, "_constructor", &args).unwrap_or_else(|error| fail(error));
+    let stored_contract = contract.stored_contract();
+    let bytecode_hash = stored_contract.bytecode_hash.clone();
+
+    let create = CreateContract {
+        contract_name: contract.name,
+        params,
+        payments,
+    };
+    let transaction = ContractTransaction::create(
+        deployer.sender,
+        deployer.password,
+        create,
+        stored_contract,
+        deployer.fee,
+    );
+    check_params(&metadata, &transaction).unwrap_or_else(|error| fail(error));
+
+    let tx_id = broadcast(&deployer.node, transaction, deployer.private_key)
+        .await
+        .unwrap_or_else(|| fail("the transaction was not accepted".to_string()));
+    confirm(&deployer.node, &tx_id, Some(&metadata), deployer.timeout).await;
+
+    // The id of the contract is the one of the transaction creating it
+    println!("Contract {} deployed on `{}`", tx_id, network.name);
+    Deployment {
+        network: network.name.clone(),
+        contract_id: tx_id.clone(),
+        version: 1,
         bytecode_hash,
+        tx_id,
+        abi: metadata,
+    }
+    .save()
+    .unwrap_or_else(|error| fail(error));
+
+    Ok(())
+}
+
+async fn upgrade(options: DeployArgs, allow_breaking: bool) -> Result<(), Error> {
+    let metadata = cargo_metadata();
+    let package =
+        select_contract(&metadata, options.package.as_deref()).unwrap_or_else(|error| fail(error));
+    let networks = Networks::load().unwrap_or_else(|error| fail(error));
+    let network = select_network(&networks, options.network.as_deref());
+    let mut deployment = Deployment::load(&network.name).unwrap_or_else(|error| fail(error));
+    let deployer = options.deployer(network, UPDATE_CONTRACT);
+
+    build_contracts(&metadata, vec![package], false)?;
+    let contract = Contract::new(&metadata, package);
+    let metadata = read_abi(&contract.abi()).unwrap_or_else(|error| fail(error));
+    let stored_contract = contract.stored_contract();
+    if stored_contract.bytecode_hash == deployment.bytecode_hash {
+        println!(
+            "Contract {} on `{}` is up to date",
+            deployment.contract_id, network.name
+        );
+        return Ok(());
+    }
+    let bytecode_hash = stored_contract.bytecode_hash.clone();
+
+    check_update(
+        &deployment.contract_id,
+        Some(&deployment),
+        Some(&metadata),
+        allow_breaking,
+    )
+    .unwrap_or_else(|error| fail(error));
+
+    let transaction = ContractTransaction::update(
+        deployer.sender,
+        deployer.password,
+        deployment.contract_id.clone(),
+        stored_contract,
+        deployer.fee,
+    );
+    let tx_id = broadcast(&deployer.node, transaction, deployer.private_key)
+        .await
+        .unwrap_or_else(|| fail("the transaction was not accepted".to_string()));
+    confirm(&deployer.node, &tx_id, Some(&metadata), deployer.timeout).await;
+
+    deployment.version += 1;
+    deployment.bytecode_hash = bytecode_hash;
+    deployment.tx_id = tx_id;
+    deployment.abi = metadata;
+    println!(
+        "Contract {} upgraded to version {} on `{}`",
+        deployment.contract_id, deployment.version, network.name
+    );
+    deployment.save().unwrap_or_else(|error| fail(error));
+
+    Ok(())
+}
+
+/// Compares the ABI of the update with the one of the deployed contract, prints the changes.
+/// Without the deployment or the new ABI the update is only sent with `allow_breaking`.
+fn check_update(
+    contract_id: &str,
+    deployment: Option<&Deployment>,
+    metadata: Option<&Metadata>,
+    allow_breaking: bool,
+) -> Result<(), String> {
+    let (deployment, metadata) = match (deployment, metadata) {
+        (Some(deployment), Some(metadata)) => (deployment, metadata),
+        _ if allow_breaking => return Ok(()),
+        (None, _) => {
+            return Err(format!(
+                "no deployment of contract {} is recorded on the network, \
+                 its ABI can't be checked, use --allow-breaking to send the update anyway",
+                contract_id
+            ))
+        }
+        (Some(_), None) => {
+            return Err(
+                "the ABI of the update is not found, run `cargo we build` or use --allow-breaking to send it anyway"
+                    .to_string(),
+            )
+        }
     };
-    config.transaction.set_stored_contract(stored_contract);
 
-    if send {
-        let node = node::Node::new(config.node_url, config.api_key);
-        node.transaction_sign_and_broadcast(config.transaction)
-            .await
-            .expect("Failed to execute the request")
+    let changes = deployment.abi.diff(metadata);
+    for change in changes.iter() {
+        println!("{}", change);
+    }
+    if changes.iter().any(Change::is_breaking) && !allow_breaking {
+        return Err(format!(
+            "the update breaks the ABI of version {} on `{}`, use --allow-breaking to send it anyway",
+            deployment.version, deployment.network
+        ));
+    }
+
+    Ok(())
+}
+
+fn abi(action: AbiAction) -> Result<(), Error> {
+    match action {
+        AbiAction::Diff { old, new } => {
+            let old = read_abi(&old).unwrap_or_else(|error| fail(error));
+            let new = read_abi(&new).unwrap_or_else(|error| fail(error));
+
+            let changes = old.diff(&new);
+            if changes.is_empty() {
+                println!("No changes");
+            }
+            for change in changes.iter() {
+                println!("{}", change);
+            }
+            if changes.iter().any(Change::is_breaking) {
+                std::process::exit(1);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Deployments are recorded by network, one of `we.toml` is required.
+fn select_network<'a>(networks: &'a Networks, name: Option<&str>) -> &'a Network {
+    networks
+        .select(name)
+        .unwrap_or_else(|error| fail(error))
+        .unwrap_or_else(|| {
+            fail(format!(
+                "no network to deploy to, add one to {} or use --network",
+                network::WE_TOML
+            ))
+        })
+}
+
+/// Connects to the node given explicitly, otherwise to the one of the network.
+fn connect(
+    network: Option<&Network>,
+    node_url: Option<String>,
+    api_key: Option<String>,
+) -> Option<Node> {
+    let auth = match api_key {
+        Some(api_key) => Auth::ApiKey(api_key),
+        None => match network {
+            Some(network) if node_url.is_none() => {
+                network.auth().unwrap_or_else(|error| fail(error))
+            }
+            _ => Auth::None,
+        },
+    };
+
+    node_url
+        .or_else(|| network.map(|network| network.url.clone()))
+        .map(|url| Node::new(url, auth))
+}
+
+/// Reads the ABI written by `cargo we build`, by default the one of the project.
+fn read_abi(path_abi: &Path) -> Result<Metadata, String> {
+    let json = fs::read_to_string(path_abi).map_err(|_| {
+        format!(
+            "can't read ABI {}, run `cargo we build`",
+            path_abi.display()
+        )
+    })?;
+
+    Metadata::from_json(&json)
+        .map_err(|error| format!("can't parse ABI {}: {}", path_abi.display(), error))
+}
+
+/// Builds the params of the call from the arguments of the action declared in the ABI.
+fn call_params(
+    metadata: &Metadata,
+    action: &str,
+    args: &[String],
+) -> Result<Vec<DataEntry>, String> {
+    let func = metadata
+        .func(action)
+        .ok_or_else(|| format!("action `{}` is not found in the ABI", action))?;
+
+    if func.args.len() != args.len() {
+        return Err(format!(
+            "`{}` takes {} arguments, {} given",
+            func,
+            func.args.len(),
+            args.len()
+        ));
+    }
+
+    func.args
+        .iter()
+        .zip(args)
+        .map(|(arg, value)| {
+            DataEntry::parse(&arg.name, &arg.ty, value)
+                .map_err(|error| format!("argument `{}`: {}", arg.name, error))
+        })
+        .collect()
+}
+
+/// Checks the params of the called action before the transaction reaches the node.
+fn check_params(metadata: &Metadata, transaction: &ContractTransaction) -> Result<(), String> {
+    let (action, params) = match transaction.action() {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+
+    let func = metadata
+        .func(action)
+        .ok_or_else(|| format!("action `{}` is not found in the ABI", action))?;
+
+    let errors = func.check(params);
+    if errors.is_empty() {
+        Ok(())
     } else {
-        println!("Transaction before send:{}", config.transaction);
+        Err(format!(
+            "params do not match `{}`:\n    {}",
+            func,
+            errors.join("\n    ")
+        ))
+    }
+}
+
+fn keys(action: KeysAction) -> Result<(), Error> {
+    let mut keystore = Keystore::open();
+
+    match action {
+        KeysAction::New { name, chain_id } => {
+            let seed_phrase = crypto::random_seed_phrase();
+            let password = new_password();
+            let account = keystore
+                .add(
+                    &name,
+                    chain_id,
+                    &Secret::from_seed(seed_phrase.clone()),
+                    &password,
+                )
+                .unwrap_or_else(|error| fail(error));
+
+            println!("Address: {}", account.address);
+            println!("Public key: {}", account.public_key);
+            println!(
+                "\nSeed phrase, the only way to restore the account:\n{}",
+                seed_phrase
+            );
+        }
+        KeysAction::Import {
+            name,
+            chain_id,
+            private_key,
+        } => {
+            let secret = match private_key {
+                true => Secret::from_private_key(keys::read_secret("Private key: "))
+                    .unwrap_or_else(|error| fail(error)),
+                false => Secret::from_seed(keys::read_secret("Seed phrase: ")),
+            };
+            let password = new_password();
+            let account = keystore
+                .add(&name, chain_id, &secret, &password)
+                .unwrap_or_else(|error| fail(error));
+
+            println!("Address: {}", account.address);
+        }
+        KeysAction::List => {
+            for account in keystore.accounts() {
+                println!(
+                    "{}\t{}\t{}",
+                    account.name, account.chain_id, account.address
+                );
+            }
+        }
+        KeysAction::Show { name } => {
+            let account = keystore.get(&name).unwrap_or_else(|error| fail(error));
+
+            println!("Name: {}", account.name);
+            println!("Chain id: {}", account.chain_id);
+            println!("Address: {}", account.address);
+            println!("Public key: {}", account.public_key);
+        }
+        KeysAction::Export { name } => {
+            let password = keys::password(&format!("Password of `{}`: ", name));
+            let secret = keystore
+                .unlock(&name, &password)
+                .unwrap_or_else(|error| fail(error));
+
+            if let Some(seed_phrase) = secret.seed_phrase {
+                println!("Seed phrase: {}", seed_phrase);
+            }
+            println!("Private key: {}", secret.private_key);
+        }
     }
 
     Ok(())
 }
+
+/// Reads the password of a new account, typed twice.
+fn new_password() -> String {
+    let password = keys::password("Password: ");
+    if env::var("WE_KEYSTORE_PASSWORD").is_err() && keys::password("Repeat password: ") != password
+    {
+        fail("the passwords do not match".to_string());
+    }
+    password
+}
+
+fn fail(error: String) -> ! {
+    eprintln!("error: {}", error);
+    std::process::exit(1)
+}
+
+async fn send_transaction(
+    node: Node,
+    transaction: ContractTransaction,
+    metadata: Option<&Metadata>,
+    send: bool,
+    private_key: Option<PrivateKey>,
+    wait: WaitArgs,
+) -> Result<(), Error> {
+    if !send {
+        println!("Transaction before send:{}", transaction);
+        return Ok(());
+    }
+
+    let tx_id = broadcast(&node, transaction, private_key).await;
+    if wait.wait {
+        let tx_id = tx_id.unwrap_or_else(|| fail("the transaction was not accepted".to_string()));
+        confirm(&node, &tx_id, metadata, wait.timeout).await;
+    }
+
+    Ok(())
+}
+
+/// Sends the transaction, signed locally with the private key or by the node.
+/// Returns the id of the transaction accepted by the node.
+async fn broadcast(
+    node: &Node,
+    transaction: ContractTransaction,
+    private_key: Option<PrivateKey>,
+) -> Option<String> {
+    match private_key {
+        // Signed locally, the key does not have to be in the keystore of the node
+        Some(private_key) => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64;
+            let signed_tx = transaction
+                .sign(&private_key, timestamp)
+                .unwrap_or_else(|error| fail(error));
+            node.transaction_broadcast(signed_tx).await
+        }
+        None => node.transaction_sign_and_broadcast(transaction).await,
+    }
+    .expect("Failed to execute the request")
+}
+
+/// Waits for the execution of the transaction, exits unless it succeeded.
+/// Without the ABI the codes of the contract are printed as is.
+async fn confirm(node: &Node, tx_id: &str, metadata: Option<&Metadata>, timeout: u64) {
+    println!("Waiting for transaction {}", tx_id);
+
+    let status = node
+        .wait_for_execution(tx_id, Duration::from_secs(timeout))
+        .await
+        .unwrap_or_else(|error| {
+            fail(format!(
+                "can't read the status of transaction {}: {}",
+                tx_id, error
+            ))
+        });
+
+    match execution_result(tx_id, status, metadata, timeout) {
+        Ok(message) => println!("{}", message),
+        Err((code, message)) => {
+            eprintln!("error: {}", message);
+            std::process::exit(code)
+        }
+    }
+}
+
+/// Describes the execution of the transaction, a failure comes with the exit code:
+/// 1 if the contract or the node failed, 2 if the transaction is not mined in time.
+fn execution_result(
+    tx_id: &str,
+    status: Option<ContractStatus>,
+    metadata: Option<&Metadata>,
+    timeout: u64,
+) -> Result<String, (i32, String)> {
+    match status {
+        Some(status) if status.is_success() => {
+            Ok(format!("Transaction {} executed successfully", tx_id))
+        }
+        Some(status) => {
+            let code = match (status.code, metadata) {
+                (Some(code), Some(metadata)) => metadata.describe_error(code),
+                (Some(code), None) => code.to_string(),
+                (None, _) => "no code".to_string(),
+            };
+            let mut message = format!(
+                "transaction {} failed with {} ({})",
+                tx_id, code, status.status
+            );
+            if let Some(details) = status.message {
+                message.push_str(&format!("\n    {}", details));
+            }
+            Err((1, message))
+        }
+        None => Err((
+            2,
+            format!(
+                "transaction {} is not mined after {} seconds",
+                tx_id, timeout
+            ),
+        )),
+    }
+}
+
+fn cargo_metadata() -> cargo_metadata::Metadata {
+    MetadataCommand::new()
+        .manifest_path("Cargo.toml")
+        .exec()
+        .unwrap_or_else(|error| {
+            fail(format!(
+                "can't read the Cargo.toml of the contract: {}",
+                error
+            ))
+        })
+}
+
+/// Package of the contract selected by `-p`, by default the one in the current directory
+/// or the only contract of a virtual workspace.
+fn select_contract<'a>(
+    metadata: &'a cargo_metadata::Metadata,
+    package: Option<&str>,
+) -> Result<&'a cargo_metadata::Package, String> {
+    let packages: Vec<String> = package.into_iter().map(str::to_string).collect();
+
+    match contracts(metadata, false, &packages)?.as_slice() {
+        [package] => Ok(package),
+        _ => Err("the workspace has several contracts, select one with -p".to_string()),
+    }
+}
+
+/// Contract of the project, with the files written by `cargo we build`.
+struct Contract {
+    name: String,
+    target_we: PathBuf,
+}
+
+impl Contract {
+    fn new(metadata: &cargo_metadata::Metadata, package: &cargo_metadata::Package) -> Self {
+        Self {
+            name: package.name.to_string(),
+            target_we: metadata
+                .target_directory
+                .join(TARGET_WE)
+                .into_std_path_buf(),
+        }
+    }
+
+    /// Reads the project once and selects the package of the contract.
+    fn select(package: Option<&str>) -> Self {
+        let metadata = cargo_metadata();
+        let package = select_contract(&metadata, package).unwrap_or_else(|error| fail(error));
+        Self::new(&metadata, package)
+    }
+
+    fn wasm(&self) -> PathBuf {
+        self.target_we.join(format!("{}.wasm", self.name))
+    }
+
+    fn abi(&self) -> PathBuf {
+        self.target_we.join(format!("{}.json", self.name))
+    }
+
+    /// Reads the bytecode built by `cargo we build`.
+    fn stored_contract(&self) -> StoredContractWasm {
+        let path_wasm = self.wasm();
+        let bytecode = fs::read(&path_wasm).unwrap_or_else(|_| {
+            fail(format!(
+                "can't read {}, run `cargo we build`",
+                path_wasm.display()
+            ))
+        });
+        let bytecode_hash = digest(bytecode.clone());
+
+        StoredContractWasm {
+            bytecode: general_purpose::STANDARD.encode(&bytecode),
+            bytecode_hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        Metadata::from_json(
+            r#"{"name": "bank", "abi": [
+                {"name": "_constructor", "args": [{"name": "owner", "type": "Binary"}]},
+                {"name": "withdraw", "args": [
+                    {"name": "to", "type": "Binary"},
+                    {"name": "amount", "type": "Integer"},
+                    {"name": "memo", "type": "String"},
+                    {"name": "all", "type": "Boolean"}
+                ]}
+            ], "errors": []}"#,
+        )
+        .unwrap()
+    }
+
+    fn call(func: &str, params: Vec<DataEntry>) -> ContractTransaction {
+        let call = CallContract {
+            contract_id: "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2".to_string(),
+            contract_version: 1,
+            call_func: func.to_string(),
+            params,
+            payments: vec![],
+        };
+        let fee = Fee {
+            amount: DEFAULT_CALL_FEE,
+            asset_id: None,
+        };
+        ContractTransaction::call(String::new(), None, call, fee)
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn params_of_call() {
+        let params = call_params(
+            &metadata(),
+            "withdraw",
+            &args(&["AQID", "42", "rent", "false"]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            params,
+            [
+                DataEntry::parse("to", "Binary", "AQID").unwrap(),
+                DataEntry::parse("amount", "Integer", "42").unwrap(),
+                DataEntry::parse("memo", "String", "rent").unwrap(),
+                DataEntry::parse("all", "Boolean", "false").unwrap(),
+            ]
+        );
+        assert_eq!(check_params(&metadata(), &call("withdraw", params)), Ok(()));
+    }
+
+    #[test]
+    fn args_coerced_to_abi_types() {
+        let metadata = Metadata::from_json(
+            r#"{"name": "flags", "abi": [
+                {"name": "set", "args": [
+                    {"name": "count", "type": "Integer"},
+                    {"name": "label", "type": "String"},
+                    {"name": "data", "type": "Binary"}
+                ]}
+            ], "errors": []}"#,
+        )
+        .unwrap();
+
+        // The same text is read as the type declared in the ABI
+        let params = call_params(&metadata, "set", &args(&["1", "1", "base64:AQ=="])).unwrap();
+        assert_eq!(
+            params.iter().map(DataEntry::type_name).collect::<Vec<_>>(),
+            ["integer", "string", "binary"]
+        );
+        assert_eq!(params[1], DataEntry::parse("label", "String", "1").unwrap());
+        assert_eq!(check_params(&metadata, &call("set", params)), Ok(()));
+    }
+
+    #[test]
+    fn args_not_matching_abi() {
+        let metadata = metadata();
+
+        assert_eq!(
+            call_params(&metadata, "withdraw", &args(&["AQID", "42"])),
+            Err(
+                "`withdraw(to: Binary, amount: Integer, memo: String, all: Boolean)` takes 4 arguments, 2 given"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            call_params(
+                &metadata,
+                "withdraw",
+                &args(&["AQID", "ten", "rent", "false"])
+            ),
+            Err("argument `amount`: `ten` is not an Integer".to_string())
+        );
+        assert_eq!(
+            call_params(&metadata, "withdraw", &args(&["AQID", "42", "rent", "1"])),
+            Err("argument `all`: `1` is not a Boolean".to_string())
+        );
+        assert_eq!(
+            call_params(&metadata, "_constructor", &args(&["alice!"])),
+            Err("argument `owner`: `alice!` is not a base64 Binary".to_string())
+        );
+    }
+
+    /// Package of the workspace in `/ws`, the root one in `/ws` itself.
+    fn package(name: &str, root: bool, crate_types: &[&str]) -> serde_json::Value {
+        let dir = match root {
+            true => "/ws".to_string(),
+            false => format!("/ws/{}", name),
+        };
+        serde_json::json!({
+            "name": name,
+            "version": "0.1.0",
+            "id": format!("{} 0.1.0 (path+file://{})", name, dir),
+            "source": null,
+            "dependencies": [],
+            "targets": [{
+                "name": name,
+                "kind": crate_types,
+                "crate_types": crate_types,
+                "src_path": format!("{}/src/lib.rs", dir),
+                "edition": "2021"
+            }],
+            "features": {},
+            "manifest_path": format!("{}/Cargo.toml", dir),
+            "edition": "2021"
+        })
+    }
+
+    fn workspace(packages: &[serde_json::Value]) -> cargo_metadata::Metadata {
+        serde_json::from_value(serde_json::json!({
+            "packages": packages,
+            "workspace_members": packages.iter().map(|package| &package["id"]).collect::<Vec<_>>(),
+            "resolve": null,
+            "workspace_root": "/ws",
+            "target_directory": "/ws/target",
+            "version": 1
+        }))
+        .unwrap()
+    }
+
+    fn names(contracts: Result<Vec<&cargo_metadata::Package>, String>) -> Vec<String> {
+        contracts
+            .unwrap()
+            .iter()
+            .map(|package| package.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn contracts_of_package() {
+        let metadata = workspace(&[package("flipper", true, &["cdylib"])]);
+        assert_eq!(names(contracts(&metadata, false, &[])), ["flipper"]);
+
+        let metadata = workspace(&[package("utils", true, &["lib"])]);
+        assert_eq!(
+            contracts(&metadata, false, &[]).unwrap_err(),
+            "package `utils` has no cdylib target"
+        );
+    }
+
+    #[test]
+    fn contracts_of_workspace() {
+        let metadata = workspace(&[
+            package("flipper", false, &["cdylib"]),
+            package("utils", false, &["lib"]),
+            package("token", false, &["cdylib", "rlib"]),
+        ]);
+
+        // A virtual manifest has no root package, every contract is built
+        assert_eq!(
+            names(contracts(&metadata, false, &[])),
+            ["flipper", "token"]
+        );
+        assert_eq!(names(contracts(&metadata, true, &[])), ["flipper", "token"]);
+
+        let metadata = workspace(&[
+            package("flipper", true, &["cdylib"]),
+            package("token", false, &["cdylib"]),
+        ]);
+        assert_eq!(names(contracts(&metadata, false, &[])), ["flipper"]);
+        assert_eq!(names(contracts(&metadata, true, &[])), ["flipper", "token"]);
+
+        let metadata = workspace(&[package("utils", false, &["lib"])]);
+        assert_eq!(
+            contracts(&metadata, true, &[]).unwrap_err(),
+            "no contracts in the workspace"
+        );
+    }
+
+    #[test]
+    fn contracts_selected() {
+        let metadata = workspace(&[
+            package("flipper", true, &["cdylib"]),
+            package("utils", false, &["lib"]),
+            package("token", false, &["cdylib"]),
+        ]);
+
+        assert_eq!(
+            names(contracts(&metadata, false, &args(&["token"]))),
+            ["token"]
+        );
+        // The packages are taken over `--workspace`, in the given order
+        assert_eq!(
+            names(contracts(&metadata, true, &args(&["token", "flipper"]))),
+            ["token", "flipper"]
+        );
+        assert_eq!(
+            contracts(&metadata, false, &args(&["utils"])).unwrap_err(),
+            "package `utils` has no cdylib target"
+        );
+        assert_eq!(
+            contracts(&metadata, false, &args(&["bank"])).unwrap_err(),
+            "no package `bank` in the workspace"
+        );
+    }
+
+    #[test]
+    fn contract_selected() {
+        let name =
+            |package: Result<&cargo_metadata::Package, String>| package.unwrap().name.clone();
+
+        let metadata = workspace(&[
+            package("flipper", false, &["cdylib"]),
+            package("utils", false, &["lib"]),
+        ]);
+        assert_eq!(name(select_contract(&metadata, None)), "flipper");
+
+        let metadata = workspace(&[
+            package("flipper", false, &["cdylib"]),
+            package("token", false, &["cdylib"]),
+        ]);
+        assert_eq!(name(select_contract(&metadata, Some("token"))), "token");
+        assert_eq!(
+            select_contract(&metadata, None).unwrap_err(),
+            "the workspace has several contracts, select one with -p"
+        );
+
+        let contract = Contract::new(
+            &metadata,
+            select_contract(&metadata, Some("token")).unwrap(),
+        );
+        assert_eq!(contract.wasm(), Path::new("/ws/target/we/token.wasm"));
+        assert_eq!(contract.abi(), Path::new("/ws/target/we/token.json"));
+    }
+
+    #[test]
+    fn update_checked_against_deployment() {
+        let contract_id = "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2";
+        let deployment = Deployment {
+            network: "testnet".to_string(),
+            contract_id: contract_id.to_string(),
+            version: 3,
+            bytecode_hash: String::new(),
+            tx_id: contract_id.to_string(),
+            abi: metadata(),
+        };
+        let compatible = Metadata::from_json(
+            r#"{"name": "bank", "abi": [
+                {"name": "withdraw", "args": [
+                    {"name": "recipient", "type": "Binary"},
+                    {"name": "amount", "type": "Integer"},
+                    {"name": "memo", "type": "String"},
+                    {"name": "all", "type": "Boolean"}
+                ]},
+                {"name": "deposit", "args": []}
+            ], "errors": []}"#,
+        )
+        .unwrap();
+        let breaking = Metadata::from_json(
+            r#"{"name": "bank", "abi": [
+                {"name": "withdraw", "args": [{"name": "amount", "type": "Integer"}]}
+            ], "errors": []}"#,
+        )
+        .unwrap();
+
+        assert!(check_update(contract_id, Some(&deployment), Some(&compatible), false).is_ok());
+        assert_eq!(
+            check_update(contract_id, Some(&deployment), Some(&breaking), false),
+            Err(
+                "the update breaks the ABI of version 3 on `testnet`, use --allow-breaking to send it anyway"
+                    .to_string()
+            )
+        );
+        assert!(check_update(contract_id, Some(&deployment), Some(&breaking), true).is_ok());
+
+        // Nothing to compare with, the update is only sent on demand
+        assert!(check_update(contract_id, None, Some(&compatible), false)
+            .unwrap_err()
+            .starts_with("no deployment of contract 4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2"));
+        assert!(check_update(contract_id, Some(&deployment), None, false)
+            .unwrap_err()
+            .starts_with("the ABI of the update is not found"));
+        assert!(check_update(contract_id, None, None, true).is_ok());
+    }
+
+    #[test]
+    fn execution_exit_codes() {
+        let metadata = Metadata::from_json(
+            r#"{"name": "voting", "abi": [], "errors": [
+                {"code": 1001, "name": "VotingError::Finished", "message": "Voting is over"}
+            ]}"#,
+        )
+        .unwrap();
+        let status = |status: &str, code: Option<i32>, message: Option<&str>| {
+            Some(ContractStatus {
+                status: status.to_string(),
+                code,
+                message: message.map(str::to_string),
+            })
+        };
+
+        assert_eq!(
+            execution_result("tx", status("Success", None, None), None, 60),
+            Ok("Transaction tx executed successfully".to_string())
+        );
+        assert_eq!(
+            execution_result(
+                "tx",
+                status("Error", Some(1001), Some("Code: 1001. Error: Voting is over.")),
+                Some(&metadata),
+                60
+            ),
+            Err((
+                1,
+                "transaction tx failed with 1001 VotingError::Finished: Voting is over (Error)\n    Code: 1001. Error: Voting is over."
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            execution_result("tx", status("Error", Some(1001), None), None, 60),
+            Err((1, "transaction tx failed with 1001 (Error)".to_string()))
+        );
+        assert_eq!(
+            execution_result("tx", status("Failure", Some(300), None), Some(&metadata), 60),
+            Err((
+                1,
+                "transaction tx failed with Code: 300. Error: Exception thrown during contract execution. (Failure)"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            execution_result("tx", status("Failure", None, None), None, 60),
+            Err((
+                1,
+                "transaction tx failed with no code (Failure)".to_string()
+            ))
+        );
+        assert_eq!(
+            execution_result("tx", None, None, 60),
+            Err((
+                2,
+                "transaction tx is not mined after 60 seconds".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn params_of_unknown_action() {
+        assert_eq!(
+            call_params(&metadata(), "deposit", &[]),
+            Err("action `deposit` is not found in the ABI".to_string())
+        );
+        assert_eq!(
+            check_params(&metadata(), &call("deposit", vec![])),
+            Err("action `deposit` is not found in the ABI".to_string())
+        );
+    }
+
+    #[test]
+    fn params_not_matching() {
+        let params = vec![
+            DataEntry::parse("amount", "Integer", "42").unwrap(),
+            DataEntry::parse("to", "String", "alice").unwrap(),
+        ];
+
+        assert_eq!(
+            check_params(&metadata(), &call("withdraw", params)),
+            Err([
+                "params do not match `withdraw(to: Binary, amount: Integer, memo: String, all: Boolean)`:",
+                "param #1 is `amount`, expected `to`",
+                "param `amount` is integer, expected binary",
+                "param #2 is `to`, expected `amount`",
+                "param `to` is string, expected integer",
+                "missing param `memo` of type string",
+                "missing param `all` of type boolean",
+            ]
+            .join("\n    "))
+        );
+    }
+}
@@ -1,109 +1,552 @@
-use serde::{Deserialize, Serialize};
-use std::{fs::File, io::Read};
+use crate::node::transactions::DataEntry;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use wasmparser::{Parser, Payload};
+
+/// Custom section with the exported functions, written by `#[action]`.
+pub const ABI_SECTION: &str = "we_abi";
+/// Custom section with the codes of the contract errors, written by `#[derive(ContractError)]`.
+pub const ERRORS_SECTION: &str = "we_errors";
+/// Custom section with the schemas of the types decoded by the contract, written by `#[derive(Decode)]`.
+pub const TYPES_SECTION: &str = "we_types";
+/// Action called by CreateContract.
+pub const CONSTRUCTOR: &str = "_constructor";
 
 #[derive(Serialize, Deserialize, Debug)]
-struct Arg {
+pub struct Arg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Type decoded from the `Binary` argument, described in `types`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.schema {
+            Some(schema) => write!(f, "{}: {}({})", self.name, self.ty, schema),
+            None => write!(f, "{}: {}", self.name, self.ty),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Func {
+    pub name: String,
+    pub args: Vec<Arg>,
+}
+
+impl Func {
+    /// Lists the params that do not match the arguments, which the node passes by position.
+    pub fn check(&self, params: &[DataEntry]) -> Vec<String> {
+        let mut errors = vec![];
+
+        for (number, (arg, param)) in self.args.iter().zip(params).enumerate() {
+            if param.key() != arg.name {
+                errors.push(format!(
+                    "param #{} is `{}`, expected `{}`",
+                    number + 1,
+                    param.key(),
+                    arg.name
+                ));
+            }
+            if !param.type_name().eq_ignore_ascii_case(&arg.ty) {
+                errors.push(format!(
+                    "param `{}` is {}, expected {}",
+                    param.key(),
+                    param.type_name(),
+                    arg.ty.to_lowercase()
+                ));
+            }
+        }
+
+        for arg in self.args.iter().skip(params.len()) {
+            errors.push(format!(
+                "missing param `{}` of type {}",
+                arg.name,
+                arg.ty.to_lowercase()
+            ));
+        }
+        for param in params.iter().skip(self.args.len()) {
+            errors.push(format!("unexpected param `{}`", param.key()));
+        }
+
+        errors
+    }
+}
+
+impl fmt::Display for Func {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(Arg::to_string).collect();
+        write!(f, "{}({})", self.name, args.join(", "))
+    }
+}
+
+/// Change of the ABI between two versions of a contract.
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    /// Calls made with the old ABI keep working.
+    Compatible(String),
+    /// Calls made with the old ABI fail or pass wrong arguments.
+    Breaking(String),
+}
+
+impl Change {
+    pub fn is_breaking(&self) -> bool {
+        matches!(self, Change::Breaking(_))
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Compatible(change) => write!(f, "compatible: {}", change),
+            Change::Breaking(change) => write!(f, "breaking: {}", change),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ErrorCode {
+    code: i32,
+    name: String,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Field {
     name: String,
     #[serde(rename = "type")]
     ty: String,
+    /// Version of the struct the field was added in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    since: Option<u8>,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Variant {
+    name: String,
+    fields: Vec<Field>,
+}
+
+/// Encoding of a struct or an enum, see `we_cdk::codec`.
 #[derive(Serialize, Deserialize, Debug)]
-struct Func {
+struct TypeSchema {
     name: String,
-    args: Vec<Arg>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<Field>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variants: Option<Vec<Variant>>,
+}
+
+impl TypeSchema {
+    /// Checks that the values encoded with the old schema are decoded by the new one.
+    /// Fields can only be added in a later version, variants at the end.
+    fn is_compatible(&self, new: &TypeSchema) -> bool {
+        match (&self.fields, &new.fields, &self.variants, &new.variants) {
+            (Some(old_fields), Some(new_fields), _, _) => {
+                let old_version = self.version.unwrap_or(1);
+                let kept: Vec<Field> = new_fields
+                    .iter()
+                    .filter(|field| field.since.unwrap_or(1) <= old_version)
+                    .map(|field| Field {
+                        since: None,
+                        ..field.clone()
+                    })
+                    .collect();
+                let old: Vec<Field> = old_fields
+                    .iter()
+                    .map(|field| Field {
+                        since: None,
+                        ..field.clone()
+                    })
+                    .collect();
+
+                self.version.is_some() == new.version.is_some()
+                    && new.version >= self.version
+                    && kept == old
+            }
+            (_, _, Some(old_variants), Some(new_variants)) => {
+                new_variants.starts_with(old_variants)
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Metadata {
     name: String,
     abi: Vec<Func>,
+    errors: Vec<ErrorCode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    types: Vec<TypeSchema>,
 }
 
 impl Metadata {
-    pub fn new(name: &str) -> Self {
-        let mut file = File::open("lib.rs").expect("Unable to open file");
-        let mut src = String::new();
-        file.read_to_string(&mut src).expect("Unable to read file");
+    /// Reads the metadata from the custom sections of the compiled contract.
+    pub fn new(name: &str, bytecode: &[u8]) -> Result<Self, String> {
+        let mut abi: Vec<Func> = vec![];
+        let mut errors: Vec<ErrorCode> = vec![];
+        let mut types: Vec<TypeSchema> = vec![];
 
-        let ast = syn::parse_file(&src).expect("Unable to parse file");
-        let abi = Self::parse_ast(ast);
+        for payload in Parser::new(0).parse_all(bytecode) {
+            let payload = payload.map_err(|error| format!("invalid wasm: {}", error))?;
+            if let Payload::CustomSection(section) = payload {
+                match section.name() {
+                    ABI_SECTION => {
+                        abi.extend(Self::parse_records::<Func>(section.name(), section.data())?)
+                    }
+                    ERRORS_SECTION => errors.extend(Self::parse_records::<ErrorCode>(
+                        section.name(),
+                        section.data(),
+                    )?),
+                    TYPES_SECTION => {
+                        for schema in
+                            Self::parse_records::<TypeSchema>(section.name(), section.data())?
+                        {
+                            // Types of the same name in several crates are not told apart
+                            if !types.iter().any(|known| known.name == schema.name) {
+                                types.push(schema);
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
 
-        Self {
+        Ok(Self {
             name: name.to_string(),
             abi,
-        }
+            errors,
+            types,
+        })
     }
 
-    pub fn as_json(&self) -> String {
-        serde_json::to_string(self).expect("Unable to serialize struct to JSON")
+    /// Reads the metadata written by `cargo we build`.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|error| error.to_string())
     }
 
-    fn parse_ast(ast: syn::File) -> Vec<Func> {
-        let mut result: Vec<Func> = vec![];
-
-        for item in ast.items {
-            // You only need to get the functions
-            if let syn::Item::Fn(func) = item {
-                for attr in func.attrs {
-                    // It is necessary to get functions with the attribute `action`
-                    if Self::is_ident(&attr.path, "action") {
-                        // Get the name of the function
-                        let func_name = func.sig.ident.to_string();
-
-                        let mut args: Vec<Arg> = vec![];
-
-                        // Going through all the arguments
-                        for arg in func.sig.inputs.iter() {
-                            if let syn::FnArg::Typed(a) = arg {
-                                if let syn::Pat::Ident(pat_ident) = &*a.pat {
-                                    // Get the name of the argument
-                                    let arg_name = pat_ident.ident.to_string();
-
-                                    if let syn::Type::Path(type_path) = &*a.ty {
-                                        args.push(Arg {
-                                            name: arg_name,
-                                            ty: Self::get_ty(&type_path.path)
-                                                .expect("Unable to get ty")
-                                                .to_string(),
-                                        })
-                                    }
-                                }
-                            }
-                        }
-                        // Add a function
-                        result.push(Func {
-                            name: func_name,
-                            args,
-                        });
+    /// Get the exported functions.
+    pub fn funcs(&self) -> &[Func] {
+        &self.abi
+    }
+
+    /// Get the exported function by name.
+    pub fn func(&self, name: &str) -> Option<&Func> {
+        self.abi.iter().find(|func| func.name == name)
+    }
+
+    /// Names the code returned by the contract, the codes of the host are named as in `we_cdk::Error`.
+    pub fn describe_error(&self, code: i32) -> String {
+        if let Some(error) = self.errors.iter().find(|error| error.code == code) {
+            return format!("{} {}: {}", code, error.name, error.message);
+        }
+
+        match we_cdk::Error::from(code) {
+            we_cdk::Error::Other(code) => code.to_string(),
+            error => error.to_string(),
+        }
+    }
+
+    /// Compares the ABI with the one of a new version of the contract.
+    /// The node passes the arguments by position, so only their names may change.
+    pub fn diff(&self, new: &Metadata) -> Vec<Change> {
+        let mut changes = vec![];
+
+        for old_func in self.abi.iter() {
+            let new_func = match new.func(&old_func.name) {
+                Some(new_func) => new_func,
+                None if old_func.name == CONSTRUCTOR => {
+                    changes.push(Change::Compatible(format!("removed `{}`", old_func)));
+                    continue;
+                }
+                None => {
+                    changes.push(Change::Breaking(format!("removed action `{}`", old_func)));
+                    continue;
+                }
+            };
+
+            let types = |func: &Func| {
+                func.args
+                    .iter()
+                    .map(|arg| (arg.ty.clone(), arg.schema.clone()))
+                    .collect::<Vec<_>>()
+            };
+            let names = |func: &Func| {
+                func.args
+                    .iter()
+                    .map(|arg| arg.name.clone())
+                    .collect::<Vec<_>>()
+            };
+
+            let change = format!("`{}` became `{}`", old_func, new_func);
+            if types(old_func) != types(new_func) {
+                // The constructor is only called by CreateContract
+                changes.push(match old_func.name == CONSTRUCTOR {
+                    true => Change::Compatible(change),
+                    false => Change::Breaking(change),
+                });
+            } else if names(old_func) != names(new_func) {
+                changes.push(Change::Compatible(change));
+            }
+        }
+
+        for new_func in new.abi.iter() {
+            if self.func(&new_func.name).is_none() {
+                changes.push(Change::Compatible(format!("added action `{}`", new_func)));
+            }
+        }
+
+        for old_error in self.errors.iter() {
+            match new.errors.iter().find(|error| error.code == old_error.code) {
+                None => changes.push(Change::Compatible(format!(
+                    "removed error {} `{}`",
+                    old_error.code, old_error.name
+                ))),
+                Some(new_error)
+                    if new_error.name != old_error.name
+                        || new_error.message != old_error.message =>
+                {
+                    changes.push(Change::Compatible(format!(
+                        "error {} `{}` became `{}`",
+                        old_error.code, old_error.name, new_error.name
+                    )))
+                }
+                Some(_) => (),
+            }
+        }
+
+        for new_error in new.errors.iter() {
+            if !self.errors.iter().any(|error| error.code == new_error.code) {
+                changes.push(Change::Compatible(format!(
+                    "added error {} `{}`",
+                    new_error.code, new_error.name
+                )));
+            }
+        }
+
+        // Values of the types are passed to actions and kept in the storage
+        for old_type in self.types.iter() {
+            match new.types.iter().find(|schema| schema.name == old_type.name) {
+                None => changes.push(Change::Compatible(format!(
+                    "removed type `{}`",
+                    old_type.name
+                ))),
+                Some(new_type) if old_type.is_compatible(new_type) => {
+                    if old_type.fields != new_type.fields || old_type.variants != new_type.variants
+                    {
+                        changes.push(Change::Compatible(format!(
+                            "type `{}` was extended",
+                            old_type.name
+                        )));
                     }
                 }
+                Some(_) => changes.push(Change::Breaking(format!(
+                    "the encoding of type `{}` changed",
+                    old_type.name
+                ))),
+            }
+        }
+
+        for new_type in new.types.iter() {
+            if !self.types.iter().any(|schema| schema.name == new_type.name) {
+                changes.push(Change::Compatible(format!(
+                    "added type `{}`",
+                    new_type.name
+                )));
             }
         }
 
-        result
+        changes
     }
 
-    fn get_ty(path: &syn::Path) -> Option<&syn::Ident> {
-        if let Some(ident) = Self::get_ident(path) {
-            Some(ident)
-        } else {
-            path.get_ident()
-        }
+    pub fn as_json(&self) -> String {
+        serde_json::to_string(self).expect("Unable to serialize struct to JSON")
     }
 
-    fn is_ident(path: &syn::Path, ident: &str) -> bool {
-        match Self::get_ident(path) {
-            Some(id) => id == ident,
-            None => path.is_ident(ident),
-        }
+    // The linker concatenates the records of all items, one JSON object per line
+    fn parse_records<T: DeserializeOwned>(section: &str, data: &[u8]) -> Result<Vec<T>, String> {
+        let data = std::str::from_utf8(data)
+            .map_err(|_| format!("custom section `{}` is not UTF-8", section))?;
+
+        data.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|error| {
+                    format!("invalid record in custom section `{}`: {}", section, error)
+                })
+            })
+            .collect()
     }
+}
 
-    fn get_ident(path: &syn::Path) -> Option<&syn::Ident> {
-        if path.segments.len() == 2 && path.segments[0].ident == "we_cdk" {
-            Some(&path.segments[1].ident)
-        } else {
-            None
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(abi: &str, errors: &str) -> Metadata {
+        Metadata::from_json(&format!(
+            r#"{{"name": "bank", "abi": {}, "errors": {}}}"#,
+            abi, errors
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn diff() {
+        let old = metadata(
+            r#"[
+                {"name": "_constructor", "args": []},
+                {"name": "deposit", "args": []},
+                {"name": "withdraw", "args": [{"name": "to", "type": "Binary"}, {"name": "amount", "type": "Integer"}]},
+                {"name": "mint", "args": [{"name": "quantity", "type": "Integer"}]},
+                {"name": "burn", "args": [{"name": "quantity", "type": "Integer"}]}
+            ]"#,
+            r#"[
+                {"code": 1000, "name": "BankError::NotEnough", "message": "Not enough funds"},
+                {"code": 1001, "name": "BankError::Locked", "message": "Locked"}
+            ]"#,
+        );
+        let new = metadata(
+            r#"[
+                {"name": "_constructor", "args": [{"name": "owner", "type": "Binary"}]},
+                {"name": "deposit", "args": []},
+                {"name": "withdraw", "args": [{"name": "recipient", "type": "Binary"}, {"name": "amount", "type": "Integer"}]},
+                {"name": "mint", "args": [{"name": "quantity", "type": "String"}]},
+                {"name": "transfer", "args": []}
+            ]"#,
+            r#"[
+                {"code": 1000, "name": "BankError::NotEnough", "message": "Not enough funds"},
+                {"code": 1002, "name": "BankError::Paused", "message": "Paused"}
+            ]"#,
+        );
+
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                Change::Compatible("`_constructor()` became `_constructor(owner: Binary)`".to_string()),
+                Change::Compatible(
+                    "`withdraw(to: Binary, amount: Integer)` became `withdraw(recipient: Binary, amount: Integer)`"
+                        .to_string()
+                ),
+                Change::Breaking("`mint(quantity: Integer)` became `mint(quantity: String)`".to_string()),
+                Change::Breaking("removed action `burn(quantity: Integer)`".to_string()),
+                Change::Compatible("added action `transfer()`".to_string()),
+                Change::Compatible("removed error 1001 `BankError::Locked`".to_string()),
+                Change::Compatible("added error 1002 `BankError::Paused`".to_string()),
+            ]
+        );
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn types() {
+        let old = Metadata::from_json(
+            r#"{"name": "shop", "abi": [
+                {"name": "place", "args": [{"name": "order", "type": "Binary", "schema": "Order"}]}
+            ], "errors": [], "types": [
+                {"name": "Order", "version": 1, "fields": [{"name": "amount", "type": "Integer"}]},
+                {"name": "Side", "variants": [{"name": "Buy", "fields": []}]},
+                {"name": "Item", "fields": [{"name": "id", "type": "Integer"}]}
+            ]}"#,
+        )
+        .unwrap();
+        let new = Metadata::from_json(
+            r#"{"name": "shop", "abi": [
+                {"name": "place", "args": [{"name": "order", "type": "Binary", "schema": "Order"}]}
+            ], "errors": [], "types": [
+                {"name": "Order", "version": 2, "fields": [
+                    {"name": "amount", "type": "Integer"},
+                    {"name": "expires", "type": "Option<Integer>", "since": 2}
+                ]},
+                {"name": "Side", "variants": [{"name": "Buy", "fields": []}, {"name": "Sell", "fields": []}]},
+                {"name": "Item", "fields": [{"name": "id", "type": "Integer"}, {"name": "price", "type": "Integer"}]}
+            ]}"#,
+        ).unwrap();
+
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                Change::Compatible("type `Order` was extended".to_string()),
+                Change::Compatible("type `Side` was extended".to_string()),
+                Change::Breaking("the encoding of type `Item` changed".to_string()),
+            ]
+        );
+        assert!(new.diff(&old).iter().all(Change::is_breaking));
+        assert_eq!(
+            old.func("place").unwrap().to_string(),
+            "place(order: Binary(Order))"
+        );
+    }
+
+    #[test]
+    fn check() {
+        let metadata = metadata(
+            r#"[{"name": "pay", "args": [{"name": "amount", "type": "Integer"}]}]"#,
+            "[]",
+        );
+        let func = metadata.func("pay").unwrap();
+        let param = |key: &str, ty: &str, value: &str| DataEntry::parse(key, ty, value).unwrap();
+
+        assert!(func.check(&[param("amount", "Integer", "1")]).is_empty());
+        assert_eq!(
+            func.check(&[param("amount", "String", "1")]),
+            ["param `amount` is string, expected integer"]
+        );
+        assert_eq!(
+            func.check(&[param("amount", "Integer", "1"), param("memo", "String", "")]),
+            ["unexpected param `memo`"]
+        );
+        assert_eq!(func.check(&[]), ["missing param `amount` of type integer"]);
+    }
+
+    #[test]
+    fn reordered_args() {
+        let old = metadata(
+            r#"[{"name": "pay", "args": [{"name": "a", "type": "Integer"}, {"name": "b", "type": "String"}]}]"#,
+            "[]",
+        );
+        let new = metadata(
+            r#"[{"name": "pay", "args": [{"name": "b", "type": "String"}, {"name": "a", "type": "Integer"}]}]"#,
+            "[]",
+        );
+
+        assert!(old.diff(&new).iter().all(Change::is_breaking));
+        assert_eq!(old.diff(&new).len(), 1);
+    }
+
+    #[test]
+    fn malformed_json() {
+        assert!(Metadata::from_json(r#"{"name": "bank", "abi": ["#).is_err());
+        assert!(Metadata::from_json(r#"{"name": "bank"}"#).is_err());
+    }
+
+    #[test]
+    fn malformed_wasm() {
+        assert_eq!(
+            Metadata::new("bank", b"\0asm\x01")
+                .unwrap_err()
+                .split(':')
+                .next(),
+            Some("invalid wasm")
+        );
+
+        let bytecode = wat::parse_str(r#"(module (@custom "we_abi" "{\"name\":"))"#).unwrap();
+        assert!(Metadata::new("bank", &bytecode)
+            .unwrap_err()
+            .starts_with("invalid record in custom section `we_abi`"));
+
+        let bytecode = wat::parse_str(r#"(module (@custom "we_errors" "\ff"))"#).unwrap();
+        assert_eq!(
+            Metadata::new("bank", &bytecode).unwrap_err(),
+            "custom section `we_errors` is not UTF-8"
+        );
     }
 }
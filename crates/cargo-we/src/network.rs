@@ -0,0 +1,203 @@
+//! Networks of the project, described in `we.toml`:
+//!
+//! ```toml
+//! default-network = "local"
+//!
+//! [networks.local]
+//! url = "http://localhost:6862"
+//! chain-id = "V"
+//! auth = "api-key"
+//! api-key-env = "WE_API_KEY"
+//! account = "alice"
+//!
+//! [networks.local.fee]
+//! create = 100000000
+//! call = 10000000
+//! update = 100000000
+//! ```
+
+use crate::node::{
+    transactions::{CALL_CONTRACT, CREATE_CONTRACT, UPDATE_CONTRACT},
+    Auth,
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+pub const WE_TOML: &str = "we.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Networks {
+    /// Network used without `--network`, the only one by default.
+    default_network: Option<String>,
+    #[serde(default)]
+    networks: BTreeMap<String, Network>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Network {
+    #[serde(skip)]
+    pub name: String,
+    pub url: String,
+    /// Chain id byte of the addresses.
+    pub chain_id: Option<char>,
+    #[serde(default)]
+    auth: AuthMode,
+    /// Environment variable holding the API key or the token.
+    api_key_env: Option<String>,
+    /// Account of the keystore signing the transactions, by default the node signs them.
+    pub account: Option<String>,
+    #[serde(default)]
+    pub fee: Fees,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum AuthMode {
+    /// `X-API-Key` header
+    #[default]
+    ApiKey,
+    /// `Authorization: Bearer` header of nodes with OAuth
+    Oauth,
+    None,
+}
+
+/// Fees of the transactions without one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fees {
+    pub create: Option<u64>,
+    pub call: Option<u64>,
+    pub update: Option<u64>,
+}
+
+impl Networks {
+    /// Reads the `we.toml` of the project, no networks are defined without it.
+    pub fn load() -> Result<Self, String> {
+        let path = Path::new(WE_TOML);
+        if !path.exists() {
+            return Ok(Networks::default());
+        }
+
+        let toml = fs::read_to_string(path)
+            .map_err(|error| format!("can't read {}: {}", WE_TOML, error))?;
+        Self::parse(&toml)
+    }
+
+    fn parse(toml: &str) -> Result<Self, String> {
+        let mut networks: Networks =
+            toml::from_str(toml).map_err(|error| format!("can't parse {}: {}", WE_TOML, error))?;
+        for (name, network) in networks.networks.iter_mut() {
+            network.name = name.clone();
+        }
+        Ok(networks)
+    }
+
+    /// Selects the network by name, or the default one.
+    pub fn select(&self, name: Option<&str>) -> Result<Option<&Network>, String> {
+        let name = match (name, &self.default_network) {
+            (Some(name), _) => name,
+            (None, Some(name)) => name,
+            (None, None) if self.networks.len() == 1 => {
+                return Ok(self.networks.values().next());
+            }
+            (None, None) => return Ok(None),
+        };
+
+        match self.networks.get(name) {
+            Some(network) => Ok(Some(network)),
+            None => Err(format!("no network `{}` in {}", name, WE_TOML)),
+        }
+    }
+}
+
+impl Network {
+    /// Reads the API key from the environment.
+    pub fn auth(&self) -> Result<Auth, String> {
+        let api_key = || match &self.api_key_env {
+            Some(name) => env::var(name).map_err(|_| format!("{} is not set", name)),
+            None => Ok(String::new()),
+        };
+
+        match self.auth {
+            AuthMode::ApiKey => api_key().map(Auth::ApiKey),
+            AuthMode::Oauth => api_key().map(Auth::Bearer),
+            AuthMode::None => Ok(Auth::None),
+        }
+    }
+}
+
+impl Fees {
+    /// Fee of the transaction of the type.
+    pub fn get(&self, type_id: u64) -> Option<u64> {
+        match type_id {
+            CREATE_CONTRACT => self.create,
+            CALL_CONTRACT => self.call,
+            UPDATE_CONTRACT => self.update,
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETWORKS: &str = r#"
+        [networks.local]
+        url = "http://localhost:6862"
+        chain-id = "V"
+
+        [networks.local.fee]
+        call = 10000000
+
+        [networks.test]
+        url = "https://test.example.com/node-0"
+        auth = "oauth"
+        api-key-env = "WE_NETWORK_TEST_TOKEN"
+    "#;
+
+    #[test]
+    fn select() {
+        let networks = Networks::parse(NETWORKS).unwrap();
+
+        let local = networks.select(Some("local")).unwrap().unwrap();
+        assert_eq!(local.name, "local");
+        assert_eq!(local.chain_id, Some('V'));
+        assert_eq!(local.fee.get(CALL_CONTRACT), Some(10000000));
+        assert_eq!(local.fee.get(CREATE_CONTRACT), None);
+        assert!(matches!(local.auth(), Ok(Auth::ApiKey(api_key)) if api_key.is_empty()));
+
+        // No default among several networks
+        assert!(networks.select(None).unwrap().is_none());
+        assert_eq!(
+            networks.select(Some("main")).unwrap_err(),
+            "no network `main` in we.toml"
+        );
+
+        let test = networks.select(Some("test")).unwrap().unwrap();
+        assert_eq!(test.auth().unwrap_err(), "WE_NETWORK_TEST_TOKEN is not set");
+    }
+
+    #[test]
+    fn default_network() {
+        let networks =
+            Networks::parse(&format!("default-network = \"test\"\n{}", NETWORKS)).unwrap();
+        assert_eq!(
+            networks.select(None).unwrap().unwrap().url,
+            "https://test.example.com/node-0"
+        );
+
+        let networks = Networks::parse(
+            r#"
+            [networks.local]
+            url = "http://localhost:6862"
+            "#,
+        )
+        .unwrap();
+        assert!(networks.select(None).unwrap().is_some());
+
+        assert!(Networks::parse("[networks.local]\nurl = \"\"\napi-key = \"we\"").is_err());
+    }
+}
@@ -1,29 +1,168 @@
 pub mod transactions;
 
-use transactions::ContractTransaction;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use transactions::{ContractTransaction, DataEntry};
+
+/// Interval between the requests of `wait_for_execution`.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Entries of the contract state read by a request of `contract_state`.
+const STATE_PAGE: usize = 100;
 
 pub struct Node {
     url: String,
-    api_key: String,
+    auth: Auth,
+}
+
+/// Authorization of the requests to the node.
+#[derive(Debug)]
+pub enum Auth {
+    ApiKey(String),
+    Bearer(String),
+    None,
+}
+
+/// Result of the execution of a contract transaction reported by the node.
+#[derive(Debug, Deserialize)]
+pub struct ContractStatus {
+    /// `Success`, `Error` for an error returned by the contract, `Failure` for a failure of the node.
+    pub status: String,
+    pub code: Option<i32>,
+    pub message: Option<String>,
+}
+
+impl ContractStatus {
+    pub fn is_success(&self) -> bool {
+        self.status == "Success"
+    }
+}
+
+#[derive(Deserialize)]
+struct BroadcastedTransaction {
+    id: String,
 }
 
 impl Node {
-    pub fn new(url: String, api_key: String) -> Self {
-        Node { url, api_key }
+    pub fn new(url: String, auth: Auth) -> Self {
+        Node { url, auth }
     }
 
+    /// Returns the id of the transaction accepted by the node.
     pub async fn transaction_sign_and_broadcast(
         &self,
         tx: ContractTransaction,
-    ) -> Result<(), reqwest::Error> {
-        let url = format!("{}/transactions/signAndBroadcast", self.url);
+    ) -> Result<Option<String>, reqwest::Error> {
+        self.post("transactions/signAndBroadcast", tx.as_json())
+            .await
+    }
+
+    /// Sends a transaction signed by `ContractTransaction::sign`.
+    /// Returns the id of the transaction accepted by the node.
+    pub async fn transaction_broadcast(
+        &self,
+        signed_tx: String,
+    ) -> Result<Option<String>, reqwest::Error> {
+        self.post("transactions/broadcast", signed_tx).await
+    }
+
+    /// Polls the node until the transaction is mined and executed, or the timeout expires.
+    /// Returns `None` on timeout.
+    pub async fn wait_for_execution(
+        &self,
+        tx_id: &str,
+        timeout: Duration,
+    ) -> Result<Option<ContractStatus>, reqwest::Error> {
+        let started = Instant::now();
+
+        while started.elapsed() < timeout {
+            let statuses = self.contract_status(tx_id).await?;
+
+            // A failed transaction is never mined, a successful one is final once mined
+            if let Some(status) = statuses.iter().position(|status| !status.is_success()) {
+                return Ok(statuses.into_iter().nth(status));
+            }
+            if !statuses.is_empty() && self.is_mined(tx_id).await? {
+                return Ok(statuses.into_iter().next());
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        Ok(None)
+    }
+
+    /// Reads all entries of the contract state, page by page.
+    pub async fn contract_state(
+        &self,
+        contract_id: &str,
+    ) -> Result<Vec<DataEntry>, reqwest::Error> {
+        let mut entries = vec![];
+
+        loop {
+            let url = format!(
+                "{}/contracts/{}?offset={}&limit={}",
+                self.url,
+                encode_path(contract_id),
+                entries.len(),
+                STATE_PAGE
+            );
+            let page: Vec<DataEntry> = self.get(url).await?.error_for_status()?.json().await?;
+            let last = page.len() < STATE_PAGE;
+
+            entries.extend(page);
+            if last {
+                return Ok(entries);
+            }
+        }
+    }
+
+    /// Reads an entry of the contract state, `None` if the key is not set.
+    pub async fn contract_key(
+        &self,
+        contract_id: &str,
+        key: &str,
+    ) -> Result<Option<DataEntry>, reqwest::Error> {
+        let url = format!(
+            "{}/contracts/{}/{}",
+            self.url,
+            encode_path(contract_id),
+            encode_path(key)
+        );
+        let response = self.get(url).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        response.error_for_status()?.json().await.map(Some)
+    }
+
+    async fn is_mined(&self, tx_id: &str) -> Result<bool, reqwest::Error> {
+        let url = format!("{}/transactions/info/{}", self.url, tx_id);
+        let response = self.get(url).await?;
+
+        Ok(response.status().is_success())
+    }
+
+    async fn contract_status(&self, tx_id: &str) -> Result<Vec<ContractStatus>, reqwest::Error> {
+        let url = format!("{}/contracts/status/{}", self.url, tx_id);
+        let response = self.get(url).await?;
+
+        // Statuses are not known until the transaction is executed
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(vec![]);
+        }
+
+        response.error_for_status()?.json().await
+    }
+
+    async fn post(&self, path: &str, body: String) -> Result<Option<String>, reqwest::Error> {
+        let url = format!("{}/{}", self.url, path);
         let client = reqwest::Client::new();
-        let response = client
-            .post(url)
+        let response = self
+            .authorize(client.post(url))
             .header("accept", "application/json")
             .header("Content-Type", "application/json")
-            .header("X-API-Key", &self.api_key)
-            .body(tx.as_json())
+            .body(body)
             .send()
             .await?;
 
@@ -33,10 +172,150 @@ impl Node {
         println!("Response body:\n{}", response_body);
         if status == 200 {
             println!("Successful send transaction");
+            Ok(
+                serde_json::from_str::<BroadcastedTransaction>(&response_body)
+                    .ok()
+                    .map(|tx| tx.id),
+            )
         } else {
             println!("Error send transaction. HTTP Response status: {}", status);
+            Ok(None)
         }
+    }
+
+    async fn get(&self, url: String) -> Result<reqwest::Response, reqwest::Error> {
+        self.authorize(reqwest::Client::new().get(url))
+            .header("accept", "application/json")
+            .send()
+            .await
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth {
+            Auth::ApiKey(api_key) => request.header("X-API-Key", api_key),
+            Auth::Bearer(token) => request.bearer_auth(token),
+            Auth::None => request,
+        }
+    }
+}
+
+/// Percent-encodes a segment of the path, the keys of the state are arbitrary strings.
+fn encode_path(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    const TX_ID: &str = "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2";
+
+    /// Serves the responses by path, other paths are not found.
+    async fn serve(responses: &[(&str, u16, &str)]) -> Node {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let responses: HashMap<String, (u16, String)> = responses
+            .iter()
+            .map(|(path, status, body)| (path.to_string(), (*status, body.to_string())))
+            .collect();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let length = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..length]);
+                let path = request.split(' ').nth(1).unwrap_or_default();
+
+                let (status, body) = responses.get(path).cloned().unwrap_or((404, String::new()));
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        Node::new(url, Auth::None)
+    }
+
+    fn status_path() -> String {
+        format!("/contracts/status/{}", TX_ID)
+    }
+
+    async fn wait(node: &Node) -> Result<Option<ContractStatus>, reqwest::Error> {
+        node.wait_for_execution(TX_ID, Duration::from_secs(5)).await
+    }
+
+    #[tokio::test]
+    async fn execution_failed() {
+        let node = serve(&[(
+            &status_path(),
+            200,
+            r#"[{"status": "Error", "code": 1001, "message": "Voting is over"}]"#,
+        )])
+        .await;
+
+        let status = wait(&node).await.unwrap().unwrap();
+        assert!(!status.is_success());
+        assert_eq!(status.code, Some(1001));
+        assert_eq!(status.message.as_deref(), Some("Voting is over"));
+    }
+
+    #[tokio::test]
+    async fn executed_and_mined() {
+        let node = serve(&[
+            (&status_path(), 200, r#"[{"status": "Success"}]"#),
+            (&format!("/transactions/info/{}", TX_ID), 200, "{}"),
+        ])
+        .await;
+
+        let status = wait(&node).await.unwrap().unwrap();
+        assert!(status.is_success());
+        assert_eq!(status.code, None);
+    }
+
+    #[tokio::test]
+    async fn not_executed() {
+        let node = serve(&[]).await;
+
+        let status = node
+            .wait_for_execution(TX_ID, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert!(status.is_none());
+    }
+
+    #[tokio::test]
+    async fn broken_response() {
+        let node = serve(&[(&status_path(), 500, r#"{"error": 0}"#)]).await;
+        let error = wait(&node).await.unwrap_err();
+        assert_eq!(
+            error.status(),
+            Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+        );
+
+        let node = serve(&[(&status_path(), 200, r#"{"status": "Success"}"#)]).await;
+        assert!(wait(&node).await.unwrap_err().is_decode());
+    }
 
-        Ok(())
+    #[test]
+    fn path_encoding() {
+        assert_eq!(encode_path("balance_3N/x y"), "balance_3N%2Fx%20y");
     }
 }
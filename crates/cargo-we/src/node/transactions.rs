@@ -1,11 +1,28 @@
+use crate::crypto::PrivateKey;
+use base58::{FromBase58, ToBase58};
+use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use serde_json::json;
+use std::{fmt, str::FromStr};
+
+/// Length of the addresses in the body of transactions.
+const ADDRESS_LENGTH: usize = 26;
+/// Length of ids of contracts and assets.
+const ID_LENGTH: usize = 32;
+
+/// Type of the CreateContract transaction.
+pub const CREATE_CONTRACT: u64 = 103;
+/// Type of the CallContract transaction.
+pub const CALL_CONTRACT: u64 = 104;
+/// Type of the UpdateContract transaction.
+pub const UPDATE_CONTRACT: u64 = 107;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
-    pub node_url: String,
-    pub api_key: String,
+    /// Without it the network of `we.toml` receives the transaction.
+    pub node_url: Option<String>,
+    pub api_key: Option<String>,
     pub transaction: ContractTransaction,
 }
 
@@ -15,26 +32,365 @@ pub struct ContractTransaction {
     #[serde(rename = "type")]
     type_id: u64,
     version: u64,
+    /// Set by `--account` when omitted.
+    #[serde(default)]
     sender: String,
     password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stored_contract: Option<StoredContractWasm>,
     #[serde(flatten)]
     data: TransactionData,
+    /// The fee of the network when omitted.
+    #[serde(default)]
     fee: u64,
     fee_asset_id: Option<String>,
-    validation_policy: ValidationPolicy,
-    group_participants: Vec<String>,
-    group_owners: Vec<String>,
 }
 
 impl ContractTransaction {
+    /// Builds a CreateContract transaction of the bytecode, with the params of the constructor.
+    pub fn create(
+        sender: String,
+        password: Option<String>,
+        create: CreateContract,
+        stored_contract: StoredContractWasm,
+        fee: Fee,
+    ) -> Self {
+        ContractTransaction {
+            type_id: CREATE_CONTRACT,
+            version: 7,
+            sender,
+            password,
+            stored_contract: Some(stored_contract),
+            data: TransactionData::CreateContract {
+                contract_name: create.contract_name,
+                params: create.params,
+                payments: create.payments,
+                is_confidential: false,
+                validation_policy: ValidationPolicy::Any,
+                group_participants: vec![],
+                group_owners: vec![],
+            },
+            fee: fee.amount,
+            fee_asset_id: fee.asset_id,
+        }
+    }
+
+    /// Builds an UpdateContract transaction replacing the bytecode of the contract.
+    pub fn update(
+        sender: String,
+        password: Option<String>,
+        contract_id: String,
+        stored_contract: StoredContractWasm,
+        fee: Fee,
+    ) -> Self {
+        ContractTransaction {
+            type_id: UPDATE_CONTRACT,
+            version: 6,
+            sender,
+            password,
+            stored_contract: Some(stored_contract),
+            data: TransactionData::UpdateContract {
+                contract_id,
+                validation_policy: ValidationPolicy::Any,
+                group_participants: vec![],
+                group_owners: vec![],
+            },
+            fee: fee.amount,
+            fee_asset_id: fee.asset_id,
+        }
+    }
+
+    /// Builds a CallContract transaction.
+    pub fn call(sender: String, password: Option<String>, call: CallContract, fee: Fee) -> Self {
+        ContractTransaction {
+            type_id: CALL_CONTRACT,
+            version: 7,
+            sender,
+            password,
+            stored_contract: None,
+            data: TransactionData::CallContract {
+                contract_id: call.contract_id,
+                contract_version: call.contract_version,
+                call_func: call.call_func,
+                params: call.params,
+                payments: call.payments,
+            },
+            fee: fee.amount,
+            fee_asset_id: fee.asset_id,
+        }
+    }
+
+    /// Get the called action with its params, the constructor for CreateContract.
+    pub fn action(&self) -> Option<(&str, &[DataEntry])> {
+        match &self.data {
+            TransactionData::CreateContract { params, .. } => Some(("_constructor", params)),
+            TransactionData::CallContract {
+                call_func, params, ..
+            } => Some((call_func, params)),
+            TransactionData::UpdateContract { .. } => None,
+        }
+    }
+
+    /// Get the id of the contract replaced by UpdateContract.
+    pub fn updated_contract(&self) -> Option<&str> {
+        match &self.data {
+            TransactionData::UpdateContract { contract_id, .. } => Some(contract_id),
+            _ => None,
+        }
+    }
+
+    /// Only CreateContract and UpdateContract carry the bytecode.
+    pub fn is_call(&self) -> bool {
+        matches!(self.data, TransactionData::CallContract { .. })
+    }
+
     pub fn as_json(&self) -> String {
         serde_json::to_string(self).expect("Unable to serialize struct to JSON")
     }
 
+    pub fn type_id(&self) -> u64 {
+        self.type_id
+    }
+
+    /// Sets the fee if the transaction has none.
+    pub fn set_default_fee(&mut self, fee: u64) {
+        if self.fee == 0 {
+            self.fee = fee;
+        }
+    }
+
+    pub fn set_sender(&mut self, sender: String) {
+        self.sender = sender;
+    }
+
     pub fn set_stored_contract(&mut self, stored_contract: StoredContractWasm) {
         self.stored_contract = Some(stored_contract);
     }
+
+    /// Signs the transaction with the key of the sender.
+    /// Returns the JSON accepted by `/transactions/broadcast`.
+    pub fn sign(&self, private_key: &PrivateKey, timestamp: u64) -> Result<String, String> {
+        let sender_public_key = private_key.public_key();
+        let body = self.body_bytes(&sender_public_key, timestamp)?;
+        let proof = private_key.sign(&body);
+
+        let mut tx = serde_json::to_value(self).expect("Unable to serialize struct to JSON");
+        let fields = tx.as_object_mut().expect("Transaction is an object");
+        fields.remove("password");
+        fields.insert(
+            "senderPublicKey".to_string(),
+            json!(sender_public_key.to_base58()),
+        );
+        fields.insert("timestamp".to_string(), json!(timestamp));
+        fields.insert("proofs".to_string(), json!([proof.to_base58()]));
+
+        Ok(tx.to_string())
+    }
+
+    /// Serializes the fields covered by the signature, in the order of the node.
+    fn body_bytes(&self, sender_public_key: &[u8], timestamp: u64) -> Result<Vec<u8>, String> {
+        let mut body = Body::default();
+        body.byte(self.type_id as u8);
+        body.byte(self.version as u8);
+        body.bytes(sender_public_key);
+
+        match &self.data {
+            TransactionData::CreateContract {
+                contract_name,
+                params,
+                payments,
+                is_confidential,
+                validation_policy,
+                group_participants,
+                group_owners,
+            } => {
+                body.array(contract_name.as_bytes());
+                body.params(params)?;
+                body.long(self.fee);
+                body.long(timestamp);
+                body.option(self.fee_asset_id.as_deref(), ID_LENGTH)?;
+                // Atomic badge
+                body.byte(0);
+                body.validation_policy(validation_policy)?;
+                body.payments(payments)?;
+                body.byte(*is_confidential as u8);
+                body.addresses(group_participants)?;
+                body.addresses(group_owners)?;
+                body.stored_contract(self.stored_contract.as_ref())?;
+            }
+            TransactionData::CallContract {
+                contract_id,
+                contract_version,
+                call_func,
+                params,
+                payments,
+            } => {
+                body.base58(contract_id, ID_LENGTH)?;
+                body.params(params)?;
+                body.long(self.fee);
+                body.long(timestamp);
+                body.int(*contract_version as u32);
+                body.option(self.fee_asset_id.as_deref(), ID_LENGTH)?;
+                // Atomic badge
+                body.byte(0);
+                body.payments(payments)?;
+                // Input commitment of confidential contracts
+                body.byte(0);
+                body.byte(1);
+                body.array(call_func.as_bytes());
+            }
+            TransactionData::UpdateContract {
+                contract_id,
+                validation_policy,
+                group_participants,
+                group_owners,
+            } => {
+                body.base58(contract_id, ID_LENGTH)?;
+                body.long(self.fee);
+                body.long(timestamp);
+                body.option(self.fee_asset_id.as_deref(), ID_LENGTH)?;
+                // Atomic badge
+                body.byte(0);
+                body.validation_policy(validation_policy)?;
+                body.addresses(group_participants)?;
+                body.addresses(group_owners)?;
+                body.stored_contract(self.stored_contract.as_ref())?;
+            }
+        }
+
+        Ok(body.0)
+    }
+}
+
+/// Binary serialization of the node: big-endian numbers,
+/// arrays and lists prefixed with their length as a short.
+#[derive(Default)]
+struct Body(Vec<u8>);
+
+impl Body {
+    fn byte(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.0.extend_from_slice(value);
+    }
+
+    fn short(&mut self, value: usize) {
+        self.bytes(&(value as u16).to_be_bytes());
+    }
+
+    fn int(&mut self, value: u32) {
+        self.bytes(&value.to_be_bytes());
+    }
+
+    fn long(&mut self, value: u64) {
+        self.bytes(&value.to_be_bytes());
+    }
+
+    fn array(&mut self, value: &[u8]) {
+        self.short(value.len());
+        self.bytes(value);
+    }
+
+    fn base58(&mut self, value: &str, length: usize) -> Result<(), String> {
+        match value.from_base58() {
+            Ok(bytes) if bytes.len() == length => {
+                self.bytes(&bytes);
+                Ok(())
+            }
+            _ => Err(format!("`{}` is not {} bytes in base58", value, length)),
+        }
+    }
+
+    fn option(&mut self, value: Option<&str>, length: usize) -> Result<(), String> {
+        match value {
+            Some(value) => {
+                self.byte(1);
+                self.base58(value, length)
+            }
+            None => {
+                self.byte(0);
+                Ok(())
+            }
+        }
+    }
+
+    fn addresses(&mut self, addresses: &[String]) -> Result<(), String> {
+        self.short(addresses.len());
+        for address in addresses {
+            self.base58(address, ADDRESS_LENGTH)?;
+        }
+        Ok(())
+    }
+
+    fn params(&mut self, params: &[DataEntry]) -> Result<(), String> {
+        self.short(params.len());
+        for param in params {
+            self.array(param.key().as_bytes());
+            match param {
+                DataEntry::Integer { value, .. } => {
+                    self.byte(0);
+                    self.long(*value as u64);
+                }
+                DataEntry::Boolean { value, .. } => {
+                    self.byte(1);
+                    self.byte(*value as u8);
+                }
+                DataEntry::Binary { key, value } => {
+                    let encoded = value.strip_prefix("base64:").unwrap_or(value);
+                    let value = general_purpose::STANDARD
+                        .decode(encoded)
+                        .map_err(|_| format!("param `{}` is not base64", key))?;
+                    self.byte(2);
+                    self.array(&value);
+                }
+                DataEntry::String { value, .. } => {
+                    self.byte(3);
+                    self.array(value.as_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn payments(&mut self, payments: &[ContractTransferInV1]) -> Result<(), String> {
+        self.short(payments.len());
+        for payment in payments {
+            self.option(payment.asset_id.as_deref(), ID_LENGTH)?;
+            self.long(payment.amount);
+        }
+        Ok(())
+    }
+
+    fn validation_policy(&mut self, policy: &ValidationPolicy) -> Result<(), String> {
+        match policy {
+            ValidationPolicy::Any => self.byte(0),
+            ValidationPolicy::Majority => self.byte(1),
+            ValidationPolicy::MajorityWithOneOf { addresses } => {
+                self.byte(2);
+                self.addresses(addresses)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn stored_contract(
+        &mut self,
+        stored_contract: Option<&StoredContractWasm>,
+    ) -> Result<(), String> {
+        let stored_contract = stored_contract.ok_or("the bytecode of the contract is not set")?;
+        let bytecode = general_purpose::STANDARD
+            .decode(&stored_contract.bytecode)
+            .map_err(|_| "the bytecode is not base64".to_string())?;
+
+        // Wasm contract, the bytecode may exceed the limit of a short
+        self.byte(1);
+        self.int(bytecode.len() as u32);
+        self.bytes(&bytecode);
+        self.array(stored_contract.bytecode_hash.as_bytes());
+        Ok(())
+    }
 }
 
 impl fmt::Display for ContractTransaction {
@@ -50,6 +406,30 @@ pub struct StoredContractWasm {
     pub bytecode_hash: String,
 }
 
+/// New contract, created from the bytecode.
+pub struct CreateContract {
+    pub contract_name: String,
+    pub params: Vec<DataEntry>,
+    pub payments: Vec<ContractTransferInV1>,
+}
+
+/// Call of an action of a deployed contract.
+pub struct CallContract {
+    pub contract_id: String,
+    pub contract_version: u64,
+    pub call_func: String,
+    pub params: Vec<DataEntry>,
+    pub payments: Vec<ContractTransferInV1>,
+}
+
+pub struct Fee {
+    pub amount: u64,
+    pub asset_id: Option<String>,
+}
+
+// Named after the transactions of the node.
+// Told apart by their fields, CallContract and UpdateContract both have a `contractId`
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum TransactionData {
@@ -59,26 +439,111 @@ enum TransactionData {
         params: Vec<DataEntry>,
         payments: Vec<ContractTransferInV1>,
         is_confidential: bool,
+        validation_policy: ValidationPolicy,
+        group_participants: Vec<String>,
+        group_owners: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    CallContract {
+        contract_id: String,
+        contract_version: u64,
+        call_func: String,
+        params: Vec<DataEntry>,
+        payments: Vec<ContractTransferInV1>,
     },
     #[serde(rename_all = "camelCase")]
-    UpdateContract { contract_id: String },
+    UpdateContract {
+        contract_id: String,
+        validation_policy: ValidationPolicy,
+        group_participants: Vec<String>,
+        group_owners: Vec<String>,
+    },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "type")]
-enum DataEntry {
-    Integer { key: String, value: u64 },
+pub enum DataEntry {
+    Integer { key: String, value: i64 },
     Boolean { key: String, value: bool },
     Binary { key: String, value: String },
     String { key: String, value: String },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl DataEntry {
+    pub fn key(&self) -> &str {
+        match self {
+            DataEntry::Integer { key, .. }
+            | DataEntry::Boolean { key, .. }
+            | DataEntry::Binary { key, .. }
+            | DataEntry::String { key, .. } => key,
+        }
+    }
+
+    /// Get the type as written in JSON.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DataEntry::Integer { .. } => "integer",
+            DataEntry::Boolean { .. } => "boolean",
+            DataEntry::Binary { .. } => "binary",
+            DataEntry::String { .. } => "string",
+        }
+    }
+
+    /// Parses the value of an argument of the type declared in the ABI.
+    /// Binary values are base64, with or without the `base64:` prefix used by the node.
+    pub fn parse(key: &str, ty: &str, value: &str) -> Result<Self, String> {
+        let key = key.to_string();
+        match ty {
+            "Integer" => value
+                .parse()
+                .map(|value| DataEntry::Integer { key, value })
+                .map_err(|_| format!("`{}` is not an Integer", value)),
+            "Boolean" => value
+                .parse()
+                .map(|value| DataEntry::Boolean { key, value })
+                .map_err(|_| format!("`{}` is not a Boolean", value)),
+            "Binary" => {
+                let encoded = value.strip_prefix("base64:").unwrap_or(value);
+                match general_purpose::STANDARD.decode(encoded) {
+                    Ok(_) => Ok(DataEntry::Binary {
+                        key,
+                        value: format!("base64:{}", encoded),
+                    }),
+                    Err(_) => Err(format!("`{}` is not a base64 Binary", value)),
+                }
+            }
+            "String" => Ok(DataEntry::String {
+                key,
+                value: value.to_string(),
+            }),
+            ty => Err(format!("unknown type `{}`", ty)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct ContractTransferInV1 {
-    asset_id: Option<String>,
-    amount: u64,
+pub struct ContractTransferInV1 {
+    pub asset_id: Option<String>,
+    pub amount: u64,
+}
+
+impl FromStr for ContractTransferInV1 {
+    type Err = String;
+
+    /// Parses `<amount>` of the system token or `<asset_id>:<amount>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (asset_id, amount) = match s.rsplit_once(':') {
+            Some((asset_id, amount)) => (Some(asset_id.to_string()), amount),
+            None => (None, s),
+        };
+        let amount = amount
+            .parse()
+            .map_err(|_| format!("`{}` is not an amount", amount))?;
+
+        Ok(ContractTransferInV1 { asset_id, amount })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -89,3 +554,313 @@ enum ValidationPolicy {
     Majority,
     MajorityWithOneOf { addresses: Vec<String> },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::tests::verify;
+
+    const PRIVATE_KEY: &str = "3j2aMHzh9azPphzuW7aF3cmUefGEQC9dcWYXYCyoPcJg";
+    const CONTRACT_ID: &str = "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2";
+
+    fn call() -> ContractTransaction {
+        ContractTransaction::call(
+            "3Ms87NGAAaPWZux233TB9A3TXps4LDkyJWN".to_string(),
+            None,
+            CallContract {
+                contract_id: CONTRACT_ID.to_string(),
+                contract_version: 1,
+                call_func: "withdraw".to_string(),
+                params: vec![
+                    DataEntry::parse("to", "Binary", "AQID").unwrap(),
+                    DataEntry::parse("amount", "Integer", "42").unwrap(),
+                ],
+                payments: vec!["100".parse().unwrap()],
+            },
+            Fee {
+                amount: 10000000,
+                asset_id: None,
+            },
+        )
+    }
+
+    fn stored_contract() -> StoredContractWasm {
+        StoredContractWasm {
+            // Header of a wasm module
+            bytecode: "AGFzbQ==".to_string(),
+            bytecode_hash: "abc".to_string(),
+        }
+    }
+
+    fn create() -> ContractTransaction {
+        ContractTransaction::create(
+            "3Ms87NGAAaPWZux233TB9A3TXps4LDkyJWN".to_string(),
+            None,
+            CreateContract {
+                contract_name: "flipper".to_string(),
+                params: vec![DataEntry::parse("flag", "Boolean", "true").unwrap()],
+                payments: vec![],
+            },
+            stored_contract(),
+            Fee {
+                amount: 100000000,
+                asset_id: None,
+            },
+        )
+    }
+
+    fn update() -> ContractTransaction {
+        ContractTransaction::update(
+            "3Ms87NGAAaPWZux233TB9A3TXps4LDkyJWN".to_string(),
+            None,
+            CONTRACT_ID.to_string(),
+            stored_contract(),
+            Fee {
+                amount: 100000000,
+                asset_id: Some(CONTRACT_ID.to_string()),
+            },
+        )
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn create_body_bytes() {
+        let private_key = PrivateKey::from_base58(PRIVATE_KEY).unwrap();
+        let body = create()
+            .body_bytes(&private_key.public_key(), 1700000000000)
+            .unwrap();
+
+        let expected = [
+            // Type and version
+            "6707",
+            // Sender public key
+            "7128bc0da66818e5419db0cd60bb653eaa61fd2075496b8b7743ed9d75161b24",
+            // Contract name
+            "0007666c6970706572",
+            // Params
+            "0001",
+            "0004666c6167",
+            "0101",
+            // Fee and timestamp
+            "0000000005f5e100",
+            "0000018bcfe56800",
+            // Fee asset id and atomic badge
+            "00",
+            "00",
+            // Validation policy
+            "00",
+            // Payments
+            "0000",
+            // Confidentiality, group participants and owners
+            "00",
+            "0000",
+            "0000",
+            // Wasm bytecode and its hash
+            "01",
+            "000000040061736d",
+            "0003616263",
+        ]
+        .concat();
+
+        assert_eq!(hex(&body), expected);
+    }
+
+    #[test]
+    fn update_body_bytes() {
+        let private_key = PrivateKey::from_base58(PRIVATE_KEY).unwrap();
+        let body = update()
+            .body_bytes(&private_key.public_key(), 1700000000000)
+            .unwrap();
+
+        let expected = [
+            // Type and version
+            "6b06",
+            // Sender public key
+            "7128bc0da66818e5419db0cd60bb653eaa61fd2075496b8b7743ed9d75161b24",
+            // Contract id
+            "34215ca5aed86961f22efe6105ae12f59ceade72ecb235275576b50e75eb94d5",
+            // Fee and timestamp
+            "0000000005f5e100",
+            "0000018bcfe56800",
+            // Fee asset id and atomic badge
+            "01",
+            "34215ca5aed86961f22efe6105ae12f59ceade72ecb235275576b50e75eb94d5",
+            "00",
+            // Validation policy, group participants and owners
+            "00",
+            "0000",
+            "0000",
+            // Wasm bytecode and its hash
+            "01",
+            "000000040061736d",
+            "0003616263",
+        ]
+        .concat();
+
+        assert_eq!(hex(&body), expected);
+    }
+
+    #[test]
+    fn call_body_bytes() {
+        let private_key = PrivateKey::from_base58(PRIVATE_KEY).unwrap();
+        let body = call()
+            .body_bytes(&private_key.public_key(), 1700000000000)
+            .unwrap();
+
+        let expected = [
+            // Type and version
+            "6807",
+            // Sender public key
+            "7128bc0da66818e5419db0cd60bb653eaa61fd2075496b8b7743ed9d75161b24",
+            // Contract id
+            "34215ca5aed86961f22efe6105ae12f59ceade72ecb235275576b50e75eb94d5",
+            // Params
+            "0002",
+            "0002746f",
+            "020003010203",
+            "0006616d6f756e74",
+            "00000000000000002a",
+            // Fee and timestamp
+            "0000000000989680",
+            "0000018bcfe56800",
+            // Contract version
+            "00000001",
+            // Fee asset id and atomic badge
+            "00",
+            "00",
+            // Payments
+            "0001",
+            "000000000000000064",
+            // Input commitment and function
+            "00",
+            "0100087769746864726177",
+        ]
+        .concat();
+
+        assert_eq!(hex(&body), expected);
+    }
+
+    #[test]
+    fn sign() {
+        let private_key = PrivateKey::from_base58(PRIVATE_KEY).unwrap();
+
+        for tx in [create(), update(), call()] {
+            let signed: serde_json::Value =
+                serde_json::from_str(&tx.sign(&private_key, 1700000000000).unwrap()).unwrap();
+            assert_eq!(signed["type"], tx.type_id());
+            assert_eq!(
+                signed["senderPublicKey"],
+                "8cj6YzvQPhSHGvnjupNTW8zrADTT8CMAAd2xTuej84gB"
+            );
+            assert_eq!(signed["timestamp"], 1700000000000u64);
+            assert!(signed.get("password").is_none());
+
+            let body = tx
+                .body_bytes(&private_key.public_key(), 1700000000000)
+                .unwrap();
+            let proof = signed["proofs"][0].as_str().unwrap().from_base58().unwrap();
+            assert!(verify(&private_key.public_key(), &body, &proof));
+
+            // The proof covers the whole body
+            let mut tampered = body.clone();
+            tampered[1] ^= 1;
+            assert!(!verify(&private_key.public_key(), &tampered, &proof));
+        }
+    }
+
+    #[test]
+    fn parse_params() {
+        let parse = |ty: &str, value: &str| DataEntry::parse("arg", ty, value);
+        let key = "arg".to_string();
+
+        assert_eq!(
+            parse("Integer", "-42"),
+            Ok(DataEntry::Integer {
+                key: key.clone(),
+                value: -42
+            })
+        );
+        assert_eq!(
+            parse("Boolean", "true"),
+            Ok(DataEntry::Boolean {
+                key: key.clone(),
+                value: true
+            })
+        );
+        for value in ["AQID", "base64:AQID"] {
+            assert_eq!(
+                parse("Binary", value),
+                Ok(DataEntry::Binary {
+                    key: key.clone(),
+                    value: "base64:AQID".to_string()
+                })
+            );
+        }
+        assert_eq!(
+            parse("String", "42"),
+            Ok(DataEntry::String {
+                key,
+                value: "42".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_malformed_params() {
+        let parse = |ty: &str, value: &str| DataEntry::parse("arg", ty, value);
+
+        assert_eq!(
+            parse("Integer", "4.2"),
+            Err("`4.2` is not an Integer".to_string())
+        );
+        assert_eq!(
+            parse("Integer", "9223372036854775808"),
+            Err("`9223372036854775808` is not an Integer".to_string())
+        );
+        assert_eq!(
+            parse("Boolean", "yes"),
+            Err("`yes` is not a Boolean".to_string())
+        );
+        assert_eq!(
+            parse("Binary", "base64:AQI"),
+            Err("`base64:AQI` is not a base64 Binary".to_string())
+        );
+        assert_eq!(parse("Float", "1"), Err("unknown type `Float`".to_string()));
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let private_key = PrivateKey::from_base58(PRIVATE_KEY).unwrap();
+
+        let mut tx = call();
+        tx.fee_asset_id = Some("WAVES".to_string());
+        assert_eq!(
+            tx.sign(&private_key, 0),
+            Err("`WAVES` is not 32 bytes in base58".to_string())
+        );
+
+        let create: ContractTransaction = serde_json::from_value(json!({
+            "type": 103,
+            "version": 7,
+            "sender": "3Ms87NGAAaPWZux233TB9A3TXps4LDkyJWN",
+            "password": null,
+            "contractName": "flipper",
+            "params": [],
+            "payments": [],
+            "isConfidential": false,
+            "validationPolicy": { "type": "any" },
+            "groupParticipants": [],
+            "groupOwners": [],
+            "fee": 100000000,
+            "feeAssetId": null
+        }))
+        .unwrap();
+        assert_eq!(
+            create.sign(&private_key, 0),
+            Err("the bytecode of the contract is not set".to_string())
+        );
+    }
+}
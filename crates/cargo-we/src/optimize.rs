@@ -0,0 +1,261 @@
+//! Size optimization of the built contract, the bytecode is sent in every
+//! CreateContract and UpdateContract transaction.
+
+use crate::metadata::{self, Metadata};
+use walrus::{
+    ir::{
+        BinaryOp, Binop, Const, Instr, InstrSeq, LocalGet, LocalSet, LocalTee, Value, VisitorMut,
+    },
+    ExportItem, ImportKind, ModuleConfig,
+};
+
+/// Global exported by the linker, the node writes the arguments after it.
+const HEAP_BASE: &str = "__heap_base";
+
+/// Removes from the bytecode what the node does not use:
+/// exports out of the ABI, the functions and data they only reach,
+/// custom sections other than the metadata, names and debug info.
+/// Fails if the ABI has no constructor, the exports could not be told apart.
+pub fn optimize(bytecode: &[u8], metadata: &Metadata) -> Result<Vec<u8>, String> {
+    if metadata.func(metadata::CONSTRUCTOR).is_none() {
+        return Err(format!(
+            "the ABI has no `{}`, its exports can not be pruned",
+            metadata::CONSTRUCTOR
+        ));
+    }
+
+    let mut module = ModuleConfig::new()
+        .generate_name_section(false)
+        .generate_producers_section(false)
+        .generate_dwarf(false)
+        .parse(bytecode)
+        .map_err(|error| error.to_string())?;
+
+    let unused: Vec<_> = module
+        .exports
+        .iter()
+        .filter(|export| match export.item {
+            ExportItem::Function(_) => metadata.func(&export.name).is_none(),
+            ExportItem::Global(_) => export.name != HEAP_BASE,
+            _ => false,
+        })
+        .map(|export| export.id())
+        .collect();
+    for export in unused {
+        module.exports.delete(export);
+    }
+
+    let customs: Vec<_> = module
+        .customs
+        .iter()
+        .filter(|(_, section)| {
+            ![
+                metadata::ABI_SECTION,
+                metadata::ERRORS_SECTION,
+                metadata::TYPES_SECTION,
+            ]
+            .contains(&section.name())
+        })
+        .map(|(id, _)| id)
+        .collect();
+    for custom in customs {
+        module.customs.delete(custom);
+    }
+    module.name = None;
+
+    for (_, func) in module.funcs.iter_local_mut() {
+        let entry = func.entry_block();
+        walrus::ir::dfs_pre_order_mut(&mut Peephole, func, entry);
+    }
+
+    // The memory provided by the node is imported even when the contract does not use it
+    let memories: Vec<_> = module
+        .imports
+        .iter()
+        .filter_map(|import| match import.kind {
+            ImportKind::Memory(memory) => {
+                let memory = module.memories.get(memory);
+                Some((
+                    import.module.clone(),
+                    import.name.clone(),
+                    (memory.shared, memory.memory64),
+                    (memory.initial, memory.maximum, memory.page_size_log2),
+                ))
+            }
+            _ => None,
+        })
+        .collect();
+
+    walrus::passes::gc::run(&mut module);
+
+    for (import_module, name, (shared, memory64), (initial, maximum, page_size)) in memories {
+        if module.imports.find(&import_module, &name).is_none() {
+            module.add_import_memory(
+                &import_module,
+                &name,
+                shared,
+                memory64,
+                initial,
+                maximum,
+                page_size,
+            );
+        }
+    }
+
+    Ok(module.emit_wasm())
+}
+
+/// Rewrites sequences of instructions into shorter ones with the same effect.
+struct Peephole;
+
+impl VisitorMut for Peephole {
+    fn start_instr_seq_mut(&mut self, seq: &mut InstrSeq) {
+        let mut index = 0;
+
+        while index + 1 < seq.instrs.len() {
+            let replacement = match (&seq.instrs[index].0, &seq.instrs[index + 1].0) {
+                // Values pushed without side effects and dropped
+                (Instr::Const(_) | Instr::LocalGet(_) | Instr::GlobalGet(_), Instr::Drop(_)) => {
+                    Some(None)
+                }
+                // Operations with the identity element
+                (Instr::Const(Const { value }), Instr::Binop(Binop { op }))
+                    if is_identity(value, *op) =>
+                {
+                    Some(None)
+                }
+                (
+                    Instr::LocalGet(LocalGet { local: get }),
+                    Instr::LocalSet(LocalSet { local: set }),
+                ) if get == set => Some(None),
+                (
+                    Instr::LocalSet(LocalSet { local: set }),
+                    Instr::LocalGet(LocalGet { local: get }),
+                ) if get == set => Some(Some(Instr::LocalTee(LocalTee { local: *set }))),
+                _ => None,
+            };
+
+            match replacement {
+                Some(instr) => {
+                    let (_, loc) = seq.instrs.remove(index + 1);
+                    match instr {
+                        Some(instr) => seq.instrs[index] = (instr, loc),
+                        None => {
+                            seq.instrs.remove(index);
+                        }
+                    }
+                    // The previous instruction may pair with the new one
+                    index = index.saturating_sub(1);
+                }
+                None => index += 1,
+            }
+        }
+    }
+}
+
+/// `x op value` is `x`.
+fn is_identity(value: &Value, op: BinaryOp) -> bool {
+    use BinaryOp::*;
+
+    match value {
+        Value::I32(0) => matches!(
+            op,
+            I32Add | I32Sub | I32Or | I32Xor | I32Shl | I32ShrS | I32ShrU | I32Rotl | I32Rotr
+        ),
+        Value::I64(0) => matches!(
+            op,
+            I64Add | I64Sub | I64Or | I64Xor | I64Shl | I64ShrS | I64ShrU | I64Rotl | I64Rotr
+        ),
+        Value::I32(1) => matches!(op, I32Mul | I32DivS | I32DivU),
+        Value::I64(1) => matches!(op, I64Mul | I64DivS | I64DivU),
+        Value::I32(-1) => matches!(op, I32And),
+        Value::I64(-1) => matches!(op, I64And),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulator::Contract;
+
+    const CONTRACT: &str = r#"(module
+        (import "env" "memory" (memory 2 16))
+        (func $used (param i64) (result i64)
+            local.get 0
+            i64.const 0
+            i64.add
+            local.set 0
+            local.get 0
+            i32.const 7
+            drop)
+        (func $unused (result i32)
+            i32.const 1)
+        (func (export "_constructor") (result i32)
+            i64.const 3
+            call $used
+            i32.wrap_i64)
+        (func (export "helper") (result i32)
+            call $unused)
+        (global (export "__heap_base") i32 (i32.const 1024))
+        (global (export "__data_end") i32 (i32.const 1024))
+        (@custom "we_abi" "{\"name\":\"_constructor\",\"args\":[]}\n")
+        (@custom "producers" "\00"))"#;
+
+    #[test]
+    fn optimize_contract() {
+        let bytecode = wat::parse_str(CONTRACT).unwrap();
+        let metadata = Metadata::new("test", &bytecode).unwrap();
+        let optimized = optimize(&bytecode, &metadata).unwrap();
+        assert!(optimized.len() < bytecode.len());
+
+        let wat = wasmprinter::print_bytes(&optimized).unwrap();
+        assert!(wat.contains(r#"(export "_constructor""#));
+        assert!(wat.contains(r#"(export "__heap_base""#));
+        assert!(!wat.contains("helper"));
+        assert!(!wat.contains("__data_end"));
+        // `$unused` is only called by the removed export
+        assert_eq!(wat.matches("(func (;").count(), 2);
+        assert!(wat.contains(r#"(import "env" "memory" (memory (;0;) 2 16))"#));
+        assert!(!wat.contains("local.set"));
+        assert!(!wat.contains("i64.add"));
+        assert!(!wat.contains("drop"));
+
+        let customs: Vec<_> = wasmparser::Parser::new(0)
+            .parse_all(&optimized)
+            .filter_map(|payload| match payload.unwrap() {
+                wasmparser::Payload::CustomSection(section) => Some(section.name().to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(customs, ["we_abi"]);
+        assert_eq!(
+            Metadata::new("test", &optimized).unwrap().as_json(),
+            metadata.as_json()
+        );
+        let contract = Contract::new(&optimized).unwrap();
+        assert_eq!(contract.execute("_constructor", &[]).unwrap(), 3);
+    }
+
+    #[test]
+    fn without_constructor() {
+        // Built without `#[action]`, or with an ABI missing the constructor
+        let contracts = [
+            CONTRACT.replace(
+                r#"(@custom "we_abi" "{\"name\":\"_constructor\",\"args\":[]}\n")"#,
+                "",
+            ),
+            CONTRACT.replace(r#"\"name\":\"_constructor\""#, r#"\"name\":\"helper\""#),
+        ];
+
+        for contract in contracts {
+            assert_ne!(contract, CONTRACT);
+            let bytecode = wat::parse_str(&contract).unwrap();
+            let metadata = Metadata::new("test", &bytecode).unwrap();
+            assert_eq!(
+                optimize(&bytecode, &metadata),
+                Err("the ABI has no `_constructor`, its exports can not be pruned".to_string())
+            );
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Rendering of the contract state read by `cargo we state`.
+
+use crate::node::transactions::DataEntry;
+use base58::ToBase58;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::{json, Value};
+
+/// Encoding of the binary values.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum Encoding {
+    Base58,
+    Base64,
+    Hex,
+}
+
+impl Encoding {
+    /// Re-encodes a binary value, returned by the node as `base64:<value>`.
+    fn encode(&self, value: &str) -> String {
+        let encoded = value.strip_prefix("base64:").unwrap_or(value);
+        let bytes = match general_purpose::STANDARD.decode(encoded) {
+            Ok(bytes) => bytes,
+            // Shown as is rather than hidden
+            Err(_) => return value.to_string(),
+        };
+
+        match self {
+            Encoding::Base58 => bytes.to_base58(),
+            Encoding::Base64 => encoded.to_string(),
+            Encoding::Hex => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        }
+    }
+}
+
+/// Line of the entry as `key: Type = value`.
+pub fn render(entry: &DataEntry, encoding: Encoding) -> String {
+    let value = match entry {
+        DataEntry::Integer { value, .. } => value.to_string(),
+        DataEntry::Boolean { value, .. } => value.to_string(),
+        DataEntry::Binary { value, .. } => encoding.encode(value),
+        DataEntry::String { value, .. } => format!("{:?}", value),
+    };
+
+    format!("{}: {} = {}", entry.key(), type_name(entry), value)
+}
+
+/// The entries sorted by key as a JSON object, stable to be diffed between blocks.
+pub fn to_json(entries: &[DataEntry], encoding: Encoding) -> String {
+    let mut entries: Vec<&DataEntry> = entries.iter().collect();
+    entries.sort_by(|a, b| a.key().cmp(b.key()));
+
+    let state: serde_json::Map<String, Value> = entries
+        .into_iter()
+        .map(|entry| {
+            let value = match entry {
+                DataEntry::Integer { value, .. } => json!(value),
+                DataEntry::Boolean { value, .. } => json!(value),
+                DataEntry::Binary { value, .. } => json!(encoding.encode(value)),
+                DataEntry::String { value, .. } => json!(value),
+            };
+            let entry_json = json!({ "type": entry.type_name(), "value": value });
+
+            (entry.key().to_string(), entry_json)
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&state).expect("Unable to serialize struct to JSON")
+}
+
+/// The type as declared in the ABI.
+fn type_name(entry: &DataEntry) -> &'static str {
+    match entry {
+        DataEntry::Integer { .. } => "Integer",
+        DataEntry::Boolean { .. } => "Boolean",
+        DataEntry::Binary { .. } => "Binary",
+        DataEntry::String { .. } => "String",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<DataEntry> {
+        serde_json::from_str(
+            r#"[
+                {"key": "owner", "type": "binary", "value": "base64:3q2+7w=="},
+                {"key": "count", "type": "integer", "value": 42},
+                {"key": "name", "type": "string", "value": "we"},
+                {"key": "flag", "type": "boolean", "value": true}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn render_entries() {
+        let entries = entries();
+
+        assert_eq!(
+            render(&entries[0], Encoding::Base64),
+            "owner: Binary = 3q2+7w=="
+        );
+        assert_eq!(
+            render(&entries[0], Encoding::Base58),
+            "owner: Binary = 6h8cQN"
+        );
+        assert_eq!(
+            render(&entries[0], Encoding::Hex),
+            "owner: Binary = deadbeef"
+        );
+        assert_eq!(render(&entries[1], Encoding::Hex), "count: Integer = 42");
+        assert_eq!(render(&entries[2], Encoding::Hex), "name: String = \"we\"");
+        assert_eq!(render(&entries[3], Encoding::Hex), "flag: Boolean = true");
+    }
+
+    #[test]
+    fn sorted_json() {
+        let json: Value = serde_json::from_str(&to_json(&entries(), Encoding::Hex)).unwrap();
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+
+        assert_eq!(keys, ["count", "flag", "name", "owner"]);
+        assert_eq!(
+            json["owner"],
+            json!({"type": "binary", "value": "deadbeef"})
+        );
+        assert_eq!(json["count"], json!({"type": "integer", "value": 42}));
+    }
+}
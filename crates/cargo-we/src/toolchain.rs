@@ -0,0 +1,182 @@
+//! Build parameters of the contract, read from `[package.metadata.we]` of its `Cargo.toml`:
+//!
+//! ```toml
+//! [package.metadata.we]
+//! toolchain = "nightly"
+//! build-std = ["std", "panic_abort"]
+//! target-features = ["bulk-memory", "multivalue"]
+//! stack-size = 16
+//! initial-memory = 131072
+//! max-memory = 1048576
+//! ```
+//!
+//! The members of a workspace share the parameters of `[workspace.metadata.we]`.
+
+use serde::Deserialize;
+use std::{path::PathBuf, process::Command};
+
+pub const TARGET: &str = "wasm32-unknown-unknown";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct BuildConfig {
+    /// Toolchain of rustup, a nightly one is required by `build-std`.
+    pub toolchain: String,
+    /// Crates of the standard library rebuilt with the contract.
+    pub build_std: Vec<String>,
+    pub target_features: Vec<String>,
+    /// Size of the stack in bytes.
+    pub stack_size: u32,
+    /// Memory in bytes, the node provides 2 to 16 pages of 64 KiB.
+    pub initial_memory: u32,
+    pub max_memory: u32,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        BuildConfig {
+            toolchain: "nightly".to_string(),
+            build_std: vec!["std".to_string(), "panic_abort".to_string()],
+            target_features: vec!["bulk-memory".to_string(), "multivalue".to_string()],
+            stack_size: 16,
+            initial_memory: 131072,
+            max_memory: 1048576,
+        }
+    }
+}
+
+impl BuildConfig {
+    /// Reads the `we` tables of the workspace and the package metadata,
+    /// the keys of the package take precedence.
+    pub fn from_metadata(
+        workspace: &serde_json::Value,
+        package: &serde_json::Value,
+    ) -> Result<Self, String> {
+        let mut we = serde_json::Map::new();
+        for metadata in [workspace, package] {
+            if let Some(table) = metadata.get("we").and_then(|we| we.as_object()) {
+                we.extend(table.clone());
+            }
+        }
+
+        serde_json::from_value(serde_json::Value::Object(we))
+            .map_err(|error| format!("can't parse [package.metadata.we]: {}", error))
+    }
+
+    /// Arguments of `cargo build`, the names are stripped unless `debug` is set.
+    pub fn cargo_args(&self, debug: bool) -> Vec<String> {
+        let features = self
+            .target_features
+            .iter()
+            .map(|feature| format!("+{}", feature))
+            .collect::<Vec<_>>()
+            .join(",");
+        let link_args = format!(
+            "link-args=--no-entry --import-memory -zstack-size={} --initial-memory={} --max-memory={}",
+            self.stack_size, self.initial_memory, self.max_memory
+        );
+
+        let mut rustflags = vec![];
+        if !features.is_empty() {
+            rustflags.extend(["-C".to_string(), format!("target-feature={}", features)]);
+        }
+        rustflags.extend(["-C".to_string(), link_args]);
+
+        let mut args = vec![
+            format!("+{}", self.toolchain),
+            "build".to_string(),
+            "--release".to_string(),
+            "--message-format=json-render-diagnostics".to_string(),
+        ];
+        if !self.build_std.is_empty() {
+            args.push(format!("-Zbuild-std={}", self.build_std.join(",")));
+        }
+        args.push(format!("--target={}", TARGET));
+        args.push(format!(
+            "--config=target.{}.rustflags = {}",
+            TARGET,
+            serde_json::to_string(&rustflags).expect("Unable to serialize struct to JSON")
+        ));
+        if debug {
+            args.push("--config=profile.release.strip = false".to_string());
+        }
+
+        args
+    }
+
+    /// Checks that the toolchain and the sources of the standard library are installed.
+    pub fn check_toolchain(&self) -> Result<(), String> {
+        let install = format!(
+            "run `rustup toolchain install {} --component rust-src`",
+            self.toolchain
+        );
+
+        let sysroot = Command::new("rustc")
+            .args([
+                format!("+{}", self.toolchain).as_str(),
+                "--print",
+                "sysroot",
+            ])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .ok_or_else(|| {
+                format!(
+                    "the `{}` toolchain is not installed, {}",
+                    self.toolchain, install
+                )
+            })?;
+
+        let library = PathBuf::from(String::from_utf8_lossy(&sysroot.stdout).trim())
+            .join("lib/rustlib/src/rust/library");
+        if !self.build_std.is_empty() && !library.exists() {
+            return Err(format!(
+                "`rust-src` is not installed for the `{}` toolchain, run `rustup component add rust-src --toolchain {}`",
+                self.toolchain, self.toolchain
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn default_args() {
+        let config = BuildConfig::from_metadata(&json!(null), &json!({})).unwrap();
+
+        assert_eq!(
+            config.cargo_args(false),
+            [
+                "+nightly",
+                "build",
+                "--release",
+                "--message-format=json-render-diagnostics",
+                "-Zbuild-std=std,panic_abort",
+                "--target=wasm32-unknown-unknown",
+                "--config=target.wasm32-unknown-unknown.rustflags = [\"-C\",\"target-feature=+bulk-memory,+multivalue\",\"-C\",\"link-args=--no-entry --import-memory -zstack-size=16 --initial-memory=131072 --max-memory=1048576\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn package_metadata() {
+        let config = BuildConfig::from_metadata(
+            &json!({"we": {"toolchain": "nightly", "build-std": []}}),
+            &json!({"we": {"toolchain": "nightly-2024-05-01", "stack-size": 1024}}),
+        )
+        .unwrap();
+        let args = config.cargo_args(true);
+
+        assert_eq!(args[0], "+nightly-2024-05-01");
+        assert!(!args.iter().any(|arg| arg.starts_with("-Zbuild-std")));
+        assert!(args[5].contains("-zstack-size=1024 --initial-memory=131072"));
+        assert_eq!(args[6], "--config=profile.release.strip = false");
+
+        assert!(BuildConfig::from_metadata(&json!(null), &json!({"we": {"stack": 1}})).is_err());
+    }
+}
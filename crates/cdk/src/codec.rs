@@ -0,0 +1,501 @@
+//! Binary encoding of values stored in `Binary` storage entries.
+//!
+//! The encoding is compact, deterministic and does not allocate:
+//! * integers - fixed size, big-endian
+//! * `bool` - `0` or `1`
+//! * `Binary` and `String` - length as unsigned LEB128, then the bytes
+//! * `[u8; N]` - the bytes, without the length
+//! * `Option` - `0` for `None`, `1` followed by the value for `Some`
+//! * tuples and structs - the fields in order
+//! * enums - the index of the variant as `u8`, then its fields
+//!
+//! Values are written into a buffer provided by the caller and decoded without copying,
+//! `Binary` and `String` fields borrow the decoded bytes.
+//!
+//! A struct with `#[codec(version = N)]` is prefixed with its version,
+//! fields added in a later version are marked with `#[codec(since = N)]`
+//! and decoded as `Default::default()` from the entries written by earlier versions.
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! #[derive(Encode, Decode, Debug, PartialEq)]
+//! #[codec(version = 2)]
+//! struct Order<'a> {
+//!     owner: Binary<'a>,
+//!     amount: Integer,
+//!     #[codec(since = 2)]
+//!     expires: Option<Integer>,
+//! }
+//!
+//! #[action]
+//! fn place(amount: Integer) -> Result<()> {
+//!     let order = Order {
+//!         owner: tx!(sender),
+//!         amount,
+//!         expires: None,
+//!     };
+//!     let mut buffer = [0u8; 64];
+//!     let bytes = order.encode_to(&mut buffer)?;
+//!     set_storage!(binary :: "order" => bytes);
+//!     Ok(())
+//! }
+//!
+//! #[action]
+//! fn cancel() -> Result<()> {
+//!     let order = Order::decode_from(get_storage!(binary :: "order"))?;
+//!     require!(equals!(binary :: order.owner, tx!(sender)));
+//!     set_storage!(binary :: "order" => &[0u8; 0]);
+//!     Ok(())
+//! }
+//!
+//! let order = Order {
+//!     owner: &[1, 2, 3],
+//!     amount: 42,
+//!     expires: Some(100),
+//! };
+//! let mut buffer = [0u8; 64];
+//! let bytes = order.encode_to(&mut buffer).unwrap();
+//! assert_eq!(bytes, &[2, 3, 1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 42, 1, 0, 0, 0, 0, 0, 0, 0, 100]);
+//! assert_eq!(Order::decode_from(bytes), Ok(order));
+//!
+//! // Written by the first version of the contract
+//! let order = Order::decode_from(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 7]).unwrap();
+//! assert_eq!((order.amount, order.expires), (7, None));
+//! ```
+//!
+//! Variants are written as their index, new ones are added at the end of the enum:
+//! ```
+//! use we_cdk::*;
+//!
+//! #[derive(Encode, Decode, Debug, PartialEq)]
+//! enum Status<'a> {
+//!     Open,
+//!     Filled(Integer),
+//!     Cancelled { reason: String<'a> },
+//! }
+//!
+//! let mut buffer = [0u8; 16];
+//! let bytes = Status::Filled(5).encode_to(&mut buffer).unwrap();
+//! assert_eq!(bytes, &[1, 0, 0, 0, 0, 0, 0, 0, 5]);
+//! assert_eq!(Status::decode_from(&[2, 2, b'n', b'o']), Ok(Status::Cancelled { reason: "no" }));
+//! assert_eq!(Status::decode_from(&[3]), Err(codec::Error::InvalidValue));
+//! ```
+//!
+//! Actions take the types implementing [`Decode`] as `Binary` params, decoded before the body runs.
+//! Malformed params stop the call with [`crate::Error::ParseError`],
+//! the ABI names the decoded type in the `schema` of the argument
+//! and `#[derive(Decode)]` describes its encoding in the `types` of the contract JSON:
+//! ```
+//! use we_cdk::*;
+//!
+//! #[derive(Decode)]
+//! struct Bid<'a> {
+//!     lot: String<'a>,
+//!     price: Integer,
+//! }
+//!
+//! #[action]
+//! fn bid(bid: Bid) {
+//!     require!(bid.price > 0);
+//!     set_storage!(integer :: bid.lot => bid.price);
+//! }
+//!
+//! # #[cfg(feature = "test-env")]
+//! # {
+//! let params = [3, b'c', b'a', b'r', 0, 0, 0, 0, 0, 0, 0, 9];
+//! assert_eq!(bid(params.as_ptr(), params.len()), 0);
+//! assert_eq!(bid(params.as_ptr(), 5), Error::ParseError.code());
+//! # }
+//! ```
+//!
+//! Textual forms of bytes are converted by [`base58`], [`base64`] and [`hex`] the same way,
+//! into buffers of the caller and without host calls.
+use core::fmt;
+
+pub mod base58;
+pub mod base64;
+pub mod hex;
+
+/// Errors of encoding and decoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The buffer is too small for the encoded value.
+    BufferTooSmall,
+    /// The bytes end before the value.
+    UnexpectedEnd,
+    /// The bytes do not encode a value of the type.
+    InvalidValue,
+    /// The value was written by a later version of the type.
+    UnknownVersion,
+    /// Bytes are left after the value.
+    TrailingBytes,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Error::BufferTooSmall => "Buffer is too small for the encoded value",
+            Error::UnexpectedEnd => "Unexpected end of the encoded value",
+            Error::InvalidValue => "Invalid encoded value",
+            Error::UnknownVersion => "Unknown version of the encoded value",
+            Error::TrailingBytes => "Trailing bytes after the encoded value",
+        };
+        f.write_str(message)
+    }
+}
+
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::BufferTooSmall => crate::Error::MemoryError,
+            _ => crate::Error::ParseError,
+        }
+    }
+}
+
+/// Writes the encoded values into a buffer.
+pub struct Writer<'b> {
+    buffer: &'b mut [u8],
+    position: usize,
+}
+
+impl<'b> Writer<'b> {
+    pub fn new(buffer: &'b mut [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// Appends the bytes.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let end = self.position + bytes.len();
+        let target = self
+            .buffer
+            .get_mut(self.position..end)
+            .ok_or(Error::BufferTooSmall)?;
+        target.copy_from_slice(bytes);
+        self.position = end;
+        Ok(())
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> Result<(), Error> {
+        self.write(&[value])
+    }
+
+    /// Appends the length as unsigned LEB128.
+    pub fn write_len(&mut self, len: usize) -> Result<(), Error> {
+        let mut len = u32::try_from(len).map_err(|_| Error::InvalidValue)?;
+        loop {
+            let byte = (len & 0x7f) as u8;
+            len >>= 7;
+            if len == 0 {
+                return self.write_u8(byte);
+            }
+            self.write_u8(byte | 0x80)?;
+        }
+    }
+
+    /// Get the bytes written so far.
+    pub fn written(&self) -> &[u8] {
+        &self.buffer[..self.position]
+    }
+
+    /// Get the bytes written, borrowed for the lifetime of the buffer.
+    pub fn into_written(self) -> &'b [u8] {
+        &self.buffer[..self.position]
+    }
+}
+
+/// Reads the encoded values from bytes.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Takes the next `len` bytes.
+    pub fn read(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if len > self.bytes.len() {
+            return Err(Error::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read(1)?[0])
+    }
+
+    /// Reads a length written as unsigned LEB128, only the shortest form is accepted.
+    pub fn read_len(&mut self) -> Result<usize, Error> {
+        let mut len: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_u8()?;
+            let bits = u32::from(byte & 0x7f);
+            if shift == 28 && bits > 0x0f {
+                return Err(Error::InvalidValue);
+            }
+            len |= bits << shift;
+            if byte & 0x80 == 0 {
+                // A zero last byte could be omitted
+                if byte == 0 && shift > 0 {
+                    return Err(Error::InvalidValue);
+                }
+                return Ok(len as usize);
+            }
+        }
+        Err(Error::InvalidValue)
+    }
+
+    /// Get the bytes not read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Checks that all the bytes have been read.
+    pub fn finish(&self) -> Result<(), Error> {
+        match self.bytes.is_empty() {
+            true => Ok(()),
+            false => Err(Error::TrailingBytes),
+        }
+    }
+}
+
+/// Types that can be encoded, usually derived with `#[derive(Encode)]`.
+pub trait Encode {
+    /// Writes the value.
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error>;
+
+    /// Encodes the value into the buffer and get the written bytes.
+    fn encode_to<'b>(&self, buffer: &'b mut [u8]) -> Result<&'b [u8], Error> {
+        let mut writer = Writer::new(buffer);
+        self.encode(&mut writer)?;
+        Ok(writer.into_written())
+    }
+}
+
+/// Types that can be decoded from bytes living for `'a`, usually derived with `#[derive(Decode)]`.
+pub trait Decode<'a>: Sized {
+    /// Reads the value.
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, Error>;
+
+    /// Decodes the value from all the bytes.
+    fn decode_from(bytes: &'a [u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+        let value = Self::decode(&mut reader)?;
+        reader.finish()?;
+        Ok(value)
+    }
+}
+
+macro_rules! integers {
+    ($( $ty:ty ),*) => {
+        $(
+            impl Encode for $ty {
+                fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+                    writer.write(&self.to_be_bytes())
+                }
+            }
+
+            impl<'a> Decode<'a> for $ty {
+                fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+                    let bytes = reader.read(core::mem::size_of::<$ty>())?;
+                    // The length has just been checked
+                    Ok(<$ty>::from_be_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+integers!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl Encode for bool {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.write_u8(*self as u8)
+    }
+}
+
+impl<'a> Decode<'a> for bool {
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+        match reader.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+impl Encode for [u8] {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.write_len(self.len())?;
+        writer.write(self)
+    }
+}
+
+impl<'a> Decode<'a> for &'a [u8] {
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+        let len = reader.read_len()?;
+        reader.read(len)
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        self.as_bytes().encode(writer)
+    }
+}
+
+impl<'a> Decode<'a> for &'a str {
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+        let bytes = <&[u8]>::decode(reader)?;
+        core::str::from_utf8(bytes).map_err(|_| Error::InvalidValue)
+    }
+}
+
+impl<const N: usize> Encode for [u8; N] {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.write(self)
+    }
+}
+
+impl<'a, const N: usize> Decode<'a> for [u8; N] {
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+        // The length has just been checked
+        Ok(reader.read(N)?.try_into().unwrap())
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        T::encode(self, writer)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        match self {
+            None => writer.write_u8(0),
+            Some(value) => {
+                writer.write_u8(1)?;
+                value.encode(writer)
+            }
+        }
+    }
+}
+
+impl<'a, T: Decode<'a>> Decode<'a> for Option<T> {
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+        match reader.read_u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(reader)?)),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+macro_rules! tuples {
+    ($( ($( $name:ident ),+) ),*) => {
+        $(
+            impl<$( $name: Encode ),+> Encode for ($( $name, )+) {
+                #[allow(non_snake_case)]
+                fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+                    let ($( $name, )+) = self;
+                    $( $name.encode(writer)?; )+
+                    Ok(())
+                }
+            }
+
+            impl<'a, $( $name: Decode<'a> ),+> Decode<'a> for ($( $name, )+) {
+                fn decode(reader: &mut Reader<'a>) -> Result<Self, Error> {
+                    Ok(($( $name::decode(reader)?, )+))
+                }
+            }
+        )*
+    };
+}
+
+tuples!((A), (A, B), (A, B, C), (A, B, C, D));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<'a, T>(value: T, expected: &[u8], buffer: &'a mut [u8])
+    where
+        T: Encode + Decode<'a> + PartialEq + fmt::Debug,
+    {
+        let bytes = value.encode_to(buffer).unwrap();
+        assert_eq!(bytes, expected);
+        assert_eq!(T::decode_from(bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn primitives() {
+        round_trip(42i64, &[0, 0, 0, 0, 0, 0, 0, 42], &mut [0; 16]);
+        round_trip(
+            -2i64,
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe],
+            &mut [0; 16],
+        );
+        round_trip(0x0102u16, &[1, 2], &mut [0; 16]);
+        round_trip(true, &[1], &mut [0; 16]);
+        round_trip(&b"ab"[..], &[2, b'a', b'b'], &mut [0; 16]);
+        round_trip("we", &[2, b'w', b'e'], &mut [0; 16]);
+        round_trip([7u8; 3], &[7, 7, 7], &mut [0; 16]);
+        round_trip(Some(1u8), &[1, 1], &mut [0; 16]);
+        round_trip(None::<u8>, &[0], &mut [0; 16]);
+        round_trip((1u8, false, "x"), &[1, 0, 1, b'x'], &mut [0; 16]);
+    }
+
+    #[test]
+    fn lengths() {
+        for (len, expected) in [
+            (0, &[0x00][..]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (300, &[0xac, 0x02]),
+            (16384, &[0x80, 0x80, 0x01]),
+            (u32::MAX as usize, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+        ] {
+            let mut buffer = [0; 8];
+            let mut writer = Writer::new(&mut buffer);
+            writer.write_len(len).unwrap();
+            assert_eq!(writer.written(), expected);
+            assert_eq!(Reader::new(expected).read_len(), Ok(len));
+        }
+
+        // Only the shortest form is accepted
+        assert_eq!(
+            Reader::new(&[0x80, 0x00]).read_len(),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            Reader::new(&[0xff, 0xff, 0xff, 0xff, 0x1f]).read_len(),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(Reader::new(&[0x80]).read_len(), Err(Error::UnexpectedEnd));
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(bool::decode_from(&[2]), Err(Error::InvalidValue));
+        assert_eq!(i64::decode_from(&[0; 7]), Err(Error::UnexpectedEnd));
+        assert_eq!(u8::decode_from(&[1, 2]), Err(Error::TrailingBytes));
+        assert_eq!(<&str>::decode_from(&[1, 0xff]), Err(Error::InvalidValue));
+        assert_eq!(<&[u8]>::decode_from(&[3, 1]), Err(Error::UnexpectedEnd));
+        assert_eq!(Option::<u8>::decode_from(&[2, 1]), Err(Error::InvalidValue));
+        assert_eq!(42i64.encode_to(&mut [0; 7]), Err(Error::BufferTooSmall));
+        assert_eq!(
+            crate::Error::from(Error::InvalidValue),
+            crate::Error::ParseError
+        );
+    }
+}
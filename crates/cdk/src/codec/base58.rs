@@ -0,0 +1,207 @@
+//! Base58 encoding of bytes with the Bitcoin alphabet, used for addresses and ids.
+//!
+//! Unlike [`crate::host::base58`] and [`crate::host::to_base58_string`],
+//! the conversion runs in the contract and writes into a buffer of the caller.
+//! The conversion is quadratic in the length, which suits keys and ids.
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! // Key of the deposit of the address, `deposit_<address>`
+//! fn deposit_key<'b>(address: Address, buffer: &'b mut [u8]) -> Result<String<'b>> {
+//!     const PREFIX: &str = "deposit_";
+//!
+//!     let (prefix, encoded) = buffer.split_at_mut(PREFIX.len());
+//!     prefix.copy_from_slice(PREFIX.as_bytes());
+//!     let len = PREFIX.len() + codec::base58::encode(&address, encoded)?.len();
+//!     core::str::from_utf8(&buffer[..len]).map_err(|_| Error::ParseError)
+//! }
+//!
+//! #[action]
+//! fn deposit(amount: Integer) -> Result<()> {
+//!     let mut buffer = [0u8; 8 + codec::base58::encoded_len(26)];
+//!     let key = deposit_key(host::tx_sender()?, &mut buffer)?;
+//!     host::set_storage_int(key, amount)
+//! }
+//!
+//! # #[cfg(feature = "test-env")]
+//! # {
+//! let address = base58!(address => "3NzkzibVRkKUzaRzjUxndpTPvoBzQ3iLng3");
+//! let mut buffer = [0u8; 64];
+//! assert_eq!(
+//!     deposit_key(address, &mut buffer).unwrap(),
+//!     "deposit_3NzkzibVRkKUzaRzjUxndpTPvoBzQ3iLng3"
+//! );
+//! # }
+//! ```
+use super::Error;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Get the largest length of `len` bytes encoded as Base58.
+pub const fn encoded_len(len: usize) -> usize {
+    // log(256) / log(58) is below 1.38
+    len * 138 / 100 + 1
+}
+
+/// Get the largest length of the bytes decoded from `len` Base58 characters.
+pub const fn decoded_len(len: usize) -> usize {
+    // log(58) / log(256) is below 0.733
+    len * 733 / 1000 + 1
+}
+
+/// Encode the bytes into the buffer and get the written string.
+pub fn encode<'b>(bytes: &[u8], buffer: &'b mut [u8]) -> Result<&'b str, Error> {
+    // Leading zero bytes are written as leading ones
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    let digits = convert(&bytes[zeros..], buffer, 256, 58)?;
+    let output = finish(buffer, zeros, digits)?;
+
+    for digit in output.iter_mut() {
+        *digit = ALPHABET[*digit as usize];
+    }
+
+    // Only characters of the alphabet were written
+    Ok(unsafe { core::str::from_utf8_unchecked(output) })
+}
+
+/// Decode the string into the buffer and get the written bytes.
+pub fn decode<'b>(value: &str, buffer: &'b mut [u8]) -> Result<&'b [u8], Error> {
+    let value = value.as_bytes();
+    let zeros = value
+        .iter()
+        .take_while(|&&char| char == ALPHABET[0])
+        .count();
+
+    let mut digits = 0;
+    for &char in &value[zeros..] {
+        let digit = ALPHABET
+            .iter()
+            .position(|&digit| digit == char)
+            .ok_or(Error::InvalidValue)?;
+        digits = push(buffer, digits, digit as u32, 58, 256)?;
+    }
+
+    finish(buffer, zeros, digits).map(|output| &*output)
+}
+
+/// Convert the big-endian digits into the buffer as little-endian digits of the other base.
+fn convert(digits: &[u8], buffer: &mut [u8], from: u32, to: u32) -> Result<usize, Error> {
+    digits
+        .iter()
+        .try_fold(0, |len, &digit| push(buffer, len, digit as u32, from, to))
+}
+
+/// Multiply the number in the first `len` little-endian digits of the buffer
+/// by `from` and add `digit`, get the new number of digits.
+fn push(buffer: &mut [u8], mut len: usize, digit: u32, from: u32, to: u32) -> Result<usize, Error> {
+    let mut carry = digit;
+    for digit in buffer[..len].iter_mut() {
+        carry += *digit as u32 * from;
+        *digit = (carry % to) as u8;
+        carry /= to;
+    }
+    while carry > 0 {
+        *buffer.get_mut(len).ok_or(Error::BufferTooSmall)? = (carry % to) as u8;
+        len += 1;
+        carry /= to;
+    }
+    Ok(len)
+}
+
+/// Turn the little-endian digits into big-endian ones preceded by `zeros` zero digits.
+fn finish(buffer: &mut [u8], zeros: usize, digits: usize) -> Result<&mut [u8], Error> {
+    let output = buffer
+        .get_mut(..zeros + digits)
+        .ok_or(Error::BufferTooSmall)?;
+    output[..digits].reverse();
+    output.copy_within(..digits, zeros);
+    output[..zeros].fill(0);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vectors of Bitcoin Core, `base58_encode_decode.json`
+    const VECTORS: &[(&str, &str)] = &[
+        ("", ""),
+        ("61", "2g"),
+        ("626262", "a3gV"),
+        ("636363", "aPEr"),
+        ("73696d706c792061206c6f6e6720737472696e67", "2cFupjhnEsSn59qHXstmK2ffpLv2"),
+        ("00eb15231dfceb60925886b67d065299925915aeb172c06647", "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L"),
+        ("516b6fcd0f", "ABnLTmg"),
+        ("bf4f89001e670274dd", "3SEo3LWLoPntC"),
+        ("572e4794", "3EFU7m"),
+        ("ecac89cad93923c02321", "EJDM8drfXA6uyA"),
+        ("10c8511e", "Rt5zm"),
+        ("00000000000000000000", "1111111111"),
+        (
+            "000111d38e5fc9071ffcd20b4a763cc9ae4f252bb4e48fd66a835e252ada93ff480d6dd43dc62a641155a5",
+            "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+        ),
+    ];
+
+    #[test]
+    fn vectors() {
+        let mut bytes = [0u8; 64];
+        let mut encoded = [0u8; 64];
+        let mut decoded = [0u8; 64];
+        for (hex, string) in VECTORS {
+            let bytes = crate::codec::hex::decode(hex, &mut bytes).unwrap();
+            assert_eq!(encode(bytes, &mut encoded), Ok(*string));
+            assert_eq!(decode(string, &mut decoded), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut encoded = [0u8; encoded_len(4)];
+        let mut decoded = [0u8; 4];
+        for value in 0..=u16::MAX {
+            let [high, low] = value.to_be_bytes();
+            for bytes in [
+                &[high][..],
+                &[high, low],
+                &[0, high, low],
+                &[high, 0, 0, low],
+            ] {
+                let string = encode(bytes, &mut encoded).unwrap();
+                assert!(string.len() <= encoded_len(bytes.len()));
+                assert_eq!(decode(string, &mut decoded).unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn lengths() {
+        // The longest ids and addresses fit into the buffers sized by the bounds
+        let mut encoded = [0u8; encoded_len(32)];
+        let string = encode(&[0xff; 32], &mut encoded).unwrap();
+        assert_eq!(string, "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG");
+
+        let mut decoded = [0u8; 32];
+        assert!(decoded_len(string.len()) >= 32);
+        assert_eq!(decode(string, &mut decoded), Ok(&[0xff; 32][..]));
+        assert_eq!(encode(&[0; 32], &mut encoded).unwrap(), "1".repeat(32));
+    }
+
+    #[test]
+    fn malformed() {
+        let mut buffer = [0u8; 4];
+        for string in ["0", "O", "I", "l", "2g+", "2 g"] {
+            assert_eq!(
+                decode(string, &mut buffer),
+                Err(Error::InvalidValue),
+                "{string}"
+            );
+        }
+        assert_eq!(decode("11111", &mut buffer), Err(Error::BufferTooSmall));
+        assert_eq!(decode("7bWpTW", &mut buffer), Err(Error::BufferTooSmall));
+        assert_eq!(encode(&[0xff; 3], &mut buffer), Err(Error::BufferTooSmall));
+        assert_eq!(encode(&[0; 5], &mut buffer), Err(Error::BufferTooSmall));
+    }
+}
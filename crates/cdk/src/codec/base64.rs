@@ -0,0 +1,170 @@
+//! Base64 encoding of bytes with the standard alphabet and padding, as used by the node.
+//!
+//! Decoding is strict: the padding is required
+//! and the unused bits of the last character must be zero.
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! #[action]
+//! fn set_signature(signature: String) -> Result<()> {
+//!     let mut buffer = [0u8; codec::base64::decoded_len(88)];
+//!     let signature = codec::base64::decode(signature, &mut buffer)?;
+//!     set_storage!(binary :: "signature" => signature);
+//!     Ok(())
+//! }
+//! ```
+use super::Error;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PADDING: u8 = b'=';
+
+/// Get the length of `len` bytes encoded as Base64.
+pub const fn encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
+}
+
+/// Get the largest length of the bytes decoded from `len` Base64 characters.
+pub const fn decoded_len(len: usize) -> usize {
+    len / 4 * 3
+}
+
+/// Encode the bytes into the buffer and get the written string.
+pub fn encode<'b>(bytes: &[u8], buffer: &'b mut [u8]) -> Result<&'b str, Error> {
+    let output = buffer
+        .get_mut(..encoded_len(bytes.len()))
+        .ok_or(Error::BufferTooSmall)?;
+
+    for (chunk, chars) in bytes.chunks(3).zip(output.chunks_exact_mut(4)) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, &byte)| {
+                group | (byte as u32) << (16 - 8 * index)
+            });
+        for (index, char) in chars.iter_mut().enumerate() {
+            *char = match index <= chunk.len() {
+                true => ALPHABET[((group >> (18 - 6 * index)) & 0x3f) as usize],
+                false => PADDING,
+            };
+        }
+    }
+
+    // Only characters of the alphabet were written
+    Ok(unsafe { core::str::from_utf8_unchecked(output) })
+}
+
+/// Decode the string into the buffer and get the written bytes.
+pub fn decode<'b>(value: &str, buffer: &'b mut [u8]) -> Result<&'b [u8], Error> {
+    let value = value.as_bytes();
+    if !value.chunks_exact(4).remainder().is_empty() {
+        return Err(Error::InvalidValue);
+    }
+
+    let padding = value
+        .iter()
+        .rev()
+        .take_while(|&&char| char == PADDING)
+        .count();
+    if padding > 2 {
+        return Err(Error::InvalidValue);
+    }
+
+    let output = buffer
+        .get_mut(..decoded_len(value.len()) - padding)
+        .ok_or(Error::BufferTooSmall)?;
+
+    let chars = &value[..value.len() - padding];
+    for (chunk, bytes) in chars.chunks(4).zip(output.chunks_mut(3)) {
+        let mut group = 0u32;
+        for (index, &char) in chunk.iter().enumerate() {
+            group |= (sextet(char)? as u32) << (18 - 6 * index);
+        }
+        // The bits of the last character past the bytes must be zero
+        if (group << (8 * bytes.len())) & 0xff_ffff != 0 {
+            return Err(Error::InvalidValue);
+        }
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = (group >> (16 - 8 * index)) as u8;
+        }
+    }
+
+    Ok(output)
+}
+
+fn sextet(char: u8) -> Result<u8, Error> {
+    match char {
+        b'A'..=b'Z' => Ok(char - b'A'),
+        b'a'..=b'z' => Ok(char - b'a' + 26),
+        b'0'..=b'9' => Ok(char - b'0' + 52),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(Error::InvalidValue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4648, section 10
+    const VECTORS: &[(&[u8], &str)] = &[
+        (b"", ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"foob", "Zm9vYg=="),
+        (b"fooba", "Zm9vYmE="),
+        (b"foobar", "Zm9vYmFy"),
+        (&[0xfb, 0xff, 0xbf], "+/+/"),
+        (&[0x00, 0x10, 0x83, 0x10, 0x51, 0x87], "ABCDEFGH"),
+    ];
+
+    #[test]
+    fn vectors() {
+        let mut encoded = [0u8; 16];
+        let mut decoded = [0u8; 16];
+        for (bytes, string) in VECTORS {
+            assert_eq!(encode(bytes, &mut encoded), Ok(*string));
+            assert_eq!(decode(string, &mut decoded), Ok(*bytes));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut encoded = [0u8; 8];
+        let mut decoded = [0u8; 6];
+        for value in 0..=u16::MAX {
+            let [high, low] = value.to_be_bytes();
+            for bytes in [
+                &[high][..],
+                &[high, low],
+                &[low, high, low],
+                &[high, 0, low, 0xff],
+            ] {
+                let string = encode(bytes, &mut encoded).unwrap();
+                assert_eq!(string.len(), encoded_len(bytes.len()));
+                assert_eq!(decode(string, &mut decoded).unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let mut buffer = [0u8; 6];
+        assert_eq!(decode("Zm9", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("Zg=", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("Z===", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("Zm=v", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("Zm9-", &mut buffer), Err(Error::InvalidValue));
+        // Non-zero bits past the decoded bytes
+        assert_eq!(decode("Zh==", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("Zm9=", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(
+            decode("Zm9vYmFyZg==", &mut buffer),
+            Err(Error::BufferTooSmall)
+        );
+        assert_eq!(encode(&[0; 5], &mut buffer), Err(Error::BufferTooSmall));
+    }
+}
@@ -0,0 +1,127 @@
+//! Hex encoding of bytes, written in lowercase and read in either case.
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! #[action]
+//! fn set_hash(hash: String) -> Result<()> {
+//!     let mut buffer = [0u8; 32];
+//!     let hash = codec::hex::decode(hash, &mut buffer)?;
+//!     require!(hash.len() == 32);
+//!     set_storage!(binary :: "hash" => hash);
+//!     Ok(())
+//! }
+//! ```
+use super::Error;
+
+const ALPHABET: &[u8; 16] = b"0123456789abcdef";
+
+/// Get the length of `len` bytes encoded as hex.
+pub const fn encoded_len(len: usize) -> usize {
+    len * 2
+}
+
+/// Get the length of the bytes decoded from `len` hex characters.
+pub const fn decoded_len(len: usize) -> usize {
+    len / 2
+}
+
+/// Encode the bytes into the buffer and get the written string.
+pub fn encode<'b>(bytes: &[u8], buffer: &'b mut [u8]) -> Result<&'b str, Error> {
+    let output = buffer
+        .get_mut(..encoded_len(bytes.len()))
+        .ok_or(Error::BufferTooSmall)?;
+
+    for (byte, chars) in bytes.iter().zip(output.chunks_exact_mut(2)) {
+        chars[0] = ALPHABET[(byte >> 4) as usize];
+        chars[1] = ALPHABET[(byte & 0x0f) as usize];
+    }
+
+    // Only characters of the alphabet were written
+    Ok(unsafe { core::str::from_utf8_unchecked(output) })
+}
+
+/// Decode the string into the buffer and get the written bytes.
+pub fn decode<'b>(value: &str, buffer: &'b mut [u8]) -> Result<&'b [u8], Error> {
+    let value = value.as_bytes();
+    if value.len() % 2 == 1 {
+        return Err(Error::InvalidValue);
+    }
+
+    let output = buffer
+        .get_mut(..decoded_len(value.len()))
+        .ok_or(Error::BufferTooSmall)?;
+
+    for (byte, chars) in output.iter_mut().zip(value.chunks_exact(2)) {
+        *byte = (digit(chars[0])? << 4) | digit(chars[1])?;
+    }
+
+    Ok(output)
+}
+
+fn digit(char: u8) -> Result<u8, Error> {
+    match char {
+        b'0'..=b'9' => Ok(char - b'0'),
+        b'a'..=b'f' => Ok(char - b'a' + 10),
+        b'A'..=b'F' => Ok(char - b'A' + 10),
+        _ => Err(Error::InvalidValue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4648, section 10
+    const VECTORS: &[(&[u8], &str)] = &[
+        (b"", ""),
+        (b"f", "66"),
+        (b"fo", "666f"),
+        (b"foo", "666f6f"),
+        (b"foob", "666f6f62"),
+        (b"fooba", "666f6f6261"),
+        (b"foobar", "666f6f626172"),
+        (&[0x00, 0x0f, 0xa0, 0xff], "000fa0ff"),
+    ];
+
+    #[test]
+    fn vectors() {
+        let mut encoded = [0u8; 16];
+        let mut decoded = [0u8; 16];
+        for (bytes, string) in VECTORS {
+            assert_eq!(encode(bytes, &mut encoded), Ok(*string));
+            assert_eq!(decode(string, &mut decoded), Ok(*bytes));
+        }
+        assert_eq!(
+            decode("000FA0FF", &mut decoded),
+            Ok(&[0x00, 0x0f, 0xa0, 0xff][..])
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut encoded = [0u8; 4];
+        let mut decoded = [0u8; 2];
+        for value in 0..=u16::MAX {
+            let bytes = value.to_be_bytes();
+            for bytes in [&bytes[..1], &bytes[..]] {
+                let string = encode(bytes, &mut encoded).unwrap();
+                assert_eq!(decode(string, &mut decoded).unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let mut buffer = [0u8; 4];
+        assert_eq!(decode("abc", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("0g", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(decode("0x00", &mut buffer), Err(Error::InvalidValue));
+        assert_eq!(
+            decode("0000000000", &mut buffer),
+            Err(Error::BufferTooSmall)
+        );
+        assert_eq!(encode(&[0; 3], &mut buffer), Err(Error::BufferTooSmall));
+    }
+}
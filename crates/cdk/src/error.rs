@@ -0,0 +1,378 @@
+//! Errors returned by the host.
+//!
+//! Every binding of the virtual machine reports failures as a numeric code,
+//! [`Error`] gives these codes names so that they can be matched and propagated with `?`.
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! fn owner() -> Result<Binary<'static>> {
+//!     let owner = host::get_storage_binary(THIS, "owner")?;
+//!     host::require(!owner.is_empty(), "Owner is not set")?;
+//!     Ok(owner)
+//! }
+//!
+//! #[action]
+//! fn withdraw(amount: Integer) -> Result<()> {
+//!     let owner = owner()?;
+//!     require!(equals!(binary :: owner, tx!(sender)));
+//!     transfer!(address => owner, amount);
+//!     Ok(())
+//! }
+//! ```
+use core::fmt;
+
+/// Result of a fallible call, the error defaults to [`Error`].
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+macro_rules! codes {
+    (
+        $(
+            #[error($message:literal)]
+            #[code($code:literal)]
+            $variant:ident,
+        )+
+    ) => {
+        /// Error codes of the virtual machine.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum Error {
+            $(
+                #[doc = $message]
+                $variant,
+            )+
+            /// Code unknown to this version of the crate.
+            Other(i32),
+        }
+
+        impl Error {
+            /// Get the numeric code of the error.
+            pub fn code(&self) -> i32 {
+                match self {
+                    $( Error::$variant => $code, )+
+                    Error::Other(code) => *code,
+                }
+            }
+
+            /// Get the description of the error.
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $( Error::$variant => $message, )+
+                    Error::Other(_) => "Unknown error",
+                }
+            }
+        }
+
+        impl ContractError for Error {
+            const CODES: &'static [(i32, &'static str, &'static str)] = &[
+                $( ($code, stringify!($variant), $message), )+
+            ];
+
+            fn code(&self) -> i32 {
+                Error::code(self)
+            }
+
+            fn message(&self) -> &'static str {
+                Error::message(self)
+            }
+
+            fn report(&self) -> &'static str {
+                match self {
+                    $( Error::$variant => concat!("Code: ", $code, ". Error: ", $message, "."), )+
+                    Error::Other(_) => "Unknown error",
+                }
+            }
+        }
+
+        impl From<i32> for Error {
+            fn from(code: i32) -> Self {
+                match code {
+                    $( $code => Error::$variant, )+
+                    code => Error::Other(code),
+                }
+            }
+        }
+    };
+}
+
+codes! {
+    // ExecutableError
+    #[error("Failed to parse and validate Wasm bytecode")]
+    #[code(100)]
+    InvalidBytecode,
+    #[error("Could not found constructor")]
+    #[code(101)]
+    ConstructorNotFound,
+    #[error("An error that may occur upon operating with virtual or linear memory")]
+    #[code(102)]
+    MemoryError,
+    #[error("Limits limit the amount of memory well below u32::MAX")]
+    #[code(103)]
+    MemoryLimits,
+    #[error("An error that may occur upon operating with Linker instances")]
+    #[code(104)]
+    LinkerError,
+    #[error("Failed to instantiate and start the Wasm bytecode")]
+    #[code(105)]
+    InstantiateFailed,
+    #[error("Global heap base not found")]
+    #[code(106)]
+    HeapBaseNotFound,
+    #[error("Could not find function")]
+    #[code(107)]
+    FuncNotFound,
+    #[error("Invalid number of arguments")]
+    #[code(108)]
+    InvalidNumArgs,
+    #[error("Failed to parse function argument")]
+    #[code(109)]
+    FailedParseFuncArgs,
+    #[error("Failed to parse (DataEntry arguments or Payments)")]
+    #[code(110)]
+    FailedDeserialize,
+    #[error("Failed during execution")]
+    #[code(111)]
+    FailedExec,
+    #[error("Call stack overflow error")]
+    #[code(112)]
+    StackOverflow,
+    #[error("Failed receiving Module")]
+    #[code(113)]
+    ModuleNotFound,
+    #[error("Fuel metering is disabled")]
+    #[code(114)]
+    FuelMeteringDisabled,
+    // JvmError
+    #[error("Failed receiving JVM")]
+    #[code(200)]
+    JvmNotFound,
+    #[error("Failed receiving JVM Callback")]
+    #[code(201)]
+    JvmCallbackNotFound,
+    #[error("Failed attaches the current thread to the Java VM")]
+    #[code(202)]
+    AttachCurrentThread,
+    #[error("Failed JVM method call")]
+    #[code(203)]
+    MethodCall,
+    #[error("Failed byte array conversion")]
+    #[code(204)]
+    ByteArrayConversion,
+    #[error("Failed receiving JavaVM interface")]
+    #[code(205)]
+    GetJavaVM,
+    #[error("Error callback new_global_ref")]
+    #[code(206)]
+    NewGlobalRef,
+    #[error("Couldn't create java byte array")]
+    #[code(207)]
+    NewByteArray,
+    #[error("Couldn't create java string")]
+    #[code(208)]
+    NewString,
+    #[error("Failed to receive object")]
+    #[code(209)]
+    ReceiveObject,
+    #[error("Failed to receive byte")]
+    #[code(210)]
+    ReceiveByte,
+    #[error("Failed to receive integer")]
+    #[code(211)]
+    ReceiveInt,
+    #[error("Failed to receive long")]
+    #[code(212)]
+    ReceiveLong,
+    #[error("Failed to receive boolean")]
+    #[code(213)]
+    ReceiveBoolean,
+    // RuntimeError
+    #[error("Exception thrown during contract execution")]
+    #[code(300)]
+    Exception,
+    #[error("Failed receiving Memory")]
+    #[code(301)]
+    MemoryNotFound,
+    #[error("Failed to retrieve UTF-8 string")]
+    #[code(302)]
+    Utf8Error,
+    #[error("Incorrect result obtained during contract execution")]
+    #[code(303)]
+    InvalidResult,
+    #[error("Error converting Base58 string to bytes")]
+    #[code(304)]
+    Base58Error,
+    #[error("Error when converting numeric types")]
+    #[code(305)]
+    ConvertingNumericTypes,
+    #[error("AssetHolder type not found")]
+    #[code(306)]
+    AssetHolderTypeNotFound,
+    #[error("Address version not found")]
+    #[code(307)]
+    AddressVersionNotFound,
+    #[error("Value parsing error")]
+    #[code(308)]
+    ParseError,
+}
+
+impl From<Error> for i32 {
+    fn from(error: Error) -> Self {
+        error.code()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Code: {}. Error: {}.", self.code(), self.message())
+    }
+}
+
+/// Errors of a contract with stable codes and messages.
+///
+/// Usually derived, the code of a variant is its discriminant and the message is set by `#[error]`.
+/// Codes must not be zero, which means success, and must not overlap with the codes
+/// of the host (`100..=399`), so that the reason of a failure can be told apart.
+///
+/// # Usage
+/// ```
+/// use we_cdk::*;
+///
+/// #[derive(ContractError)]
+/// enum VotingError {
+///     #[error("Caller is not the owner")]
+///     NotOwner = 1000,
+///     #[error("Voting is over")]
+///     Finished,
+/// }
+///
+/// #[action]
+/// fn finish() {
+///     let owner: Binary = get_storage!(binary :: "owner");
+///     require!(equals!(binary :: owner, tx!(sender)), VotingError::NotOwner);
+///     require!(!get_storage!(boolean :: "finished"), VotingError::Finished);
+///     set_storage!(boolean :: "finished" => true);
+/// }
+///
+/// assert_eq!(i32::from(VotingError::Finished), 1001);
+/// assert_eq!(VotingError::Finished.report(), "Code: 1001. Error: Voting is over.");
+/// ```
+pub trait ContractError {
+    /// Code, variant name and message of every error.
+    const CODES: &'static [(i32, &'static str, &'static str)];
+
+    /// Get the numeric code of the error.
+    fn code(&self) -> i32;
+
+    /// Get the description of the error.
+    fn message(&self) -> &'static str;
+
+    /// Get the message reported to the host, containing both the code and the description.
+    fn report(&self) -> &'static str;
+}
+
+/// Converts an error code into the return value of the enclosing function,
+/// which lets the macros stop execution both in actions and in functions returning [`Result`].
+#[doc(hidden)]
+pub trait Failure {
+    fn failure(code: i32) -> Self;
+}
+
+impl Failure for i32 {
+    fn failure(code: i32) -> Self {
+        code
+    }
+}
+
+impl<T, E: From<Error>> Failure for core::result::Result<T, E> {
+    fn failure(code: i32) -> Self {
+        Err(E::from(Error::from(code)))
+    }
+}
+
+/// Converts a contract error into the return value of the enclosing function,
+/// used by `require!` with an error instead of a message.
+#[doc(hidden)]
+pub trait Reject<C> {
+    fn reject(error: C) -> Self;
+}
+
+impl<C: ContractError> Reject<C> for i32 {
+    fn reject(error: C) -> Self {
+        error.code()
+    }
+}
+
+impl<T, E: From<C>, C: ContractError> Reject<C> for core::result::Result<T, E> {
+    fn reject(error: C) -> Self {
+        Err(E::from(error))
+    }
+}
+
+/// Reason of an unmet `require!`, reported to the host before the execution stops:
+/// a message stops it with `Error::Exception`, a contract error with its own code.
+#[doc(hidden)]
+pub trait Requirement<R> {
+    fn unmet(self) -> R;
+}
+
+impl<R: Failure> Requirement<R> for &str {
+    fn unmet(self) -> R {
+        match unsafe { crate::wevm::v0::bindings::require(self.as_ptr(), self.len()) } {
+            0 => R::failure(Error::Exception.code()),
+            error => R::failure(error),
+        }
+    }
+}
+
+impl<R: Reject<C>, C: ContractError> Requirement<R> for C {
+    fn unmet(self) -> R {
+        let report = self.report();
+        // The code of the error is returned even if the message was not reported,
+        // so that it stays the reason of the failure
+        unsafe { crate::wevm::v0::bindings::require(report.as_ptr(), report.len()) };
+        R::reject(self)
+    }
+}
+
+/// Returns `Ok` for the zero code of a successful call.
+pub(crate) fn check(code: i32) -> Result<()> {
+    match code {
+        0 => Ok(()),
+        code => Err(Error::from(code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes() {
+        assert_eq!(Error::from(300), Error::Exception);
+        assert_eq!(Error::from(1000), Error::Other(1000));
+        assert_eq!(i32::from(Error::ParseError), 308);
+        assert_eq!(Error::Other(1000).message(), "Unknown error");
+        assert_eq!(
+            Error::Base58Error.to_string(),
+            "Code: 304. Error: Error converting Base58 string to bytes."
+        );
+        assert_eq!(
+            Error::Base58Error.report(),
+            Error::Base58Error.to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn check_code() {
+        assert_eq!(check(0), Ok(()));
+        assert_eq!(check(302), Err(Error::Utf8Error));
+        assert_eq!(check(-1), Err(Error::Other(-1)));
+    }
+
+    #[test]
+    fn failure() {
+        assert_eq!(<i32 as Failure>::failure(300), 300);
+        assert_eq!(<Result<()> as Failure>::failure(300), Err(Error::Exception));
+        assert_eq!(<Result<u8, i32> as Failure>::failure(308), Err(308));
+    }
+}
@@ -0,0 +1,691 @@
+//! Fallible wrappers over the host bindings.
+//!
+//! Unlike the macros, these are ordinary functions returning [`Result`],
+//! so contract logic can be split into helper functions and composed with `?`.
+//! Data returned by the host stays in the memory of the contract until the end of the call.
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! fn increment(key: String) -> Result<Integer> {
+//!     let value = match host::contains_key(THIS, key)? {
+//!         true => host::get_storage_int(THIS, key)?,
+//!         false => 0,
+//!     };
+//!     host::set_storage_int(key, value + 1)?;
+//!     Ok(value + 1)
+//! }
+//!
+//! #[action]
+//! fn _constructor() -> Result<()> {
+//!     let count = increment("count")?;
+//!     host::require(count < 10, "Too many calls")
+//! }
+//! ```
+use crate::{
+    error::{check, Error, Result},
+    ids::LeaseRecipient,
+    wevm, Address, Alias, AssetId, Binary, Boolean, ContractId, Integer, String, SYSTEM_TOKEN,
+    THIS,
+};
+
+/// Holder of assets addressed by the v1 bindings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Holder<'a> {
+    Address(Address<'a>),
+    Alias(Alias<'a>),
+    Contract(ContractId<'a>),
+}
+
+impl<'a> Holder<'a> {
+    /// Get the bytes, type and version of the holder as expected by the host.
+    pub fn raw(&self) -> (&'a [u8], u32, u32) {
+        match self {
+            Holder::Address(address) => (address.as_bytes(), 0, 1),
+            Holder::Alias(alias) => (alias.as_str().as_bytes(), 0, 2),
+            Holder::Contract(contract_id) => (contract_id.as_bytes(), 1, 1),
+        }
+    }
+}
+
+impl<'a> From<Address<'a>> for Holder<'a> {
+    fn from(address: Address<'a>) -> Self {
+        Holder::Address(address)
+    }
+}
+
+impl<'a> From<Alias<'a>> for Holder<'a> {
+    fn from(alias: Alias<'a>) -> Self {
+        Holder::Alias(alias)
+    }
+}
+
+impl<'a> From<ContractId<'a>> for Holder<'a> {
+    fn from(contract_id: ContractId<'a>) -> Self {
+        Holder::Contract(contract_id)
+    }
+}
+
+/// Get the bytes of the asset, `None` stands for the system token.
+fn asset(asset_id: Option<AssetId<'_>>) -> &[u8] {
+    match asset_id {
+        Some(asset_id) => asset_id.as_bytes(),
+        None => SYSTEM_TOKEN,
+    }
+}
+
+unsafe fn bytes(result: (i32, *const u8, usize)) -> Result<Binary<'static>> {
+    let (error, ptr, len) = result;
+    check(error)?;
+    Ok(core::slice::from_raw_parts(ptr, len))
+}
+
+unsafe fn string(result: (i32, *const u8, usize)) -> Result<String<'static>> {
+    let bytes = bytes(result)?;
+    Ok(core::str::from_utf8_unchecked(bytes))
+}
+
+fn value<T>(result: (i32, T)) -> Result<T> {
+    let (error, value) = result;
+    check(error)?;
+    Ok(value)
+}
+
+// Asset
+
+/// Get the balance of the asset, a `None` holder stands for the current contract.
+/// A `None` asset stands for the system token.
+pub fn get_balance(asset_id: Option<AssetId>, holder: Option<Holder>) -> Result<Integer> {
+    let asset_id = asset(asset_id);
+    unsafe {
+        match holder {
+            Some(holder) => {
+                let (holder, type_, version) = holder.raw();
+                value(wevm::v1::bindings::get_balance(
+                    asset_id.as_ptr(),
+                    asset_id.len(),
+                    holder.as_ptr(),
+                    holder.len(),
+                    type_,
+                    version,
+                ))
+            }
+            None => value(wevm::v0::bindings::get_balance(
+                asset_id.as_ptr(),
+                asset_id.len(),
+                THIS.as_ptr(),
+                THIS.len(),
+            )),
+        }
+    }
+}
+
+/// Transfer the asset, a `None` asset stands for the system token.
+pub fn transfer<'a>(
+    asset_id: Option<AssetId>,
+    recipient: impl Into<Holder<'a>>,
+    amount: Integer,
+) -> Result<()> {
+    let asset_id = asset(asset_id);
+    let (recipient, type_, version) = recipient.into().raw();
+    check(unsafe {
+        wevm::v1::bindings::transfer(
+            asset_id.as_ptr(),
+            asset_id.len(),
+            recipient.as_ptr(),
+            recipient.len(),
+            type_,
+            version,
+            amount,
+        )
+    })
+}
+
+/// Issue the asset and get its id.
+pub fn issue(
+    name: String,
+    description: String,
+    quantity: Integer,
+    decimals: Integer,
+    is_reissuable: Boolean,
+) -> Result<AssetId<'static>> {
+    unsafe {
+        bytes(wevm::v1::bindings::issue(
+            name.as_ptr(),
+            name.len(),
+            description.as_ptr(),
+            description.len(),
+            quantity,
+            decimals,
+            is_reissuable,
+        ))
+        .map(AssetId::new_unchecked)
+    }
+}
+
+/// Burn the asset.
+pub fn burn(asset_id: AssetId, amount: Integer) -> Result<()> {
+    check(unsafe { wevm::v0::bindings::burn(asset_id.as_ptr(), asset_id.len(), amount) })
+}
+
+/// Reissue the asset.
+pub fn reissue(asset_id: AssetId, amount: Integer, is_reissuable: Boolean) -> Result<()> {
+    check(unsafe {
+        wevm::v0::bindings::reissue(asset_id.as_ptr(), asset_id.len(), amount, is_reissuable)
+    })
+}
+
+// Block
+
+fn block(field: &str) -> Result<Integer> {
+    unsafe {
+        let value = bytes(wevm::v1::bindings::block(field.as_ptr(), field.len()))?;
+        let value = bytes(wevm::v0::bindings::to_le_bytes(value.as_ptr(), value.len()))?;
+        match value.len() {
+            8 => Ok(core::ptr::read_unaligned(value.as_ptr() as *const i64)),
+            _ => Err(Error::ConvertingNumericTypes),
+        }
+    }
+}
+
+/// Get the timestamp of the current block.
+pub fn block_timestamp() -> Result<Integer> {
+    block("timestamp")
+}
+
+/// Get the height of the current block.
+pub fn block_height() -> Result<Integer> {
+    block("height")
+}
+
+// Call contract
+
+/// Add an integer argument to the next contract call.
+pub fn call_arg_int(value: Integer) {
+    unsafe { wevm::v0::bindings::call_arg_int(value) }
+}
+
+/// Add a boolean argument to the next contract call.
+pub fn call_arg_bool(value: Boolean) {
+    unsafe { wevm::v0::bindings::call_arg_bool(value) }
+}
+
+/// Add a binary argument to the next contract call.
+pub fn call_arg_binary(value: Binary) -> Result<()> {
+    check(unsafe { wevm::v0::bindings::call_arg_binary(value.as_ptr(), value.len()) })
+}
+
+/// Add a string argument to the next contract call.
+pub fn call_arg_string(value: String) -> Result<()> {
+    check(unsafe { wevm::v0::bindings::call_arg_string(value.as_ptr(), value.len()) })
+}
+
+/// Attach a payment to the next contract call, a `None` asset stands for the system token.
+pub fn call_payment(asset_id: Option<AssetId>, amount: Integer) -> Result<()> {
+    let asset_id = asset(asset_id);
+    check(unsafe { wevm::v0::bindings::call_payment(asset_id.as_ptr(), asset_id.len(), amount) })
+}
+
+/// Call the contract with the previously added arguments and payments.
+pub fn call_contract(contract_id: ContractId, func_name: String) -> Result<()> {
+    check(unsafe {
+        wevm::v0::bindings::call_contract(
+            contract_id.as_ptr(),
+            contract_id.len(),
+            func_name.as_ptr(),
+            func_name.len(),
+        )
+    })
+}
+
+/// Call the contract with serialized arguments.
+pub fn call_contract_params(
+    contract_id: ContractId,
+    func_name: String,
+    params: Binary,
+) -> Result<()> {
+    check(unsafe {
+        wevm::v0::bindings::call_contract_params(
+            contract_id.as_ptr(),
+            contract_id.len(),
+            func_name.as_ptr(),
+            func_name.len(),
+            params.as_ptr(),
+            params.len(),
+        )
+    })
+}
+
+// Converts
+
+/// Parse an integer from its decimal string.
+pub fn parse_int(value: String) -> Result<Integer> {
+    unsafe { self::value(wevm::v0::bindings::parse_int(value.as_ptr(), value.len())) }
+}
+
+/// Parse a boolean from `true` or `false`.
+pub fn parse_bool(value: String) -> Result<Boolean> {
+    unsafe { self::value(wevm::v0::bindings::parse_bool(value.as_ptr(), value.len())) }
+}
+
+/// Convert an integer to its big-endian bytes.
+pub fn to_bytes(value: Integer) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::to_bytes(value)) }
+}
+
+/// Convert big-endian bytes to an integer.
+pub fn to_int(value: Binary) -> Result<Integer> {
+    unsafe { self::value(wevm::v0::bindings::to_int(value.as_ptr(), value.len())) }
+}
+
+/// Convert a boolean to a string.
+pub fn to_string_bool(value: Boolean) -> Result<String<'static>> {
+    unsafe { string(wevm::v0::bindings::to_string_bool(value)) }
+}
+
+/// Convert an integer to a string.
+pub fn to_string_int(value: Integer) -> Result<String<'static>> {
+    unsafe { string(wevm::v0::bindings::to_string_int(value)) }
+}
+
+// Crypto
+
+/// Get the Blake2b-256 hash of the bytes.
+pub fn fast_hash(value: Binary) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::fast_hash(value.as_ptr(), value.len())) }
+}
+
+/// Get the Keccak-256 hash of the Blake2b-256 hash of the bytes.
+pub fn secure_hash(value: Binary) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::secure_hash(value.as_ptr(), value.len())) }
+}
+
+/// Get the Blake2b-256 hash of the bytes.
+pub fn blake2b256(value: Binary) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::blake2b256(value.as_ptr(), value.len())) }
+}
+
+/// Get the Keccak-256 hash of the bytes.
+pub fn keccak256(value: Binary) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::keccak256(value.as_ptr(), value.len())) }
+}
+
+/// Get the SHA-256 hash of the bytes.
+pub fn sha256(value: Binary) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::sha256(value.as_ptr(), value.len())) }
+}
+
+/// Verify the Curve25519 signature of the message.
+pub fn sig_verify(message: Binary, signature: Binary, public_key: Binary) -> Result<Boolean> {
+    unsafe {
+        value(wevm::v0::bindings::sig_verify(
+            message.as_ptr(),
+            message.len(),
+            signature.as_ptr(),
+            signature.len(),
+            public_key.as_ptr(),
+            public_key.len(),
+        ))
+    }
+}
+
+// Lease
+
+/// Lease the system token to the address or alias and get the lease id.
+pub fn lease(recipient: impl LeaseRecipient, amount: Integer) -> Result<Binary<'static>> {
+    unsafe { bytes(recipient.lease(amount)) }
+}
+
+/// Lease the system token to the address and get the lease id.
+pub fn lease_address(recipient: Address, amount: Integer) -> Result<Binary<'static>> {
+    lease(recipient, amount)
+}
+
+/// Lease the system token to the alias and get the lease id.
+pub fn lease_alias(recipient: Alias, amount: Integer) -> Result<Binary<'static>> {
+    lease(recipient, amount)
+}
+
+/// Cancel the lease.
+pub fn cancel_lease(lease_id: Binary) -> Result<()> {
+    check(unsafe { wevm::v0::bindings::cancel_lease(lease_id.as_ptr(), lease_id.len()) })
+}
+
+// Memory
+
+/// Compare the bytes.
+pub fn binary_equals(left: Binary, right: Binary) -> Result<Boolean> {
+    unsafe {
+        value(wevm::v0::bindings::binary_equals(
+            left.as_ptr(),
+            left.len(),
+            right.as_ptr(),
+            right.len(),
+        ))
+    }
+}
+
+/// Compare the strings.
+pub fn string_equals(left: String, right: String) -> Result<Boolean> {
+    unsafe {
+        value(wevm::v0::bindings::string_equals(
+            left.as_ptr(),
+            left.len(),
+            right.as_ptr(),
+            right.len(),
+        ))
+    }
+}
+
+/// Concatenate the bytes.
+pub fn join(left: Binary, right: Binary) -> Result<Binary<'static>> {
+    unsafe {
+        bytes(wevm::v0::bindings::join(
+            left.as_ptr(),
+            left.len(),
+            right.as_ptr(),
+            right.len(),
+        ))
+    }
+}
+
+/// Checks if the bytes contain the subbytes.
+pub fn contains(bytes: Binary, subbytes: Binary) -> Result<Boolean> {
+    unsafe {
+        value(wevm::v0::bindings::contains(
+            bytes.as_ptr(),
+            bytes.len(),
+            subbytes.as_ptr(),
+            subbytes.len(),
+        ))
+    }
+}
+
+/// Drop the first `n` bytes.
+pub fn drop(value: Binary, n: Integer) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::drop(value.as_ptr(), value.len(), n)) }
+}
+
+/// Drop the last `n` bytes.
+pub fn drop_right(value: Binary, n: Integer) -> Result<Binary<'static>> {
+    unsafe {
+        bytes(wevm::v0::bindings::drop_right(
+            value.as_ptr(),
+            value.len(),
+            n,
+        ))
+    }
+}
+
+/// Get the index of the first occurrence of the substring.
+pub fn index_of(string: String, substring: String) -> Result<Integer> {
+    unsafe {
+        value(wevm::v0::bindings::index_of(
+            string.as_ptr(),
+            string.len(),
+            substring.as_ptr(),
+            substring.len(),
+        ))
+    }
+}
+
+/// Get the index of the last occurrence of the substring.
+pub fn last_index_of(string: String, substring: String) -> Result<Integer> {
+    unsafe {
+        value(wevm::v0::bindings::last_index_of(
+            string.as_ptr(),
+            string.len(),
+            substring.as_ptr(),
+            substring.len(),
+        ))
+    }
+}
+
+/// Take the first `n` bytes.
+pub fn take(value: Binary, n: Integer) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::take(value.as_ptr(), value.len(), n)) }
+}
+
+/// Take the last `n` bytes.
+pub fn take_right(value: Binary, n: Integer) -> Result<Binary<'static>> {
+    unsafe {
+        bytes(wevm::v0::bindings::take_right(
+            value.as_ptr(),
+            value.len(),
+            n,
+        ))
+    }
+}
+
+// Storage
+
+/// Checks if a record with this key exists, an empty `address` stands for the current contract.
+pub fn contains_key(address: Binary, key: String) -> Result<Boolean> {
+    unsafe {
+        value(wevm::v0::bindings::contains_key(
+            address.as_ptr(),
+            address.len(),
+            key.as_ptr(),
+            key.len(),
+        ))
+    }
+}
+
+/// Get an integer from the storage, an empty `address` stands for the current contract.
+pub fn get_storage_int(address: Binary, key: String) -> Result<Integer> {
+    unsafe {
+        value(wevm::v0::bindings::get_storage_int(
+            address.as_ptr(),
+            address.len(),
+            key.as_ptr(),
+            key.len(),
+        ))
+    }
+}
+
+/// Get a boolean from the storage, an empty `address` stands for the current contract.
+pub fn get_storage_bool(address: Binary, key: String) -> Result<Boolean> {
+    unsafe {
+        value(wevm::v0::bindings::get_storage_bool(
+            address.as_ptr(),
+            address.len(),
+            key.as_ptr(),
+            key.len(),
+        ))
+    }
+}
+
+/// Get bytes from the storage, an empty `address` stands for the current contract.
+pub fn get_storage_binary(address: Binary, key: String) -> Result<Binary<'static>> {
+    unsafe {
+        bytes(wevm::v0::bindings::get_storage_binary(
+            address.as_ptr(),
+            address.len(),
+            key.as_ptr(),
+            key.len(),
+        ))
+    }
+}
+
+/// Get a string from the storage, an empty `address` stands for the current contract.
+pub fn get_storage_string(address: Binary, key: String) -> Result<String<'static>> {
+    unsafe {
+        string(wevm::v0::bindings::get_storage_string(
+            address.as_ptr(),
+            address.len(),
+            key.as_ptr(),
+            key.len(),
+        ))
+    }
+}
+
+/// Set an integer in the storage.
+pub fn set_storage_int(key: String, value: Integer) -> Result<()> {
+    check(unsafe { wevm::v0::bindings::set_storage_int(key.as_ptr(), key.len(), value) })
+}
+
+/// Set a boolean in the storage.
+pub fn set_storage_bool(key: String, value: Boolean) -> Result<()> {
+    check(unsafe { wevm::v0::bindings::set_storage_bool(key.as_ptr(), key.len(), value) })
+}
+
+/// Set bytes in the storage.
+pub fn set_storage_binary(key: String, value: Binary) -> Result<()> {
+    check(unsafe {
+        wevm::v0::bindings::set_storage_binary(key.as_ptr(), key.len(), value.as_ptr(), value.len())
+    })
+}
+
+/// Set a string in the storage.
+pub fn set_storage_string(key: String, value: String) -> Result<()> {
+    check(unsafe {
+        wevm::v0::bindings::set_storage_string(key.as_ptr(), key.len(), value.as_ptr(), value.len())
+    })
+}
+
+// Tx
+
+fn tx(field: &str) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v1::bindings::tx(field.as_ptr(), field.len())) }
+}
+
+/// Get the id of the transaction.
+pub fn tx_id() -> Result<Binary<'static>> {
+    tx("txId")
+}
+
+/// Get the address of the transaction sender.
+pub fn tx_sender() -> Result<Address<'static>> {
+    tx("sender").map(Address::new_unchecked)
+}
+
+/// Get the public key of the transaction sender.
+pub fn tx_sender_public_key() -> Result<Binary<'static>> {
+    tx("senderPublicKey")
+}
+
+/// Get the number of payments attached to the transaction.
+pub fn get_tx_payments() -> Result<Integer> {
+    unsafe { value(wevm::v1::bindings::get_payments()) }
+}
+
+/// Get the asset id and amount of the payment.
+pub fn get_tx_payment(number: Integer) -> Result<(Binary<'static>, Integer)> {
+    unsafe {
+        let asset_id = bytes(wevm::v1::bindings::get_payment_asset_id(number))?;
+        let amount = value(wevm::v1::bindings::get_payment_amount(number))?;
+        Ok((asset_id, amount))
+    }
+}
+
+// Utils
+
+/// Decode a Base58 string.
+pub fn base58(value: String) -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::base_58(value.as_ptr(), value.len())) }
+}
+
+/// Encode the bytes as a Base58 string.
+pub fn to_base58_string(value: Binary) -> Result<String<'static>> {
+    unsafe {
+        string(wevm::v0::bindings::to_base_58_string(
+            value.as_ptr(),
+            value.len(),
+        ))
+    }
+}
+
+/// Get the address or contract id of the caller.
+pub fn caller() -> Result<Binary<'static>> {
+    unsafe { bytes(wevm::v0::bindings::caller()) }
+}
+
+/// Verify the condition, otherwise fail with the message and [`Error::Exception`].
+pub fn require(condition: Boolean, message: String) -> Result<()> {
+    if condition {
+        return Ok(());
+    }
+
+    check(unsafe { wevm::v0::bindings::require(message.as_ptr(), message.len()) })?;
+    Err(Error::Exception)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{self, DataEntry, Holder as WorldHolder};
+
+    const CONTRACT: &[u8] = &[1; 32];
+
+    fn setup() {
+        test_env::reset();
+        test_env::with(|world| {
+            world.set_contract_id(CONTRACT);
+        });
+    }
+
+    #[test]
+    fn storage() {
+        setup();
+
+        assert_eq!(contains_key(THIS, "count"), Ok(false));
+        assert_eq!(set_storage_int("count", 3), Ok(()));
+        assert_eq!(set_storage_bool("flag", true), Ok(()));
+        assert_eq!(set_storage_binary("owner", b"owner"), Ok(()));
+        assert_eq!(set_storage_string("name", "counter"), Ok(()));
+
+        assert_eq!(contains_key(THIS, "count"), Ok(true));
+        assert_eq!(get_storage_int(THIS, "count"), Ok(3));
+        assert_eq!(get_storage_bool(THIS, "flag"), Ok(true));
+        assert_eq!(get_storage_binary(THIS, "owner"), Ok(&b"owner"[..]));
+        assert_eq!(get_storage_string(CONTRACT, "name"), Ok("counter"));
+
+        test_env::with(|world| {
+            assert_eq!(
+                world.storage(CONTRACT, "count"),
+                Some(&DataEntry::Integer(3))
+            );
+        });
+    }
+
+    #[test]
+    fn errors() {
+        setup();
+
+        assert_eq!(parse_int("12"), Ok(12));
+        assert_eq!(parse_int("twelve"), Err(Error::ParseError));
+        assert_eq!(parse_bool("yes"), Err(Error::ParseError));
+        assert_eq!(base58("0OIl"), Err(Error::Base58Error));
+        assert_eq!(
+            burn(AssetId::new_unchecked(&[2; 32]), 1),
+            Err(Error::Exception)
+        );
+    }
+
+    #[test]
+    fn balances() {
+        setup();
+        let recipient = Address::new_unchecked(&[1, 2, 3]);
+        test_env::with(|world| {
+            world.set_balance(WorldHolder::Contract(CONTRACT.to_vec()), SYSTEM_TOKEN, 100);
+        });
+
+        assert_eq!(get_balance(None, None), Ok(100));
+        assert_eq!(transfer(None, recipient, 40), Ok(()));
+        assert_eq!(get_balance(None, Some(recipient.into())), Ok(40));
+        assert_eq!(get_balance(None, None), Ok(60));
+        assert_eq!(transfer(None, recipient, 100), Err(Error::Exception));
+    }
+
+    #[test]
+    fn require_message() {
+        setup();
+
+        assert_eq!(require(true, "Unused"), Ok(()));
+        assert_eq!(require(false, "Not allowed"), Err(Error::Exception));
+        test_env::with(|world| {
+            assert_eq!(world.require_messages(), ["Not allowed"]);
+        });
+    }
+}
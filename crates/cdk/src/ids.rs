@@ -0,0 +1,382 @@
+//! Typed ids of accounts, contracts and assets.
+//!
+//! The host tells the holders of assets apart by a type and a version,
+//! these types carry the distinction themselves, so that an asset id cannot be passed
+//! where an address is expected. The ids dereference to their bytes
+//! and are accepted by the macros as well as by the functions of [`crate::host`].
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! const CHAIN_ID: u8 = b'V';
+//!
+//! #[action]
+//! fn pay(recipient: String, asset_id: String, amount: Integer) -> Result<()> {
+//!     let recipient = Address::from_base58(recipient, CHAIN_ID)?;
+//!     let asset_id = AssetId::from_base58(asset_id)?;
+//!
+//!     transfer!(asset => asset_id, recipient, amount);
+//!     host::transfer(None, Alias::new("treasury")?, amount / 100)
+//! }
+//!
+//! #[action]
+//! fn stake(amount: Integer) -> Result<()> {
+//!     let node = Alias::new("node-1")?;
+//!     let lease_id = lease!(node, amount);
+//!     set_storage!(binary :: "lease" => lease_id);
+//!     Ok(())
+//! }
+//! ```
+use crate::{
+    codec, host,
+    storage::{StorageKey, StorageType},
+    wevm, Binary, Error, Integer, Result, String,
+};
+use core::ops::Deref;
+
+/// Length of an address: version, chain id, hash of the public key and checksum.
+pub const ADDRESS_LENGTH: usize = 26;
+/// Version of the addresses.
+pub const ADDRESS_VERSION: u8 = 1;
+/// Length of the ids of contracts and assets.
+pub const ID_LENGTH: usize = 32;
+/// Length of the checksum ending an address.
+const CHECKSUM_LENGTH: usize = 4;
+/// Lengths of an alias.
+const ALIAS_LENGTH: core::ops::RangeInclusive<usize> = 4..=30;
+
+/// Address of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Address<'a>(Binary<'a>);
+
+impl<'a> Address<'a> {
+    /// Wraps the bytes without checking them, for the addresses returned by the host.
+    pub const fn new_unchecked(bytes: Binary<'a>) -> Self {
+        Self(bytes)
+    }
+
+    /// Checks the length and the version of the address.
+    pub fn from_bytes(bytes: Binary<'a>) -> Result<Self> {
+        if bytes.len() != ADDRESS_LENGTH {
+            return Err(Error::ParseError);
+        }
+        if bytes[0] != ADDRESS_VERSION {
+            return Err(Error::AddressVersionNotFound);
+        }
+        Ok(Self(bytes))
+    }
+
+    /// Get the bytes of the address.
+    pub fn as_bytes(&self) -> Binary<'a> {
+        self.0
+    }
+
+    /// Get the byte of the network the address belongs to.
+    pub fn chain_id(&self) -> u8 {
+        self.0.get(1).copied().unwrap_or_default()
+    }
+}
+
+impl Address<'static> {
+    /// Decodes the address and checks it belongs to the network of `chain_id`.
+    /// The checksum is the start of the `secure_hash` of the other bytes.
+    ///
+    /// # Usage
+    /// ```
+    /// use we_cdk::*;
+    ///
+    /// # #[cfg(feature = "test-env")]
+    /// # {
+    /// let mut bytes = [0u8; 26];
+    /// bytes[..2].copy_from_slice(&[1, b'V']);
+    /// let checksum = host::secure_hash(&bytes[..22]).unwrap();
+    /// bytes[22..].copy_from_slice(&checksum[..4]);
+    /// let address = host::to_base58_string(&bytes).unwrap();
+    ///
+    /// assert_eq!(Address::from_base58(address, b'V').unwrap().as_bytes(), bytes);
+    /// assert_eq!(Address::from_base58(address, b'T'), Err(Error::ParseError));
+    ///
+    /// bytes[25] ^= 1;
+    /// let corrupted = host::to_base58_string(&bytes).unwrap();
+    /// assert_eq!(Address::from_base58(corrupted, b'V'), Err(Error::ParseError));
+    /// # }
+    /// ```
+    pub fn from_base58(value: String, chain_id: u8) -> Result<Self> {
+        let address = Self::from_bytes(host::base58(value)?)?;
+        if address.chain_id() != chain_id {
+            return Err(Error::ParseError);
+        }
+
+        let (body, checksum) = address.0.split_at(ADDRESS_LENGTH - CHECKSUM_LENGTH);
+        let hash = host::secure_hash(body)?;
+        if hash.get(..CHECKSUM_LENGTH) != Some(checksum) {
+            return Err(Error::ParseError);
+        }
+
+        Ok(address)
+    }
+}
+
+/// Alias of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alias<'a>(String<'a>);
+
+impl<'a> Alias<'a> {
+    /// Wraps the alias without checking it.
+    pub const fn new_unchecked(alias: String<'a>) -> Self {
+        Self(alias)
+    }
+
+    /// Checks the alias, made of 4 to 30 lowercase letters, digits and `-.@_`.
+    pub fn new(alias: String<'a>) -> Result<Self> {
+        let valid = ALIAS_LENGTH.contains(&alias.len())
+            && alias
+                .bytes()
+                .all(|byte| matches!(byte, b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'@' | b'_'));
+        match valid {
+            true => Ok(Self(alias)),
+            false => Err(Error::ParseError),
+        }
+    }
+
+    /// Get the text of the alias.
+    pub fn as_str(&self) -> String<'a> {
+        self.0
+    }
+}
+
+macro_rules! ids {
+    ($( $(#[$doc:meta])* $name:ident, )+) => {
+        $(
+            $(#[$doc])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            pub struct $name<'a>(Binary<'a>);
+
+            impl<'a> $name<'a> {
+                /// Wraps the bytes without checking them, for the ids returned by the host.
+                pub const fn new_unchecked(bytes: Binary<'a>) -> Self {
+                    Self(bytes)
+                }
+
+                /// Checks the length of the id.
+                pub fn from_bytes(bytes: Binary<'a>) -> Result<Self> {
+                    match bytes.len() {
+                        ID_LENGTH => Ok(Self(bytes)),
+                        _ => Err(Error::ParseError),
+                    }
+                }
+
+                /// Get the bytes of the id.
+                pub fn as_bytes(&self) -> Binary<'a> {
+                    self.0
+                }
+            }
+
+            impl $name<'static> {
+                /// Decodes the id and checks its length.
+                pub fn from_base58(value: String) -> Result<Self> {
+                    Self::from_bytes(host::base58(value)?)
+                }
+            }
+        )+
+    };
+}
+
+ids! {
+    /// Id of a contract, the id of the transaction that created it.
+    ContractId,
+    /// Id of an asset, the id of the transaction that issued it.
+    AssetId,
+}
+
+macro_rules! deref {
+    ($( $name:ident => $target:ty, )+) => {
+        $(
+            impl<'a> Deref for $name<'a> {
+                type Target = $target;
+
+                fn deref(&self) -> &Self::Target {
+                    self.0
+                }
+            }
+        )+
+    };
+}
+
+deref! {
+    Address => [u8],
+    Alias => str,
+    ContractId => [u8],
+    AssetId => [u8],
+}
+
+macro_rules! codec {
+    ($( $name:ident => $len:expr, )+) => {
+        $(
+            // Written without the length, which is fixed
+            impl<'a> codec::Encode for $name<'a> {
+                fn encode(&self, writer: &mut codec::Writer<'_>) -> core::result::Result<(), codec::Error> {
+                    writer.write(self.0)
+                }
+            }
+
+            impl<'a> codec::Decode<'a> for $name<'a> {
+                fn decode(reader: &mut codec::Reader<'a>) -> core::result::Result<Self, codec::Error> {
+                    let bytes = reader.read($len)?;
+                    Self::from_bytes(bytes).map_err(|_| codec::Error::InvalidValue)
+                }
+            }
+        )+
+    };
+}
+
+codec! {
+    Address => ADDRESS_LENGTH,
+    ContractId => ID_LENGTH,
+    AssetId => ID_LENGTH,
+}
+
+impl<'a> codec::Encode for Alias<'a> {
+    fn encode(&self, writer: &mut codec::Writer<'_>) -> core::result::Result<(), codec::Error> {
+        self.0.encode(writer)
+    }
+}
+
+impl<'a> codec::Decode<'a> for Alias<'a> {
+    fn decode(reader: &mut codec::Reader<'a>) -> core::result::Result<Self, codec::Error> {
+        let alias = <&str>::decode(reader)?;
+        Self::new(alias).map_err(|_| codec::Error::InvalidValue)
+    }
+}
+
+/// Recipients of a lease, contracts cannot be leased to.
+pub trait LeaseRecipient {
+    #[doc(hidden)]
+    unsafe fn lease(&self, amount: Integer) -> (i32, *const u8, usize);
+}
+
+impl<'a> LeaseRecipient for Address<'a> {
+    unsafe fn lease(&self, amount: Integer) -> (i32, *const u8, usize) {
+        wevm::v0::bindings::lease_address(self.0.as_ptr(), self.0.len(), amount)
+    }
+}
+
+impl<'a> LeaseRecipient for Alias<'a> {
+    unsafe fn lease(&self, amount: Integer) -> (i32, *const u8, usize) {
+        wevm::v0::bindings::lease_alias(self.0.as_ptr(), self.0.len(), amount)
+    }
+}
+
+macro_rules! storage {
+    ($( $name:ident, )+) => {
+        $(
+            impl<'a> StorageType for $name<'a> {
+                fn read(address: &[u8], key: &str) -> Result<Self> {
+                    Self::from_bytes(host::get_storage_binary(address, key)?)
+                }
+
+                fn write(key: &str, value: Self) -> Result<()> {
+                    host::set_storage_binary(key, value.0)
+                }
+            }
+
+            impl<'a> StorageKey for $name<'a> {
+                fn encode(&self) -> Result<&str> {
+                    host::to_base58_string(self.0)
+                }
+            }
+        )+
+    };
+}
+
+storage! {
+    Address,
+    ContractId,
+    AssetId,
+}
+
+impl<'a> StorageType for Alias<'a> {
+    fn read(address: &[u8], key: &str) -> Result<Self> {
+        Self::new(host::get_storage_string(address, key)?)
+    }
+
+    fn write(key: &str, value: Self) -> Result<()> {
+        host::set_storage_string(key, value.0)
+    }
+}
+
+impl<'a> StorageKey for Alias<'a> {
+    fn encode(&self) -> Result<&str> {
+        Ok(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decode, Encode};
+
+    #[test]
+    fn lengths() {
+        let mut address = [0u8; ADDRESS_LENGTH];
+        address[0] = ADDRESS_VERSION;
+        assert!(Address::from_bytes(&address).is_ok());
+        assert_eq!(Address::from_bytes(&address[1..]), Err(Error::ParseError));
+
+        address[0] = 2;
+        assert_eq!(
+            Address::from_bytes(&address),
+            Err(Error::AddressVersionNotFound)
+        );
+
+        assert!(AssetId::from_bytes(&[0; ID_LENGTH]).is_ok());
+        assert_eq!(AssetId::from_bytes(&[0; 26]), Err(Error::ParseError));
+        assert_eq!(ContractId::from_bytes(&[]), Err(Error::ParseError));
+        assert_eq!(
+            ContractId::from_bytes(&[7; ID_LENGTH + 1]),
+            Err(Error::ParseError)
+        );
+        assert_eq!(
+            ContractId::from_bytes(&[7; ID_LENGTH]).map(|id| id.as_bytes()),
+            Ok(&[7; ID_LENGTH][..])
+        );
+    }
+
+    #[test]
+    fn aliases() {
+        for alias in ["miner", "node-1", "a.b@c_d", "abcd", &"x".repeat(30)] {
+            assert!(Alias::new(alias).is_ok(), "{alias}");
+        }
+        for alias in ["abc", "Miner", "node 1", "ноды", &"x".repeat(31)] {
+            assert_eq!(Alias::new(alias), Err(Error::ParseError), "{alias}");
+        }
+    }
+
+    #[test]
+    fn codec() {
+        let mut buffer = [0u8; 64];
+        let asset_id = AssetId::new_unchecked(&[7; ID_LENGTH]);
+        let bytes = asset_id.encode_to(&mut buffer).unwrap();
+        assert_eq!(bytes, &[7; ID_LENGTH]);
+        assert_eq!(AssetId::decode_from(bytes), Ok(asset_id));
+        assert_eq!(
+            ContractId::decode_from(&bytes[1..]),
+            Err(codec::Error::UnexpectedEnd)
+        );
+
+        let mut address = [0u8; ADDRESS_LENGTH];
+        address[0] = 2;
+        assert_eq!(
+            Address::decode_from(&address),
+            Err(codec::Error::InvalidValue)
+        );
+
+        let bytes = Alias::new("miner").unwrap().encode_to(&mut buffer).unwrap();
+        assert_eq!(bytes, b"\x05miner");
+        assert_eq!(
+            Alias::decode_from(b"\x03abc"),
+            Err(codec::Error::InvalidValue)
+        );
+    }
+}
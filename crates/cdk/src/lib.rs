@@ -1,8 +1,75 @@
 #![cfg_attr(not(doctest), doc = include_str!("../README.md"))]
 
+pub mod codec;
+pub mod error;
+pub mod host;
+pub mod ids;
 pub mod macros;
+pub mod storage;
 
+pub use codec::{Decode, Encode};
+pub use error::{ContractError, Error, Result};
+pub use ids::{Address, Alias, AssetId, ContractId};
+pub use storage::{Encoded, StorageMap, StorageValue};
+
+/// Native in-memory host for unit-testing contracts with `cargo test`.
+///
+/// With the `test-env` feature enabled on a non-wasm target, every macro of `we_cdk`
+/// is routed to a mock world kept per test thread instead of the `wevm` host bindings.
+/// Functions marked with `#[action]` can then be called directly from `#[test]`s.
+///
+/// Keys that were never written read as the default value of the requested type,
+/// use `contains_key!` to tell them apart.
+///
+/// # Usage
+/// ```
+/// use we_cdk::*;
+/// use we_cdk::test_env::{self, DataEntry, Holder, Tx};
+///
+/// #[action]
+/// fn _constructor(init_value: Boolean) {
+///     set_storage!(boolean :: "value" => init_value);
+///     set_storage!(binary :: "owner" => tx!(sender));
+/// }
+///
+/// #[action]
+/// fn withdraw(amount: Integer) {
+///     let owner: Binary = get_storage!(binary :: "owner");
+///     require!(equals!(binary :: owner, tx!(sender)));
+///     transfer!(address => owner, amount);
+/// }
+///
+/// test_env::reset();
+/// test_env::with(|world| {
+///     world
+///         .set_contract_id(b"contract")
+///         .set_tx(Tx {
+///             sender: b"owner".to_vec(),
+///             ..Default::default()
+///         })
+///         .add_payment(SYSTEM_TOKEN, 100);
+/// });
+///
+/// assert_eq!(_constructor(true), 0);
+/// assert_eq!(withdraw(40), 0);
+/// assert_eq!(withdraw(100), 300);
+///
+/// test_env::with(|world| {
+///     let owner = Holder::Address(b"owner".to_vec());
+///     let contract = Holder::Contract(b"contract".to_vec());
+///
+///     assert_eq!(world.storage(b"contract", "value"), Some(&DataEntry::Boolean(true)));
+///     assert_eq!(world.balance(&owner, SYSTEM_TOKEN), 40);
+///     assert_eq!(world.balance(&contract, SYSTEM_TOKEN), 60);
+/// });
+/// ```
+#[cfg(all(any(test, feature = "test-env"), not(target_arch = "wasm32")))]
+pub use we_test_env as test_env;
+
+#[cfg(all(any(test, feature = "test-env"), not(target_arch = "wasm32")))]
+pub use test_env::wevm;
 pub use we_contract_proc_macro::*;
+#[cfg(not(all(any(test, feature = "test-env"), not(target_arch = "wasm32"))))]
 pub use wevm;
 
 /// Integer is an integer data type.
@@ -18,7 +85,7 @@ pub type String<'a> = &'a str;
 /// Payment is a payment that can be attached when calling the function of another contract.
 pub type Payment<'a> = (&'a [u8], i64);
 
-#[doc(hidden)]
+/// Empty asset id standing for the system token.
 pub const SYSTEM_TOKEN: &[u8] = &[0u8; 0];
-#[doc(hidden)]
+/// Empty address standing for the current contract.
 pub const THIS: &[u8] = &[0u8; 0];
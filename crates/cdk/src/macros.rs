@@ -25,7 +25,7 @@ macro_rules! internal_data {
 macro_rules! error {
     ($error:expr) => {
         if $error != 0 {
-            return $error;
+            return $crate::error::Failure::failure($error);
         }
     };
 }
@@ -34,7 +34,11 @@ macro_rules! error {
 ///
 /// # Result
 /// If the condition was not satisfied,
-/// the execution will be stopped with error code 300 (RuntimeError::Exception)
+/// the execution will be stopped with error code 300 (RuntimeError::Exception).
+/// In functions returning `Result` the error is returned as `Error::Exception`.
+///
+/// With an error deriving `ContractError` instead of a message,
+/// the execution is stopped with the code of the error and its message is reported to the host
 ///
 /// # Usage
 /// ```
@@ -51,22 +55,149 @@ macro_rules! error {
 ///     let balance = get_balance!(this);
 ///     require!(balance > 1337, "Balance is less than 1337!");
 /// }
+///
+/// const NOT_ENOUGH: &str = "Balance is not enough";
+///
+/// #[action]
+/// fn require_with_constant(amount: Integer) {
+///     let balance = get_balance!(this);
+///     require!(balance >= amount, NOT_ENOUGH);
+/// }
+///
+/// #[derive(ContractError)]
+/// enum BalanceError {
+///     #[error("Balance is less than 1337")]
+///     TooLow = 1000,
+/// }
+///
+/// #[action]
+/// fn require_with_error() {
+///     let balance = get_balance!(this);
+///     require!(balance > 1337, BalanceError::TooLow);
+/// }
+///
+/// #[action]
+/// fn require_with_imported_error() {
+///     use BalanceError::*;
+///     let balance = get_balance!(this);
+///     require!(balance > 1337, TooLow);
+/// }
 /// ```
 #[macro_export]
 macro_rules! require {
     ($condition:expr) => {
         if !($condition) {
-            return 300;
+            return $crate::error::Failure::failure(300);
         }
     };
-    ($condition:expr, $message:tt) => {
+    ($condition:expr, $reason:expr) => {
         if !($condition) {
-            let error = wevm::v0::bindings::require($message.as_ptr(), $message.len());
-            if error != 0 {
-                return error;
-            } else {
-                return 300;
-            }
+            return $crate::error::Requirement::unmet($reason);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_env::{self, DataEntry},
+        *,
+    };
+
+    const CONTRACT: &[u8] = &[1; 32];
+
+    /// Plain function, outside of the `unsafe` block of an action.
+    fn increment(limit: Integer) -> Result<Integer> {
+        let count = match contains_key!("count") {
+            true => get_storage!(integer :: "count"),
+            false => 0,
+        };
+        require!(count < limit, "Limit reached");
+        set_storage!(integer :: "count" => count + 1);
+        Ok(count + 1)
+    }
+
+    fn name() -> Result<String<'static>> {
+        let name = get_storage!(string :: CONTRACT => "name");
+        require!(!name.is_empty());
+        Ok(name)
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum LimitError {
+        Reached,
+    }
+
+    impl ContractError for LimitError {
+        const CODES: &'static [(i32, &'static str, &'static str)] =
+            &[(1000, "LimitError::Reached", "Limit reached")];
+
+        fn code(&self) -> i32 {
+            1000
+        }
+
+        fn message(&self) -> &'static str {
+            "Limit reached"
         }
+
+        fn report(&self) -> &'static str {
+            "Code: 1000. Error: Limit reached."
+        }
+    }
+
+    const EMPTY: &str = "Value is empty";
+
+    fn check(value: Integer) -> core::result::Result<Integer, LimitError> {
+        require!(value < 10, LimitError::Reached);
+        Ok(value)
+    }
+
+    fn check_binary(value: Binary) -> Result<()> {
+        let message = EMPTY;
+        require!(!value.is_empty(), message);
+        require!(value.len() < 4, EMPTY);
+        Ok(())
+    }
+
+    #[test]
+    fn reasons() {
+        test_env::reset();
+
+        assert_eq!(check(1), Ok(1));
+        assert_eq!(check(10), Err(LimitError::Reached));
+        assert_eq!(check_binary(&[1]), Ok(()));
+        assert_eq!(check_binary(&[]), Err(Error::Exception));
+        assert_eq!(check_binary(&[1; 4]), Err(Error::Exception));
+        test_env::with(|world| {
+            assert_eq!(
+                world.require_messages(),
+                ["Code: 1000. Error: Limit reached.", EMPTY, EMPTY]
+            );
+        });
+    }
+
+    #[test]
+    fn helpers() {
+        test_env::reset();
+        test_env::with(|world| {
+            world.set_contract_id(CONTRACT);
+        });
+
+        assert_eq!(increment(2), Ok(1));
+        assert_eq!(increment(2), Ok(2));
+        assert_eq!(increment(2), Err(Error::Exception));
+        test_env::with(|world| {
+            assert_eq!(world.require_messages(), ["Limit reached"]);
+            assert_eq!(
+                world.storage(CONTRACT, "count"),
+                Some(&DataEntry::Integer(2))
+            );
+        });
+
+        assert_eq!(name(), Err(Error::Exception));
+        test_env::with(|world| {
+            world.set_storage(CONTRACT, "name", DataEntry::String("counter".to_string()));
+        });
+        assert_eq!(name(), Ok("counter"));
     }
 }
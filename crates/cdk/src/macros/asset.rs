@@ -22,6 +22,24 @@
 ///     let address_asset_balance: Integer = get_balance!(address => another_address, asset => token);
 /// }
 /// ```
+///
+/// With the typed ids the kind of the holder is taken from its type
+///
+/// ```
+/// use we_cdk::*;
+///
+/// #[action]
+/// fn balances(address: String, asset_id: String) -> Result<()> {
+///     let address = Address::from_base58(address, b'V')?;
+///     let asset_id = AssetId::from_base58(asset_id)?;
+///     let contract = ContractId::from_base58("4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2")?;
+///
+///     let address_balance: Integer = get_balance!(address);
+///     let alias_balance: Integer = get_balance!(Alias::new("alias")?);
+///     let contract_asset_balance: Integer = get_balance!(contract, asset => asset_id);
+///     Ok(())
+/// }
+/// ```
 #[macro_export]
 macro_rules! get_balance {
     // For use within a macro
@@ -76,14 +94,23 @@ macro_rules! get_balance {
         get_balance!(@inner, $address, 0, 2)
     };
     (alias => $address:expr, asset => $asset_id:expr) => {
-        get_balance!($address, $asset_id, 0, 2)
+        get_balance!(@inner, $address, $asset_id, 0, 2)
     };
     (contract => $address:expr) => {
         get_balance!(@inner, $address, 1, 1)
     };
     (contract => $address:expr, asset => $asset_id:expr) => {
-        get_balance!($address, $asset_id, 1, 1)
+        get_balance!(@inner, $address, $asset_id, 1, 1)
     };
+    ($holder:expr) => {{
+        let (holder, type_, version) = $crate::host::Holder::from($holder).raw();
+        get_balance!(@inner, holder, type_, version)
+    }};
+    ($holder:expr, asset => $asset_id:expr) => {{
+        let asset_id: $crate::AssetId = $asset_id;
+        let (holder, type_, version) = $crate::host::Holder::from($holder).raw();
+        get_balance!(@inner, holder, asset_id, type_, version)
+    }};
 }
 
 /// Tokens transfer
@@ -101,6 +128,24 @@ macro_rules! get_balance {
 ///     transfer!(asset => asset_id, address => recipient, amount);
 /// }
 /// ```
+///
+/// With the typed ids the kind of the recipient is taken from its type,
+/// and only an [`AssetId`](crate::AssetId) is accepted as the asset
+///
+/// ```
+/// use we_cdk::*;
+///
+/// #[action]
+/// fn pay(recipient: String, asset_id: String, amount: Integer) -> Result<()> {
+///     let recipient = Address::from_base58(recipient, b'V')?;
+///     let asset_id = AssetId::from_base58(asset_id)?;
+///
+///     transfer!(recipient, amount);
+///     transfer!(asset => asset_id, recipient, amount);
+///     transfer!(Alias::new("treasury")?, amount);
+///     Ok(())
+/// }
+/// ```
 #[macro_export]
 macro_rules! transfer {
     // For use within a macro
@@ -148,6 +193,15 @@ macro_rules! transfer {
     (asset => $asset_id:expr, contract => $recipient:expr, $amount:expr) => {
         transfer!(@inner, $asset_id, $recipient, $amount, 1, 1);
     };
+    (asset => $asset_id:expr, $recipient:expr, $amount:expr) => {
+        let asset_id: $crate::AssetId = $asset_id;
+        let (recipient, type_, version) = $crate::host::Holder::from($recipient).raw();
+        transfer!(@inner, asset_id, recipient, $amount, type_, version);
+    };
+    ($recipient:expr, $amount:expr) => {
+        let (recipient, type_, version) = $crate::host::Holder::from($recipient).raw();
+        transfer!(@inner, recipient, $amount, type_, version);
+    };
 }
 
 /// Issue the asset
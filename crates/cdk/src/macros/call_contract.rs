@@ -11,8 +11,7 @@
 ///
 /// #[action]
 /// fn _constructor() {
-///     let contract: Binary = base58!("4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
-///     let asset: Binary = base58!("DnK5Xfi2wXUJx9BjK9X6ZpFdTLdq2GtWH9pWrcxcmrhB");
+///     let contract = base58!(contract => "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
 ///
 ///     let integer: Integer = 42;
 ///     let boolean: Boolean = true;
@@ -32,7 +31,7 @@
 ///
 /// #[action]
 /// fn call_with_binary_params(func_name: String, params: Binary) {
-///     let contract: Binary = base58!("4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
+///     let contract = base58!(contract => "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
 ///
 ///     call_contract! {
 ///         (contract)::call(func_name, params)
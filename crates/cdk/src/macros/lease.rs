@@ -15,6 +15,19 @@
 ///     let lease_id_second: Binary = lease!(alias => "miner", amount);
 /// }
 /// ```
+///
+/// An [`Address`](crate::Address) or an [`Alias`](crate::Alias) picks the binding by itself
+///
+/// ```
+/// use we_cdk::*;
+///
+/// #[action]
+/// fn stake(amount: Integer) -> Result<()> {
+///     let lease_id: Binary = lease!(Alias::new("miner")?, amount);
+///     set_storage!(binary :: "lease" => lease_id);
+///     Ok(())
+/// }
+/// ```
 #[macro_export]
 macro_rules! lease {
     (address => $recipient:expr, $amount:expr) => {{
@@ -29,6 +42,11 @@ macro_rules! lease {
         error!(error);
         core::slice::from_raw_parts(ptr, len)
     }};
+    ($recipient:expr, $amount:expr) => {{
+        let (error, ptr, len) = $crate::ids::LeaseRecipient::lease(&$recipient, $amount);
+        error!(error);
+        core::slice::from_raw_parts(ptr, len)
+    }};
 }
 
 /// Cancel the lease
@@ -3,17 +3,39 @@
 /// # Result
 /// The result of execution is `&[u8]` bytes
 ///
+/// String literals are decoded during compilation, other strings by the host.
+/// A literal preceded by `address =>`, `asset =>` or `contract =>` is checked to be of that kind
+/// and results in an [`Address`](crate::Address), [`AssetId`](crate::AssetId) or [`ContractId`](crate::ContractId),
+/// an invalid literal fails the compilation.
+///
 /// # Usage
 /// ```
 /// use we_cdk::*;
 ///
+/// const TOKEN: AssetId = base58!(asset => "DnK5Xfi2wXUJx9BjK9X6ZpFdTLdq2GtWH9pWrcxcmrhB");
+/// const OWNER: Address = base58!(address => "3NzkzibVRkKUzaRzjUxndpTPvoBzQ3iLng3");
+///
 /// #[action]
-/// fn _constructor() {
+/// fn _constructor(encoded: String) {
 ///     let address: Binary = base58!("3NzkzibVRkKUzaRzjUxndpTPvoBzQ3iLng3");
+///     let decoded: Binary = base58!(encoded);
+///     transfer!(asset => TOKEN, OWNER, 100);
 /// }
+///
+/// # #[cfg(feature = "test-env")]
+/// # {
+/// assert_eq!(base58!("112gV"), host::base58("112gV").unwrap());
+/// assert_eq!(TOKEN.as_bytes(), host::base58("DnK5Xfi2wXUJx9BjK9X6ZpFdTLdq2GtWH9pWrcxcmrhB").unwrap());
+/// # }
 /// ```
 #[macro_export]
 macro_rules! base58 {
+    ($value:literal) => {
+        $crate::base58_literal!($value)
+    };
+    ($kind:ident => $value:literal) => {
+        $crate::base58_literal!($kind => $value)
+    };
     ($value:expr) => {{
         let (error, ptr, len) = wevm::v0::bindings::base_58($value.as_ptr(), $value.len());
         error!(error);
@@ -0,0 +1,387 @@
+//! Typed handles over the contract storage.
+//!
+//! A [`StorageValue`] is bound to a single key, a [`StorageMap`] to a key prefix.
+//! Both pick the storage binding from the value type, so a read does not name the type twice.
+//!
+//! Map keys are encoded as `<prefix>_<key>`, where the key is rendered as:
+//! * `Integer` - decimal string
+//! * `Boolean` - `true` or `false`
+//! * `Binary` - Base58 string
+//! * `String` - as is
+//!
+//! Structs deriving [`Encode`] and [`Decode`] are stored as `Binary` entries through [`Encoded`].
+//!
+//! # Usage
+//! ```
+//! use we_cdk::*;
+//!
+//! const OWNER: StorageValue<Binary> = StorageValue::new("owner");
+//! const COUNT: StorageValue<Integer> = StorageValue::new("count");
+//! const VOTES: StorageMap<Binary, Integer> = StorageMap::new("votes");
+//!
+//! #[action]
+//! fn _constructor() -> Result<()> {
+//!     OWNER.set(tx!(sender))
+//! }
+//!
+//! #[action]
+//! fn vote() -> Result<()> {
+//!     let sender = tx!(sender);
+//!     require!(VOTES.get_or(sender, 0)? == 0);
+//!
+//!     let count = COUNT.get_or(0)?;
+//!     VOTES.set(sender, 1)?;
+//!     COUNT.set(count + 1)
+//! }
+//! ```
+use crate::{host, Binary, Boolean, ContractId, Decode, Encode, Integer, Result, String, THIS};
+use core::marker::PhantomData;
+
+/// Separator between the prefix of a [`StorageMap`] and the encoded key.
+pub const SEPARATOR: &str = "_";
+
+/// Types that can be stored in the contract storage.
+pub trait StorageType: Sized {
+    /// Reads the value of the key from the storage of the contract.
+    /// An empty `address` stands for the current contract.
+    fn read(address: &[u8], key: &str) -> Result<Self>;
+    /// Writes the value of the key into the storage of the current contract.
+    fn write(key: &str, value: Self) -> Result<()>;
+}
+
+/// Types that can be used as keys of a [`StorageMap`].
+pub trait StorageKey {
+    /// Encodes the key into its textual form.
+    fn encode(&self) -> Result<&str>;
+}
+
+/// Storage value bound to a key.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageValue<'k, T> {
+    key: &'k str,
+    _marker: PhantomData<T>,
+}
+
+impl<'k, T: StorageType> StorageValue<'k, T> {
+    /// Binds the value to the key.
+    pub const fn new(key: &'k str) -> Self {
+        Self {
+            key,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the key of the value.
+    pub fn key(&self) -> &'k str {
+        self.key
+    }
+
+    /// Get the value.
+    pub fn get(&self) -> Result<T> {
+        T::read(THIS, self.key)
+    }
+
+    /// Get the value, or `default` if the key has not been written.
+    pub fn get_or(&self, default: T) -> Result<T> {
+        match self.contains()? {
+            true => self.get(),
+            false => Ok(default),
+        }
+    }
+
+    /// Get the value from the storage of another contract.
+    pub fn get_from<'c>(&self, contract_id: ContractId<'c>) -> Result<T> {
+        T::read(contract_id.as_bytes(), self.key)
+    }
+
+    /// Set the value.
+    pub fn set(&self, value: T) -> Result<()> {
+        T::write(self.key, value)
+    }
+
+    /// Checks if the value has been written.
+    pub fn contains(&self) -> Result<bool> {
+        host::contains_key(THIS, self.key)
+    }
+
+    /// Checks if the value has been written in the storage of another contract.
+    pub fn contains_in<'c>(&self, contract_id: ContractId<'c>) -> Result<bool> {
+        host::contains_key(contract_id.as_bytes(), self.key)
+    }
+}
+
+/// Storage map bound to a key prefix.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageMap<'k, K, V> {
+    prefix: &'k str,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<'k, K: StorageKey, V: StorageType> StorageMap<'k, K, V> {
+    /// Binds the map to the key prefix.
+    pub const fn new(prefix: &'k str) -> Self {
+        Self {
+            prefix,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the storage key of the entry.
+    pub fn key(&self, key: K) -> Result<String<'static>> {
+        let key = key.encode()?;
+        let prefix = host::join(self.prefix.as_bytes(), SEPARATOR.as_bytes())?;
+        let bytes = host::join(prefix, key.as_bytes())?;
+        // Joined from strings, hence valid UTF-8
+        Ok(unsafe { core::str::from_utf8_unchecked(bytes) })
+    }
+
+    /// Get the value of the entry.
+    pub fn get(&self, key: K) -> Result<V> {
+        V::read(THIS, self.key(key)?)
+    }
+
+    /// Get the value of the entry, or `default` if the entry has not been written.
+    pub fn get_or(&self, key: K, default: V) -> Result<V> {
+        let key = self.key(key)?;
+        match host::contains_key(THIS, key)? {
+            true => V::read(THIS, key),
+            false => Ok(default),
+        }
+    }
+
+    /// Get the value of the entry from the storage of another contract.
+    pub fn get_from<'c>(&self, contract_id: ContractId<'c>, key: K) -> Result<V> {
+        V::read(contract_id.as_bytes(), self.key(key)?)
+    }
+
+    /// Set the value of the entry.
+    pub fn set(&self, key: K, value: V) -> Result<()> {
+        V::write(self.key(key)?, value)
+    }
+
+    /// Checks if the entry has been written.
+    pub fn contains(&self, key: K) -> Result<bool> {
+        host::contains_key(THIS, self.key(key)?)
+    }
+
+    /// Checks if the entry has been written in the storage of another contract.
+    pub fn contains_in<'c>(&self, contract_id: ContractId<'c>, key: K) -> Result<bool> {
+        host::contains_key(contract_id.as_bytes(), self.key(key)?)
+    }
+}
+
+impl StorageType for Integer {
+    fn read(address: &[u8], key: &str) -> Result<Self> {
+        host::get_storage_int(address, key)
+    }
+
+    fn write(key: &str, value: Self) -> Result<()> {
+        host::set_storage_int(key, value)
+    }
+}
+
+impl StorageType for Boolean {
+    fn read(address: &[u8], key: &str) -> Result<Self> {
+        host::get_storage_bool(address, key)
+    }
+
+    fn write(key: &str, value: Self) -> Result<()> {
+        host::set_storage_bool(key, value)
+    }
+}
+
+impl<'a> StorageType for Binary<'a> {
+    fn read(address: &[u8], key: &str) -> Result<Self> {
+        host::get_storage_binary(address, key)
+    }
+
+    fn write(key: &str, value: Self) -> Result<()> {
+        host::set_storage_binary(key, value)
+    }
+}
+
+impl<'a> StorageType for String<'a> {
+    fn read(address: &[u8], key: &str) -> Result<Self> {
+        host::get_storage_string(address, key)
+    }
+
+    fn write(key: &str, value: Self) -> Result<()> {
+        host::set_storage_string(key, value)
+    }
+}
+
+/// Value stored as a `Binary` entry in the encoding of [`crate::codec`].
+///
+/// The value is encoded into a buffer of `N` bytes on the stack,
+/// which must fit into the `stack-size` set in `[package.metadata.we]`.
+///
+/// # Usage
+/// ```
+/// use we_cdk::*;
+///
+/// #[derive(Encode, Decode)]
+/// #[codec(version = 1)]
+/// struct Vote {
+///     option: u8,
+///     weight: Integer,
+/// }
+///
+/// const VOTES: StorageMap<Binary, Encoded<Vote, 16>> = StorageMap::new("votes");
+///
+/// #[action]
+/// fn vote(option: Integer) -> Result<()> {
+///     let sender = tx!(sender);
+///     require!(!VOTES.contains(sender)?);
+///
+///     let vote = Vote {
+///         option: option as u8,
+///         weight: get_balance!(address => sender),
+///     };
+///     VOTES.set(sender, Encoded(vote))
+/// }
+///
+/// #[action]
+/// fn revote(option: Integer) -> Result<()> {
+///     let Encoded(mut vote) = VOTES.get(tx!(sender))?;
+///     vote.option = option as u8;
+///     VOTES.set(tx!(sender), Encoded(vote))
+/// }
+///
+/// # #[cfg(feature = "test-env")]
+/// # {
+/// test_env::reset();
+/// test_env::with(|world| {
+///     world.set_tx(test_env::Tx {
+///         sender: b"voter".to_vec(),
+///         ..Default::default()
+///     });
+/// });
+///
+/// assert_eq!(vote(1), 0);
+/// assert_eq!(vote(2), 300);
+/// assert_eq!(revote(3), 0);
+/// assert_eq!(VOTES.get(b"voter").unwrap().0.option, 3);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Encoded<T, const N: usize = 256>(pub T);
+
+impl<T, const N: usize> StorageType for Encoded<T, N>
+where
+    T: Encode + Decode<'static>,
+{
+    fn read(address: &[u8], key: &str) -> Result<Self> {
+        let bytes = host::get_storage_binary(address, key)?;
+        Ok(Encoded(T::decode_from(bytes)?))
+    }
+
+    fn write(key: &str, value: Self) -> Result<()> {
+        let mut buffer = [0u8; N];
+        let bytes = value.0.encode_to(&mut buffer)?;
+        host::set_storage_binary(key, bytes)
+    }
+}
+
+impl StorageKey for Integer {
+    fn encode(&self) -> Result<&str> {
+        host::to_string_int(*self)
+    }
+}
+
+impl StorageKey for Boolean {
+    fn encode(&self) -> Result<&str> {
+        match self {
+            true => Ok("true"),
+            false => Ok("false"),
+        }
+    }
+}
+
+impl<'a> StorageKey for Binary<'a> {
+    fn encode(&self) -> Result<&str> {
+        host::to_base58_string(self)
+    }
+}
+
+impl<'a> StorageKey for String<'a> {
+    fn encode(&self) -> Result<&str> {
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{self, DataEntry};
+
+    const CONTRACT: &[u8] = &[1; 32];
+    const OTHER: &[u8] = &[2; 32];
+
+    #[test]
+    fn value() {
+        const COUNT: StorageValue<Integer> = StorageValue::new("count");
+
+        test_env::reset();
+        test_env::with(|world| {
+            world.set_contract_id(CONTRACT);
+        });
+
+        assert_eq!(COUNT.contains(), Ok(false));
+        assert_eq!(COUNT.get_or(7), Ok(7));
+        assert_eq!(COUNT.set(3), Ok(()));
+        assert_eq!(COUNT.contains(), Ok(true));
+        assert_eq!(COUNT.get(), Ok(3));
+        assert_eq!(COUNT.get_or(7), Ok(3));
+
+        test_env::with(|world| {
+            assert_eq!(
+                world.storage(CONTRACT, "count"),
+                Some(&DataEntry::Integer(3))
+            );
+            world.set_storage(OTHER, "count", DataEntry::Integer(5));
+        });
+
+        let other = ContractId::from_bytes(OTHER).unwrap();
+        assert_eq!(COUNT.contains_in(other), Ok(true));
+        assert_eq!(COUNT.get_from(other), Ok(5));
+    }
+
+    #[test]
+    fn map() {
+        const BALANCES: StorageMap<Binary, Integer> = StorageMap::new("balance");
+        const FLAGS: StorageMap<Integer, Boolean> = StorageMap::new("flag");
+        const NAMES: StorageMap<String, String> = StorageMap::new("name");
+
+        test_env::reset();
+        test_env::with(|world| {
+            world.set_contract_id(CONTRACT);
+        });
+
+        assert_eq!(BALANCES.key(&[0, 1, 2]), Ok("balance_15T"));
+        assert_eq!(FLAGS.key(-12), Ok("flag_-12"));
+        assert_eq!(NAMES.key("alice"), Ok("name_alice"));
+
+        assert_eq!(BALANCES.get_or(b"alice", 0), Ok(0));
+        assert_eq!(BALANCES.set(b"alice", 10), Ok(()));
+        assert_eq!(BALANCES.get(b"alice"), Ok(10));
+        assert_eq!(BALANCES.contains(b"bob"), Ok(false));
+        assert_eq!(FLAGS.set(1, true), Ok(()));
+        assert_eq!(NAMES.set("alice", "Alice"), Ok(()));
+
+        test_env::with(|world| {
+            assert_eq!(
+                world.storage(CONTRACT, "flag_1"),
+                Some(&DataEntry::Boolean(true))
+            );
+            assert_eq!(
+                world.storage(CONTRACT, "name_alice"),
+                Some(&DataEntry::String("Alice".to_string()))
+            );
+            world.set_storage(OTHER, "flag_2", DataEntry::Boolean(true));
+        });
+
+        let other = ContractId::from_bytes(OTHER).unwrap();
+        assert_eq!(FLAGS.contains_in(other, 1), Ok(false));
+        assert_eq!(FLAGS.get_from(other, 2), Ok(true));
+    }
+}
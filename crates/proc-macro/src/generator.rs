@@ -1,5 +1,13 @@
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+
+/// Custom section of the wasm module with the exported functions.
+const ABI_SECTION: &str = "we_abi";
+/// Custom section of the wasm module with the codes of the contract errors.
+const ERRORS_SECTION: &str = "we_errors";
+/// Custom section of the wasm module with the schemas of the decoded types.
+const TYPES_SECTION: &str = "we_types";
 
 /// Convert the described interface into a
 /// WASM method set for calling contract methods.
@@ -9,80 +17,109 @@ pub fn interface(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
     let input = syn::parse2::<syn::ItemTrait>(input)?;
     let mod_name = input.ident;
 
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "interfaces cannot be generic",
+        ));
+    }
+
     for item in input.items {
-        if let syn::TraitItem::Fn(func) = item {
-            let func_name_str = func.sig.ident.to_string();
-            let func_name = func.sig.ident;
-
-            let mut args: Vec<TokenStream2> = vec![];
-            let mut call_args: Vec<TokenStream2> = vec![];
-            for arg in func.sig.inputs.iter() {
-                if let syn::FnArg::Typed(a) = arg {
-                    if let syn::Pat::Ident(pat_ident) = &*a.pat {
-                        let arg_name = &pat_ident.ident;
-
-                        if let syn::Type::Path(type_path) = &*a.ty {
-                            let path_seg = &type_path.path.segments[0];
-                            let name = path_seg.ident.to_string();
-                            match name.as_str() {
-                                "Integer" => {
-                                    args.push(quote!(
-                                        #arg_name: i64
-                                    ));
-
-                                    call_args.push(quote!(
-                                        wevm::v0::bindings::call_arg_int(#arg_name);
-                                    ));
-                                }
-                                "Boolean" => {
-                                    args.push(quote!(
-                                        #arg_name: bool
-                                    ));
-
-                                    call_args.push(quote!(
-                                        wevm::v0::bindings::call_arg_bool(#arg_name);
-                                    ));
-                                }
-                                "Binary" => {
-                                    args.push(quote!(
-                                        #arg_name: &[u8]
-                                    ));
-
-                                    call_args.push(quote!(
-                                        let error = wevm::v0::bindings::call_arg_binary(#arg_name.as_ptr(), #arg_name.len());
-                                        if error != 0 {
-                                            return error;
-                                        }
-                                    ));
-                                }
-                                "String" => {
-                                    args.push(quote!(
-                                        #arg_name: &str
-                                    ));
-
-                                    call_args.push(quote!(
-                                        let error = wevm::v0::bindings::call_arg_string(#arg_name.as_ptr(), #arg_name.len());
-                                        if error != 0 {
-                                            return error;
-                                        }
-                                    ));
-                                }
-                                _ => (),
-                            }
+        let func = match item {
+            syn::TraitItem::Fn(func) => func,
+            item => {
+                return Err(syn::Error::new_spanned(
+                    item,
+                    "interfaces can only contain functions",
+                ))
+            }
+        };
+
+        check_signature(&func.sig)?;
+
+        if let Some(block) = &func.default {
+            return Err(syn::Error::new_spanned(
+                block,
+                "interface functions cannot have a body",
+            ));
+        }
+
+        if let syn::ReturnType::Type(_, output) = &func.sig.output {
+            return Err(syn::Error::new_spanned(
+                output,
+                "interface functions cannot return a value, the call returns the error code",
+            ));
+        }
+
+        let func_name_str = func.sig.ident.to_string();
+        let func_name = func.sig.ident;
+
+        let mut args: Vec<TokenStream2> = vec![];
+        let mut call_args: Vec<TokenStream2> = vec![];
+        for arg in func.sig.inputs.iter() {
+            let Arg {
+                name: arg_name,
+                ty,
+                type_path,
+                ..
+            } = parse_arg(arg, false)?;
+
+            match ty {
+                ArgType::Integer => {
+                    args.push(quote!(
+                        #arg_name: i64
+                    ));
+
+                    call_args.push(quote!(
+                        wevm::v0::bindings::call_arg_int(#arg_name);
+                    ));
+                }
+                ArgType::Boolean => {
+                    args.push(quote!(
+                        #arg_name: bool
+                    ));
+
+                    call_args.push(quote!(
+                        wevm::v0::bindings::call_arg_bool(#arg_name);
+                    ));
+                }
+                ArgType::Binary => {
+                    args.push(quote!(
+                        #arg_name: &[u8]
+                    ));
+
+                    call_args.push(quote!(
+                        let error = wevm::v0::bindings::call_arg_binary(#arg_name.as_ptr(), #arg_name.len());
+                        if error != 0 {
+                            return error;
                         }
-                    }
+                    ));
                 }
-            }
+                ArgType::String => {
+                    args.push(quote!(
+                        #arg_name: &str
+                    ));
 
-            mod_func.push(quote!(
-                pub fn #func_name(contract_id: &[u8], #( #args ),*) -> i32 {
-                    unsafe {
-                        #( #call_args )*
-                        wevm::v0::bindings::call_contract(contract_id.as_ptr(), contract_id.len(), #func_name_str.as_ptr(), #func_name_str.len())
-                    }
+                    call_args.push(quote!(
+                        let error = wevm::v0::bindings::call_arg_string(#arg_name.as_ptr(), #arg_name.len());
+                        if error != 0 {
+                            return error;
+                        }
+                    ));
                 }
-            ));
+                // The arguments of a call are only the types of the host
+                ArgType::Encoded => return Err(unsupported_type(type_path, false)),
+            }
         }
+
+        mod_func.push(quote!(
+            pub fn #func_name(contract_id: ContractId<'_>, #( #args ),*) -> i32 {
+                unsafe {
+                    #( #call_args )*
+                    wevm::v0::bindings::call_contract(contract_id.as_ptr(), contract_id.len(), #func_name_str.as_ptr(), #func_name_str.len())
+                }
+            }
+        ));
     }
 
     Ok(quote!(
@@ -98,71 +135,1092 @@ pub fn interface(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
 pub fn action(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
     let input = syn::parse2::<syn::ItemFn>(input)?;
 
+    check_signature(&input.sig)?;
+
     let name = &input.sig.ident;
     let block = &input.block;
 
     let mut args: Vec<TokenStream2> = vec![];
     let mut args_build: Vec<TokenStream2> = vec![];
+    let mut abi_args: Vec<String> = vec![];
 
     for arg in input.sig.inputs.iter() {
-        if let syn::FnArg::Typed(a) = arg {
-            if let syn::Pat::Ident(pat_ident) = &*a.pat {
-                let arg_name = &pat_ident.ident;
-
-                let offset = format_ident!("offset_{}", arg_name);
-                let length = format_ident!("length_{}", arg_name);
-
-                if let syn::Type::Path(type_path) = &*a.ty {
-                    let path_seg = &type_path.path.segments[0];
-                    let name = path_seg.ident.to_string();
-                    match name.as_str() {
-                        "Binary" => {
-                            args.push(quote!(
-                                #offset: *const u8
-                            ));
-
-                            args.push(quote!(
-                                #length: usize
-                            ));
-
-                            args_build.push(quote!(
-                                let #arg_name = core::slice::from_raw_parts(#offset, #length);
-                            ));
-                        }
-                        "String" => {
-                            args.push(quote!(
-                                #offset: *const u8
-                            ));
-
-                            args.push(quote!(
-                                #length: usize
-                            ));
-
-                            args_build.push(quote!(
-                                let #arg_name = {
-                                    let bytes = core::slice::from_raw_parts(#offset, #length);
-                                    core::str::from_utf8_unchecked(bytes)
-                                };
-                            ));
+        let Arg {
+            name: arg_name,
+            mutability,
+            ty,
+            type_path,
+        } = parse_arg(arg, true)?;
+
+        // Decoded arguments are passed as `Binary`, the schema names the decoded type
+        let schema = match ty {
+            ArgType::Encoded => format!(r#","schema":{}"#, json_string(&schema_path(type_path))),
+            _ => String::new(),
+        };
+        abi_args.push(format!(
+            r#"{{"name":{},"type":{}{}}}"#,
+            json_string(&arg_name.to_string()),
+            json_string(ty.as_str()),
+            schema
+        ));
+
+        let offset = format_ident!("offset_{}", arg_name);
+        let length = format_ident!("length_{}", arg_name);
+
+        match ty {
+            ArgType::Binary => {
+                args.push(quote!(
+                    #offset: *const u8
+                ));
+
+                args.push(quote!(
+                    #length: usize
+                ));
+
+                args_build.push(quote!(
+                    let #mutability #arg_name = core::slice::from_raw_parts(#offset, #length);
+                ));
+            }
+            ArgType::String => {
+                args.push(quote!(
+                    #offset: *const u8
+                ));
+
+                args.push(quote!(
+                    #length: usize
+                ));
+
+                args_build.push(quote!(
+                    let #mutability #arg_name = {
+                        let bytes = core::slice::from_raw_parts(#offset, #length);
+                        core::str::from_utf8_unchecked(bytes)
+                    };
+                ));
+            }
+            ArgType::Encoded => {
+                args.push(quote!(
+                    #offset: *const u8
+                ));
+
+                args.push(quote!(
+                    #length: usize
+                ));
+
+                // Malformed arguments stop the call with `Error::ParseError`
+                let decode = quote_spanned!(type_path.span()=>
+                    <#type_path as we_cdk::codec::Decode>::decode_from
+                );
+                args_build.push(quote!(
+                    let #mutability #arg_name: #type_path = {
+                        let bytes = core::slice::from_raw_parts(#offset, #length);
+                        match #decode(bytes) {
+                            Ok(value) => value,
+                            Err(error) => return we_cdk::Error::from(error).code(),
                         }
-                        _ => args.push(quote!(
-                            #arg_name: #type_path
-                        )),
-                    }
-                }
+                    };
+                ));
             }
+            ArgType::Integer | ArgType::Boolean => args.push(quote!(
+                #mutability #arg_name: #type_path
+            )),
         }
     }
 
+    // Actions returning `Result<(), E>` are executed in a closure,
+    // so that `?` and `return Err(..)` are converted to the error code.
+    // An error converted to zero would be reported as success, so it becomes `Error::Exception`
+    let body = match &input.sig.output {
+        syn::ReturnType::Type(_, output) if is_unit(output) => quote!(
+            #block
+            0
+        ),
+        syn::ReturnType::Type(_, output) if is_result(output) => {
+            check_result(output)?;
+            quote!(
+                let result = (move || -> #output #block)();
+                match result {
+                    Ok(()) => 0,
+                    Err(error) => match error.into() {
+                        0 => we_cdk::Error::Exception.code(),
+                        code => code,
+                    },
+                }
+            )
+        }
+        syn::ReturnType::Type(_, output) => {
+            return Err(syn::Error::new_spanned(
+                output,
+                "actions can only return `()` or `Result<(), E>` where `E: Into<i32>`",
+            ))
+        }
+        syn::ReturnType::Default => quote!(
+            #block
+            0
+        ),
+    };
+
+    let abi = custom_section(
+        ABI_SECTION,
+        format!(
+            r#"{{"name":{},"args":[{}]}}"#,
+            json_string(&name.to_string()),
+            abi_args.join(",")
+        ),
+    );
+
+    // Exported symbols are only needed by the wasm host,
+    // natively they would clash with the symbols of other libraries
     Ok(quote!(
-        #[no_mangle]
+        #[cfg_attr(target_arch = "wasm32", no_mangle)]
         pub extern "C" fn #name ( #( #args ),* ) -> i32 {
+            #abi
+
             unsafe {
                 #( #args_build )*
-                #block
+                #body
             }
+        }
+    ))
+}
 
-            0
+/// Types of arguments supported by the host,
+/// other types of actions are decoded from `Binary`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ArgType {
+    Integer,
+    Boolean,
+    Binary,
+    String,
+    Encoded,
+}
+
+impl ArgType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ArgType::Integer => "Integer",
+            ArgType::Boolean => "Boolean",
+            ArgType::Binary | ArgType::Encoded => "Binary",
+            ArgType::String => "String",
+        }
+    }
+}
+
+/// Primitive types other than the ones of the host.
+const PRIMITIVES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i128", "isize", "f32", "f64",
+    "char", "str",
+];
+
+/// Argument of an action or an interface function.
+struct Arg<'a> {
+    name: &'a syn::Ident,
+    mutability: Option<syn::token::Mut>,
+    ty: ArgType,
+    type_path: &'a syn::TypePath,
+}
+
+/// Checks the parts of the signature that cannot be called by the host.
+fn check_signature(sig: &syn::Signature) -> Result<(), syn::Error> {
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "contract functions cannot be `async`",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "contract functions cannot be generic",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses the argument, `encoded` allows the types implementing `Decode`.
+fn parse_arg(arg: &syn::FnArg, encoded: bool) -> Result<Arg<'_>, syn::Error> {
+    let arg = match arg {
+        syn::FnArg::Typed(arg) => arg,
+        syn::FnArg::Receiver(receiver) => {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "contract functions cannot take `self`",
+            ))
+        }
+    };
+
+    let pat_ident = match &*arg.pat {
+        syn::Pat::Ident(pat_ident) if pat_ident.by_ref.is_none() && pat_ident.subpat.is_none() => {
+            pat_ident
+        }
+        pat => {
+            return Err(syn::Error::new_spanned(
+                pat,
+                "argument patterns are not supported, use an identifier",
+            ))
+        }
+    };
+
+    let unsupported = || unsupported_type(&arg.ty, encoded);
+
+    let type_path = match &*arg.ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => type_path,
+        _ => return Err(unsupported()),
+    };
+
+    // `Integer` and `Boolean` are aliases of `i64` and `bool`,
+    // the other primitives are not passed by the host nor decoded
+    let ty = match type_name(type_path).as_str() {
+        "Integer" | "i64" => ArgType::Integer,
+        "Boolean" | "bool" => ArgType::Boolean,
+        "Binary" => ArgType::Binary,
+        "String" => ArgType::String,
+        name if PRIMITIVES.contains(&name) => return Err(unsupported()),
+        _ if encoded => ArgType::Encoded,
+        _ => return Err(unsupported()),
+    };
+
+    Ok(Arg {
+        name: &pat_ident.ident,
+        mutability: pat_ident.mutability,
+        ty,
+        type_path,
+    })
+}
+
+fn unsupported_type(ty: impl ToTokens, encoded: bool) -> syn::Error {
+    let expected = match encoded {
+        true => "`Integer`, `Boolean`, `Binary`, `String` or a type implementing `Decode`",
+        false => "`Integer`, `Boolean`, `Binary` or `String`",
+    };
+    syn::Error::new_spanned(
+        ty,
+        format!("unsupported argument type, expected {}", expected),
+    )
+}
+
+fn is_unit(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Tuple(tuple) if tuple.elems.is_empty())
+}
+
+fn is_result(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(type_path) if type_name(type_path) == "Result")
+}
+
+/// The result of an action is `Result<(), E>`, or `Result<()>` of `we_cdk` with its default error.
+fn check_result(ty: &syn::Type) -> syn::Result<()> {
+    let arguments = match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| &segment.arguments),
+        _ => None,
+    };
+    let args: Vec<&syn::GenericArgument> = match arguments {
+        Some(syn::PathArguments::AngleBracketed(arguments)) => arguments.args.iter().collect(),
+        _ => vec![],
+    };
+
+    match args.as_slice() {
+        [syn::GenericArgument::Type(ok)]
+        | [syn::GenericArgument::Type(ok), syn::GenericArgument::Type(_)]
+            if is_unit(ok) =>
+        {
+            Ok(())
+        }
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "actions can only return `()` or `Result<(), E>` where `E: Into<i32>`",
+        )),
+    }
+}
+
+/// Get the name of the type, `we_cdk::Binary` and `Binary` are the same type.
+fn type_name(type_path: &syn::TypePath) -> String {
+    match type_path.path.segments.last() {
+        Some(segment) => segment.ident.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Places the JSON record into a custom section of the wasm module.
+/// The linker concatenates the records of all items, one per line.
+fn custom_section(section: &str, record: String) -> TokenStream2 {
+    let record = format!("{}\n", record);
+    let len = record.len();
+    let bytes = syn::LitByteStr::new(record.as_bytes(), Span::call_site());
+
+    quote!(
+        #[cfg(target_arch = "wasm32")]
+        #[link_section = #section]
+        #[used]
+        static METADATA: [u8; #len] = *#bytes;
+    )
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Assigning codes and messages to the variants of a contract error.
+pub fn contract_error(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
+    let input = syn::parse2::<syn::DeriveInput>(input)?;
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "contract errors cannot be generic",
+        ));
+    }
+
+    let data = match &input.data {
+        syn::Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`ContractError` can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut codes: Vec<(i64, &syn::Ident, String)> = vec![];
+    let mut next: i64 = 0;
+
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "variants of contract errors cannot have fields",
+            ));
+        }
+
+        // Codes follow the rules of enum discriminants, so rustc rejects duplicates
+        let code = match &variant.discriminant {
+            Some((_, expr)) => parse_code(expr)?,
+            None => next,
+        };
+        next = code + 1;
+
+        if code == 0 {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "error code 0 means success, set a discriminant",
+            ));
+        }
+        if (100..=399).contains(&code) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("error code {} is reserved for the host (100..=399)", code),
+            ));
+        }
+        if i32::try_from(code).is_err() {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("error code {} does not fit into i32", code),
+            ));
+        }
+
+        let mut message = variant.ident.to_string();
+        for attr in variant.attrs.iter() {
+            if attr.path().is_ident("error") {
+                message = attr.parse_args::<syn::LitStr>()?.value();
+            }
+        }
+
+        codes.push((code, &variant.ident, message));
+    }
+
+    let code: Vec<i32> = codes.iter().map(|(code, _, _)| *code as i32).collect();
+    let variant: Vec<&syn::Ident> = codes.iter().map(|(_, variant, _)| *variant).collect();
+    let variant_name: Vec<String> = variant.iter().map(|variant| variant.to_string()).collect();
+    let message: Vec<&String> = codes.iter().map(|(_, _, message)| message).collect();
+    let report: Vec<String> = codes
+        .iter()
+        .map(|(code, _, message)| format!("Code: {}. Error: {}.", code, message))
+        .collect();
+
+    let records: Vec<String> = codes
+        .iter()
+        .map(|(code, variant, message)| {
+            format!(
+                r#"{{"code":{},"name":{},"message":{}}}"#,
+                code,
+                json_string(&format!("{}::{}", name, variant)),
+                json_string(message)
+            )
+        })
+        .collect();
+    let errors = custom_section(ERRORS_SECTION, records.join("\n"));
+
+    Ok(quote!(
+        const _: () = {
+            #errors
+        };
+
+        impl we_cdk::ContractError for #name {
+            const CODES: &'static [(i32, &'static str, &'static str)] = &[
+                #( (#code, #variant_name, #message) ),*
+            ];
+
+            fn code(&self) -> i32 {
+                match self {
+                    #( #name::#variant => #code ),*
+                }
+            }
+
+            fn message(&self) -> &'static str {
+                match self {
+                    #( #name::#variant => #message ),*
+                }
+            }
+
+            fn report(&self) -> &'static str {
+                match self {
+                    #( #name::#variant => #report ),*
+                }
+            }
+        }
+
+        impl From<#name> for i32 {
+            fn from(error: #name) -> Self {
+                we_cdk::ContractError::code(&error)
+            }
+        }
+
+        impl From<#name> for we_cdk::Error {
+            fn from(error: #name) -> Self {
+                we_cdk::Error::from(we_cdk::ContractError::code(&error))
+            }
+        }
+    ))
+}
+
+fn parse_code(expr: &syn::Expr) -> Result<i64, syn::Error> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_code(expr).map(|code| -code),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "error code must be an integer literal",
+        )),
+    }
+}
+
+/// Implementing the encoding of `we_cdk::codec` for a struct or an enum.
+pub fn encode(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
+    let input = syn::parse2::<syn::DeriveInput>(input)?;
+    let name = &input.ident;
+    let version = codec_version(&input)?;
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(we_cdk::codec::Encode));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        syn::Data::Struct(data) => {
+            let (pattern, bindings) = fields_pattern(&data.fields);
+            let version = version.map(|version| quote!(writer.write_u8(#version)?;));
+            quote!(
+                #version
+                let Self #pattern = self;
+                #( we_cdk::codec::Encode::encode(#bindings, writer)?; )*
+            )
+        }
+        syn::Data::Enum(data) => {
+            let mut arms: Vec<TokenStream2> = vec![];
+            for (index, variant) in data.variants.iter().enumerate() {
+                let variant_name = &variant.ident;
+                let index = variant_index(variant, index)?;
+                let (pattern, bindings) = fields_pattern(&variant.fields);
+                arms.push(quote!(
+                    Self::#variant_name #pattern => {
+                        writer.write_u8(#index)?;
+                        #( we_cdk::codec::Encode::encode(#bindings, writer)?; )*
+                    }
+                ));
+            }
+            quote!(
+                match self {
+                    #( #arms )*
+                }
+            )
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`Encode` can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    Ok(quote!(
+        impl #impl_generics we_cdk::codec::Encode for #name #ty_generics #where_clause {
+            fn encode(
+                &self,
+                writer: &mut we_cdk::codec::Writer<'_>,
+            ) -> core::result::Result<(), we_cdk::codec::Error> {
+                #body
+                Ok(())
+            }
+        }
+    ))
+}
+
+/// Implementing the decoding of `we_cdk::codec` for a struct or an enum.
+pub fn decode(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
+    let input = syn::parse2::<syn::DeriveInput>(input)?;
+    let name = &input.ident;
+    let version = codec_version(&input)?;
+
+    // Binary and String fields borrow the decoded bytes for the lifetime of the type
+    let mut generics = input.generics.clone();
+    let lifetime = match input.generics.lifetimes().collect::<Vec<_>>().as_slice() {
+        [] => {
+            let lifetime: syn::Lifetime = syn::parse_quote!('__codec);
+            generics.params.insert(
+                0,
+                syn::GenericParam::Lifetime(syn::LifetimeParam::new(lifetime.clone())),
+            );
+            lifetime
+        }
+        [param] => param.lifetime.clone(),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.generics,
+                "`Decode` can only be derived for types with at most one lifetime",
+            ))
+        }
+    };
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(syn::parse_quote!(we_cdk::codec::Decode<#lifetime>));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        syn::Data::Struct(data) => {
+            let construct = fields_decode(&data.fields, version.is_some())?;
+            match version {
+                Some(version) => {
+                    let newer = match version {
+                        u8::MAX => quote!(),
+                        version => quote!(|| version > #version),
+                    };
+                    quote!(
+                        let version = reader.read_u8()?;
+                        if version == 0 #newer {
+                            return Err(we_cdk::codec::Error::UnknownVersion);
+                        }
+                        Ok(Self #construct)
+                    )
+                }
+                None => quote!(Ok(Self #construct)),
+            }
+        }
+        syn::Data::Enum(data) => {
+            let mut arms: Vec<TokenStream2> = vec![];
+            for (index, variant) in data.variants.iter().enumerate() {
+                let variant_name = &variant.ident;
+                let index = variant_index(variant, index)?;
+                let construct = fields_decode(&variant.fields, false)?;
+                arms.push(quote!(
+                    #index => Ok(Self::#variant_name #construct),
+                ));
+            }
+            quote!(
+                match reader.read_u8()? {
+                    #( #arms )*
+                    _ => Err(we_cdk::codec::Error::InvalidValue),
+                }
+            )
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`Decode` can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    let schema = custom_section(TYPES_SECTION, type_schema(&input, version));
+
+    Ok(quote!(
+        const _: () = {
+            #schema
+        };
+
+        impl #impl_generics we_cdk::codec::Decode<#lifetime> for #name #ty_generics #where_clause {
+            fn decode(
+                reader: &mut we_cdk::codec::Reader<#lifetime>,
+            ) -> core::result::Result<Self, we_cdk::codec::Error> {
+                #body
+            }
         }
     ))
 }
+
+/// Get the version set by `#[codec(version = N)]`, checking the versions of the fields.
+fn codec_version(input: &syn::DeriveInput) -> Result<Option<u8>, syn::Error> {
+    let version = codec_attr(&input.attrs, "version")?;
+
+    match &input.data {
+        syn::Data::Struct(data) => {
+            for field in data.fields.iter() {
+                let since = match codec_attr(&field.attrs, "since")? {
+                    Some(since) => since,
+                    None => continue,
+                };
+                match version {
+                    None => {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            "fields can only be added to versioned structs, set `#[codec(version = N)]`",
+                        ))
+                    }
+                    Some(version) if since > version => {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            format!(
+                                "field added in version {} of a struct of version {}",
+                                since, version
+                            ),
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        syn::Data::Enum(data) => {
+            if version.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "only structs can be versioned, add variants at the end of the enum instead",
+                ));
+            }
+            for field in data
+                .variants
+                .iter()
+                .flat_map(|variant| variant.fields.iter())
+            {
+                if codec_attr(&field.attrs, "since")?.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "only fields of structs can be versioned",
+                    ));
+                }
+            }
+        }
+        syn::Data::Union(_) => {}
+    }
+
+    Ok(version)
+}
+
+/// Get the value of `#[codec(key = N)]`, versions start at 1.
+fn codec_attr(attrs: &[syn::Attribute], key: &str) -> Result<Option<u8>, syn::Error> {
+    let mut value = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident(key) {
+                return Err(meta.error(format!("unsupported attribute, expected `{}`", key)));
+            }
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            match lit.base10_parse::<u8>()? {
+                0 => Err(syn::Error::new_spanned(lit, "versions start at 1")),
+                version => {
+                    value = Some(version);
+                    Ok(())
+                }
+            }
+        })?;
+    }
+
+    Ok(value)
+}
+
+/// Get the index of the variant written as its tag.
+fn variant_index(variant: &syn::Variant, index: usize) -> Result<u8, syn::Error> {
+    u8::try_from(index).map_err(|_| {
+        syn::Error::new_spanned(variant, "enums can have at most 256 variants to be encoded")
+    })
+}
+
+/// Get the pattern binding the fields and the names of the bindings.
+fn fields_pattern(fields: &syn::Fields) -> (TokenStream2, Vec<syn::Ident>) {
+    let bindings: Vec<syn::Ident> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("field_{}", index),
+        })
+        .collect();
+
+    let pattern = match fields {
+        syn::Fields::Named(_) => quote!({ #( #bindings ),* }),
+        syn::Fields::Unnamed(_) => quote!(( #( #bindings ),* )),
+        syn::Fields::Unit => quote!(),
+    };
+
+    (pattern, bindings)
+}
+
+/// Get the constructor of the fields decoded in order,
+/// the fields added in a later version than the decoded one get their default value.
+fn fields_decode(fields: &syn::Fields, versioned: bool) -> Result<TokenStream2, syn::Error> {
+    let mut values: Vec<TokenStream2> = vec![];
+    for field in fields.iter() {
+        let since = match versioned {
+            true => codec_attr(&field.attrs, "since")?,
+            false => None,
+        };
+        values.push(match since {
+            Some(since) if since > 1 => quote!(
+                if version >= #since {
+                    we_cdk::codec::Decode::decode(reader)?
+                } else {
+                    core::default::Default::default()
+                }
+            ),
+            _ => quote!(we_cdk::codec::Decode::decode(reader)?),
+        });
+    }
+
+    Ok(match fields {
+        syn::Fields::Named(_) => {
+            let names = fields.iter().map(|field| &field.ident);
+            quote!({ #( #names: #values ),* })
+        }
+        syn::Fields::Unnamed(_) => quote!(( #( #values ),* )),
+        syn::Fields::Unit => quote!(),
+    })
+}
+
+/// Get the JSON schema of the decoded type, with the fields in the order of the encoding.
+fn type_schema(input: &syn::DeriveInput, version: Option<u8>) -> String {
+    let fields_schema = |fields: &syn::Fields| -> String {
+        let fields: Vec<String> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let name = match &field.ident {
+                    Some(ident) => ident.to_string(),
+                    None => index.to_string(),
+                };
+                // Checked by `codec_version`
+                let since = match codec_attr(&field.attrs, "since") {
+                    Ok(Some(since)) if version.is_some() => format!(r#","since":{}"#, since),
+                    _ => String::new(),
+                };
+                format!(
+                    r#"{{"name":{},"type":{}{}}}"#,
+                    json_string(&name),
+                    json_string(&schema_type(&field.ty)),
+                    since
+                )
+            })
+            .collect();
+        format!("[{}]", fields.join(","))
+    };
+
+    let body = match &input.data {
+        syn::Data::Struct(data) => {
+            let version = match version {
+                Some(version) => format!(r#""version":{},"#, version),
+                None => String::new(),
+            };
+            format!(r#"{}"fields":{}"#, version, fields_schema(&data.fields))
+        }
+        syn::Data::Enum(data) => {
+            let variants: Vec<String> = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    format!(
+                        r#"{{"name":{},"fields":{}}}"#,
+                        json_string(&variant.ident.to_string()),
+                        fields_schema(&variant.fields)
+                    )
+                })
+                .collect();
+            format!(r#""variants":[{}]"#, variants.join(","))
+        }
+        syn::Data::Union(_) => String::new(),
+    };
+
+    format!(
+        r#"{{"name":{},{}}}"#,
+        json_string(&input.ident.to_string()),
+        body
+    )
+}
+
+/// Get the name of the type in the schema, without lifetimes and paths.
+/// The types of the host are named as in the ABI.
+fn schema_type(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(type_path) => schema_path(type_path),
+        syn::Type::Reference(reference) => match &*reference.elem {
+            syn::Type::Slice(slice) if schema_type(&slice.elem) == "u8" => "Binary".to_string(),
+            syn::Type::Path(type_path) if type_path.path.is_ident("str") => "String".to_string(),
+            elem => schema_type(elem),
+        },
+        syn::Type::Array(array) => {
+            let len = &array.len;
+            format!("[{}; {}]", schema_type(&array.elem), quote!(#len))
+        }
+        syn::Type::Tuple(tuple) => {
+            let elems: Vec<String> = tuple.elems.iter().map(schema_type).collect();
+            format!("({})", elems.join(", "))
+        }
+        syn::Type::Paren(paren) => schema_type(&paren.elem),
+        syn::Type::Group(group) => schema_type(&group.elem),
+        ty => quote!(#ty).to_string(),
+    }
+}
+
+/// Get the name of the type with its type arguments, `Option<Bid>` is not `Option`.
+fn schema_path(type_path: &syn::TypePath) -> String {
+    let segment = match type_path.path.segments.last() {
+        Some(segment) => segment,
+        None => return String::new(),
+    };
+    let name = match segment.ident.to_string().as_str() {
+        "i64" => "Integer".to_string(),
+        "bool" => "Boolean".to_string(),
+        name => name.to_string(),
+    };
+    let args: Vec<String> = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(schema_type(ty)),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+    match args.is_empty() {
+        true => name,
+        false => format!("{}<{}>", name, args.join(", ")),
+    }
+}
+
+/// Alphabet of the Base58 encoding.
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// Version of the addresses.
+const ADDRESS_VERSION: u8 = 1;
+/// Length of an address: version, chain id, hash of the public key and checksum.
+const ADDRESS_LENGTH: usize = 26;
+/// Length of the checksum ending an address.
+const CHECKSUM_LENGTH: usize = 4;
+/// Length of the ids of contracts and assets.
+const ID_LENGTH: usize = 32;
+
+/// Literal passed to `base58!`, optionally preceded by the kind of the value.
+struct Base58Literal {
+    kind: Option<syn::Ident>,
+    value: syn::LitStr,
+}
+
+impl syn::parse::Parse for Base58Literal {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let kind = match input.peek(syn::Ident) {
+            true => {
+                let kind = input.parse()?;
+                input.parse::<syn::Token![=>]>()?;
+                Some(kind)
+            }
+            false => None,
+        };
+        let value = input.parse()?;
+        Ok(Self { kind, value })
+    }
+}
+
+/// Decode the Base58 literal into bytes,
+/// checking the length of the ids and the version and checksum of the addresses.
+pub fn base58(input: TokenStream2) -> Result<TokenStream2, syn::Error> {
+    let Base58Literal { kind, value } = syn::parse2(input)?;
+
+    let bytes =
+        decode_base58(&value.value()).map_err(|message| syn::Error::new(value.span(), message))?;
+    let length = |name: &str, expected: usize| match bytes.len() == expected {
+        true => Ok(()),
+        false => Err(syn::Error::new(
+            value.span(),
+            format!(
+                "{name} must be {expected} bytes long, found {}",
+                bytes.len()
+            ),
+        )),
+    };
+
+    let literal = quote!(&[#( #bytes ),*] as &'static [u8]);
+    let kind = match kind {
+        Some(kind) => kind,
+        None => return Ok(literal),
+    };
+
+    match kind.to_string().as_str() {
+        "address" => {
+            length("an address", ADDRESS_LENGTH)?;
+            if bytes[0] != ADDRESS_VERSION {
+                return Err(syn::Error::new(
+                    value.span(),
+                    format!("unsupported version {} of the address", bytes[0]),
+                ));
+            }
+            let (body, checksum) = bytes.split_at(ADDRESS_LENGTH - CHECKSUM_LENGTH);
+            if secure_hash(body)[..CHECKSUM_LENGTH] != *checksum {
+                return Err(syn::Error::new(
+                    value.span(),
+                    "invalid checksum of the address",
+                ));
+            }
+            Ok(quote!(we_cdk::Address::new_unchecked(#literal)))
+        }
+        "asset" => {
+            length("an asset id", ID_LENGTH)?;
+            Ok(quote!(we_cdk::AssetId::new_unchecked(#literal)))
+        }
+        "contract" => {
+            length("a contract id", ID_LENGTH)?;
+            Ok(quote!(we_cdk::ContractId::new_unchecked(#literal)))
+        }
+        _ => Err(syn::Error::new_spanned(
+            kind,
+            "expected `address`, `asset` or `contract`",
+        )),
+    }
+}
+
+fn decode_base58(value: &str) -> Result<Vec<u8>, String> {
+    // Big-endian digits in base 256
+    let mut bytes: Vec<u8> = vec![];
+    for (index, char) in value.char_indices() {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&digit| char == digit as char)
+            .ok_or_else(|| format!("invalid Base58 character `{char}` at {index}"))?
+            as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    // Leading ones stand for zero bytes
+    let zeros = value.bytes().take_while(|&char| char == b'1').count();
+    let mut result = vec![0; zeros];
+    result.extend(bytes);
+    Ok(result)
+}
+
+/// Keccak-256 of the Blake2b-256 hash, as computed by the host.
+fn secure_hash(value: &[u8]) -> Vec<u8> {
+    use blake2::{digest::consts::U32, Blake2b, Digest};
+    use sha3::Keccak256;
+
+    let hash = Blake2b::<U32>::digest(value);
+    Keccak256::digest(hash).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg(arg: syn::FnArg, encoded: bool) -> Result<(ArgType, String), String> {
+        parse_arg(&arg, encoded)
+            .map(|arg| (arg.ty, schema_path(arg.type_path)))
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn arg_types() {
+        let cases: [(syn::FnArg, ArgType); 6] = [
+            (syn::parse_quote!(amount: Integer), ArgType::Integer),
+            (syn::parse_quote!(amount: i64), ArgType::Integer),
+            (syn::parse_quote!(flag: bool), ArgType::Boolean),
+            (syn::parse_quote!(flag: we_cdk::Boolean), ArgType::Boolean),
+            (syn::parse_quote!(data: Binary), ArgType::Binary),
+            (syn::parse_quote!(name: String), ArgType::String),
+        ];
+
+        for (input, ty) in cases {
+            assert_eq!(arg(input.clone(), true).unwrap().0, ty);
+            assert_eq!(arg(input, false).unwrap().0, ty);
+        }
+    }
+
+    #[test]
+    fn unsupported_primitives() {
+        for input in [
+            syn::parse_quote!(value: u8),
+            syn::parse_quote!(value: u64),
+            syn::parse_quote!(value: i32),
+            syn::parse_quote!(value: f64),
+            syn::parse_quote!(value: core::primitive::u8),
+        ] {
+            assert_eq!(
+                arg(input, true).unwrap_err(),
+                "unsupported argument type, expected `Integer`, `Boolean`, `Binary`, `String` or a type implementing `Decode`"
+            );
+        }
+    }
+
+    #[test]
+    fn encoded_schemas() {
+        assert_eq!(
+            arg(syn::parse_quote!(bid: Bid), true),
+            Ok((ArgType::Encoded, "Bid".to_string()))
+        );
+        assert_eq!(
+            arg(syn::parse_quote!(bid: auction::Bid<'static>), true),
+            Ok((ArgType::Encoded, "Bid".to_string()))
+        );
+        assert_eq!(
+            arg(syn::parse_quote!(bid: Option<Bid>), true),
+            Ok((ArgType::Encoded, "Option<Bid>".to_string()))
+        );
+        assert_eq!(
+            arg(syn::parse_quote!(bids: Vec<(i64, Bid)>), true),
+            Ok((ArgType::Encoded, "Vec<(Integer, Bid)>".to_string()))
+        );
+        assert_eq!(
+            arg(syn::parse_quote!(bid: Bid), false).unwrap_err(),
+            "unsupported argument type, expected `Integer`, `Boolean`, `Binary` or `String`"
+        );
+    }
+}
@@ -7,15 +7,67 @@ use proc_macro::TokenStream;
 pub fn interface(_attr: TokenStream, item: TokenStream) -> TokenStream {
     match generator::interface(item.into()) {
         Ok(result) => result.into(),
-        Err(_) => panic!(),
+        Err(error) => error.to_compile_error().into(),
     }
 }
 
 /// Marks function as a called function.
+///
+/// The function may return `Result<(), E>` where `E: Into<i32>`,
+/// the error is then returned to the host as the error code of the call.
+///
+/// Arguments of other types than `Integer`, `Boolean`, `Binary` and `String`
+/// are passed as `Binary` and decoded with `we_cdk::codec::Decode`.
 #[proc_macro_attribute]
 pub fn action(_attr: TokenStream, item: TokenStream) -> TokenStream {
     match generator::action(item.into()) {
         Ok(result) => result.into(),
-        Err(_) => panic!(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Assigns codes and messages to the variants of a contract error.
+///
+/// The code of a variant is its discriminant, the message is set by `#[error("...")]`
+/// and defaults to the name of the variant.
+#[proc_macro_derive(ContractError, attributes(error))]
+pub fn contract_error(item: TokenStream) -> TokenStream {
+    match generator::contract_error(item.into()) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Implements `we_cdk::codec::Encode`, the fields are written in order.
+///
+/// A struct with `#[codec(version = N)]` is prefixed with its version,
+/// the variant of an enum with its index.
+#[proc_macro_derive(Encode, attributes(codec))]
+pub fn encode(item: TokenStream) -> TokenStream {
+    match generator::encode(item.into()) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Implements `we_cdk::codec::Decode`, borrowing `Binary` and `String` fields from the decoded bytes.
+///
+/// Fields marked with `#[codec(since = N)]` are set to their default value
+/// when decoding a version of the struct earlier than `N`.
+#[proc_macro_derive(Decode, attributes(codec))]
+pub fn decode(item: TokenStream) -> TokenStream {
+    match generator::decode(item.into()) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Decodes a Base58 literal during compilation, used by `we_cdk::base58!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn base58_literal(input: TokenStream) -> TokenStream {
+    match generator::base58(input.into()) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
     }
 }
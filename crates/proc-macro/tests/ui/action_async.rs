@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+#[action]
+async fn flip() {}
+
+fn main() {}
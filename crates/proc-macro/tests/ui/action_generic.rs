@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+#[action]
+fn flip<T>(value: Integer) {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+#[action]
+fn flip((left, right): (Integer, Integer)) {}
+
+fn main() {}
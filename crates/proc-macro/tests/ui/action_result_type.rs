@@ -0,0 +1,18 @@
+use we_cdk::*;
+
+#[action]
+fn flip() -> Result<Integer> {
+    Ok(0)
+}
+
+#[action]
+fn flop() -> Result<Integer, Error> {
+    Ok(0)
+}
+
+#[action]
+fn swap() -> Result {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use we_cdk::*;
+
+#[action]
+fn flip() -> Integer {
+    0
+}
+
+fn main() {}
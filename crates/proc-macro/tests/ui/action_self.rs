@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+#[action]
+fn flip(&self, value: Integer) {}
+
+fn main() {}
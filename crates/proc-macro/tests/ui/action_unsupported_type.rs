@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+#[action]
+fn flip(value: u8) {}
+
+fn main() {}
@@ -0,0 +1,5 @@
+use we_cdk::*;
+
+const TOKEN: Binary = base58!("DnK5Xfi2wXUJx9BjK9X6ZpFdTLdq2GtWH0pWrcxcmrhB");
+
+fn main() {}
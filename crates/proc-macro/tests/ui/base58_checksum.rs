@@ -0,0 +1,5 @@
+use we_cdk::*;
+
+const OWNER: Address = base58!(address => "3NzkzibVRkKUzaRzjUxndpTPvoBzQ3iLng4");
+
+fn main() {}
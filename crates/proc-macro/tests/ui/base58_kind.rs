@@ -0,0 +1,5 @@
+use we_cdk::*;
+
+const LEASE: Binary = base58!(lease => "6Tn7ir9MycHW6Gq2F2dGok2stokSwXJadPh4hW8eZ8Sp");
+
+fn main() {}
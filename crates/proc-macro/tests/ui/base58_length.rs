@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+// An address instead of an asset id
+const TOKEN: AssetId = base58!(asset => "3NzkzibVRkKUzaRzjUxndpTPvoBzQ3iLng3");
+
+fn main() {}
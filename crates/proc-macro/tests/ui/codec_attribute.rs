@@ -0,0 +1,9 @@
+use we_cdk::*;
+
+#[derive(Encode)]
+#[codec(since = 1)]
+struct Order {
+    amount: Integer,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use we_cdk::*;
+
+#[derive(Decode)]
+struct Order<'a, 'b> {
+    owner: Binary<'a>,
+    memo: String<'b>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use we_cdk::*;
+
+#[derive(Encode, Decode)]
+#[codec(version = 2)]
+struct Order {
+    amount: Integer,
+    #[codec(since = 3)]
+    expires: Integer,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use we_cdk::*;
+
+#[derive(Encode, Decode)]
+struct Order {
+    amount: Integer,
+    #[codec(since = 2)]
+    expires: Integer,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use we_cdk::*;
+
+#[derive(Encode, Decode)]
+#[codec(version = 1)]
+enum Status {
+    Open,
+    Filled,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use we_cdk::*;
+
+#[derive(Encode, Decode)]
+#[codec(version = 0)]
+struct Order {
+    amount: Integer,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use we_cdk::*;
+
+const BASE: isize = 1000;
+
+#[derive(ContractError)]
+enum FlipError {
+    Disabled = BASE,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use we_cdk::*;
+
+#[derive(ContractError)]
+enum FlipError<T> {
+    Disabled(T),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use we_cdk::*;
+
+#[derive(ContractError)]
+enum FlipError {
+    #[error(1000)]
+    Disabled = 1000,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use we_cdk::*;
+
+#[derive(ContractError)]
+#[repr(i64)]
+enum FlipError {
+    Disabled = 4294967296,
+}
+
+fn main() {}
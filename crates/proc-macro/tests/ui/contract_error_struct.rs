@@ -0,0 +1,6 @@
+use we_cdk::*;
+
+#[derive(ContractError)]
+struct FlipError;
+
+fn main() {}
@@ -0,0 +1,8 @@
+use we_cdk::*;
+
+#[derive(ContractError)]
+enum FlipError {
+    Disabled,
+}
+
+fn main() {}
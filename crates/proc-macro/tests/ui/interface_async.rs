@@ -0,0 +1,8 @@
+use we_cdk::*;
+
+#[interface]
+trait i_contract {
+    async fn flip(value: Integer);
+}
+
+fn main() {}
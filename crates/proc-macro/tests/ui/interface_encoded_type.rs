@@ -0,0 +1,13 @@
+use we_cdk::*;
+
+#[derive(Encode, Decode)]
+struct Order {
+    amount: Integer,
+}
+
+#[interface]
+trait i_shop {
+    fn place(order: Order);
+}
+
+fn main() {}
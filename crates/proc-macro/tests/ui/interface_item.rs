@@ -0,0 +1,8 @@
+use we_cdk::*;
+
+#[interface]
+trait i_contract {
+    const VALUE: Integer;
+}
+
+fn main() {}
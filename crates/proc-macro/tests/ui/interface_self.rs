@@ -0,0 +1,8 @@
+use we_cdk::*;
+
+#[interface]
+trait i_contract {
+    fn flip(&self, value: Integer);
+}
+
+fn main() {}
@@ -0,0 +1,127 @@
+//! In-memory host of the Waves Enterprise virtual machine, with a world kept per thread.
+//!
+//! The bindings of `v0` and `v1` mirror the `wevm` host functions over a mock [`World`].
+//! The `test-env` feature of `we-cdk` routes the macros to them to unit-test contracts natively,
+//! and `cargo we test` calls them from the host functions of its wasm interpreter.
+//!
+//! Keys that were never written read as the default value of the requested type.
+// The bindings mirror the `extern "C"` declarations of `wevm`,
+// which are unsafe to call for the same reasons.
+#![allow(clippy::missing_safety_doc)]
+
+#[doc(hidden)]
+pub mod v0;
+#[doc(hidden)]
+pub mod v1;
+mod world;
+
+pub use world::{Asset, Block, Call, ContractHandler, DataEntry, Holder, Lease, Tx, World};
+
+use std::cell::RefCell;
+use world::Frame;
+
+/// Drop-in replacement of the `wevm` bindings used by the macros.
+#[doc(hidden)]
+pub mod wevm {
+    pub mod v0 {
+        pub use crate::v0 as bindings;
+    }
+
+    pub mod v1 {
+        pub use crate::v1 as bindings;
+    }
+}
+
+thread_local! {
+    static WORLD: RefCell<World> = RefCell::new(World::default());
+    static MEMORY: RefCell<Vec<Box<[u64]>>> = const { RefCell::new(vec![]) };
+}
+
+/// Runs the closure with the world of the current test thread.
+///
+/// Contract functions must not be called from inside the closure.
+pub fn with<R>(f: impl FnOnce(&mut World) -> R) -> R {
+    WORLD.with(|world| f(&mut world.borrow_mut()))
+}
+
+/// Replaces the world of the current test thread with an empty one.
+///
+/// Tests usually run in separate threads, but the world is shared
+/// when they run sequentially on a single thread (`--test-threads=1`).
+pub fn reset() {
+    WORLD.with(|world| *world.borrow_mut() = World::default());
+    MEMORY.with(|memory| memory.borrow_mut().clear());
+}
+
+/// Copies the data to memory that lives until the world is reset,
+/// as the host does with the linear memory of the contract.
+pub(crate) fn write_memory(bytes: &[u8]) -> (*const u8, usize) {
+    // Backed by `u64` so that the contract can read integers through the pointer
+    let mut buffer = vec![0u64; bytes.len().div_ceil(8)].into_boxed_slice();
+    let ptr = buffer.as_mut_ptr() as *mut u8;
+    unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+    MEMORY.with(|memory| memory.borrow_mut().push(buffer));
+    (ptr, bytes.len())
+}
+
+/// Reads the data passed by the contract.
+/// Empty data may be passed as a null pointer (see `internal_data!`).
+pub(crate) unsafe fn read_memory<'a>(offset: *const u8, length: usize) -> &'a [u8] {
+    if length == 0 {
+        &[]
+    } else {
+        core::slice::from_raw_parts(offset, length)
+    }
+}
+
+pub(crate) fn bytes_result(result: world::Result<Vec<u8>>) -> (i32, *const u8, usize) {
+    match result {
+        Ok(bytes) => {
+            let (ptr, len) = write_memory(&bytes);
+            (0, ptr, len)
+        }
+        Err(error) => (error, core::ptr::null(), 0),
+    }
+}
+
+pub(crate) fn value_result<T: Default>(result: world::Result<T>) -> (i32, T) {
+    match result {
+        Ok(value) => (0, value),
+        Err(error) => (error, T::default()),
+    }
+}
+
+pub(crate) fn unit_result(result: world::Result<()>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(error) => error,
+    }
+}
+
+/// Executes the registered handler of the called contract in its own frame.
+pub(crate) fn call_contract(
+    contract_id: &[u8],
+    func_name: &[u8],
+    params: Option<Vec<DataEntry>>,
+) -> i32 {
+    let (call, handler) = match with(|world| world.prepare_call(contract_id, func_name, params)) {
+        Ok(result) => result,
+        Err(error) => return error,
+    };
+
+    let handler = match handler {
+        Some(handler) => handler,
+        None => return 0,
+    };
+
+    let frame = Frame {
+        contract_id: call.contract_id.clone(),
+        caller: call.caller.clone(),
+        payments: call.payments.clone(),
+    };
+    let frame = with(|world| core::mem::replace(&mut world.frame, frame));
+    let result = handler(&call);
+    with(|world| world.frame = frame);
+
+    result
+}
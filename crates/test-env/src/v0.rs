@@ -0,0 +1,622 @@
+use super::{
+    bytes_result, read_memory, unit_result, value_result, with,
+    world::{
+        DataEntry, Holder, BASE58_ERROR, CONVERTING_NUMERIC_TYPES, FAILED_DESERIALIZE, PARSE_ERROR,
+        UTF8_ERROR,
+    },
+    write_memory,
+};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use sha2::Sha256;
+use sha3::Keccak256;
+
+// Asset
+
+pub unsafe fn get_balance(
+    offset_asset_id: *const u8,
+    length_asset_id: usize,
+    offset_address: *const u8,
+    length_address: usize,
+) -> (i32, i64) {
+    super::v1::get_balance(
+        offset_asset_id,
+        length_asset_id,
+        offset_address,
+        length_address,
+        0,
+        1,
+    )
+}
+
+pub unsafe fn transfer(
+    offset_asset_id: *const u8,
+    length_asset_id: usize,
+    offset_recipient: *const u8,
+    length_recipient: usize,
+    amount: i64,
+) -> i32 {
+    super::v1::transfer(
+        offset_asset_id,
+        length_asset_id,
+        offset_recipient,
+        length_recipient,
+        0,
+        1,
+        amount,
+    )
+}
+
+pub unsafe fn issue(
+    offset_name: *const u8,
+    length_name: usize,
+    offset_description: *const u8,
+    length_description: usize,
+    quantity: i64,
+    decimals: i32,
+    is_reissuable: bool,
+) -> (i32, *const u8, usize) {
+    super::v1::issue(
+        offset_name,
+        length_name,
+        offset_description,
+        length_description,
+        quantity,
+        decimals as i64,
+        is_reissuable,
+    )
+}
+
+pub unsafe fn burn(offset_asset_id: *const u8, length_asset_id: usize, amount: i64) -> i32 {
+    let asset_id = read_memory(offset_asset_id, length_asset_id);
+    unit_result(with(|world| world.burn(asset_id, amount)))
+}
+
+pub unsafe fn reissue(
+    offset_asset_id: *const u8,
+    length_asset_id: usize,
+    amount: i64,
+    is_reissuable: bool,
+) -> i32 {
+    let asset_id = read_memory(offset_asset_id, length_asset_id);
+    unit_result(with(|world| world.reissue(asset_id, amount, is_reissuable)))
+}
+
+// Block
+
+pub unsafe fn get_block_timestamp() -> (i32, i64) {
+    block_field(b"timestamp")
+}
+
+pub unsafe fn get_block_height() -> (i32, i64) {
+    block_field(b"height")
+}
+
+fn block_field(field: &[u8]) -> (i32, i64) {
+    value_result(with(|world| world.block_field(field)).map(|bytes| {
+        let mut result = [0u8; 8];
+        result.copy_from_slice(&bytes);
+        i64::from_be_bytes(result)
+    }))
+}
+
+// Call contract
+
+pub unsafe fn call_arg_int(value: i64) {
+    with(|world| world.push_call_arg(DataEntry::Integer(value)))
+}
+
+pub unsafe fn call_arg_bool(value: bool) {
+    with(|world| world.push_call_arg(DataEntry::Boolean(value)))
+}
+
+pub unsafe fn call_arg_binary(offset_value: *const u8, length_value: usize) -> i32 {
+    let value = read_memory(offset_value, length_value);
+    with(|world| world.push_call_arg(DataEntry::Binary(value.to_vec())));
+    0
+}
+
+pub unsafe fn call_arg_string(offset_value: *const u8, length_value: usize) -> i32 {
+    let value = match core::str::from_utf8(read_memory(offset_value, length_value)) {
+        Ok(value) => value,
+        Err(_) => return UTF8_ERROR,
+    };
+    with(|world| world.push_call_arg(DataEntry::String(value.to_string())));
+    0
+}
+
+pub unsafe fn call_payment(offset_asset_id: *const u8, length_asset_id: usize, amount: i64) -> i32 {
+    let asset_id = read_memory(offset_asset_id, length_asset_id);
+    with(|world| world.push_call_payment(asset_id, amount));
+    0
+}
+
+pub unsafe fn call_contract(
+    offset_contract_id: *const u8,
+    length_contract_id: usize,
+    offset_func_name: *const u8,
+    length_func_name: usize,
+) -> i32 {
+    let contract_id = read_memory(offset_contract_id, length_contract_id);
+    let func_name = read_memory(offset_func_name, length_func_name);
+    super::call_contract(contract_id, func_name, None)
+}
+
+pub unsafe fn call_contract_params(
+    offset_contract_id: *const u8,
+    length_contract_id: usize,
+    offset_func_name: *const u8,
+    length_func_name: usize,
+    offset_params: *const u8,
+    length_params: usize,
+) -> i32 {
+    let contract_id = read_memory(offset_contract_id, length_contract_id);
+    let func_name = read_memory(offset_func_name, length_func_name);
+    match DataEntry::deserialize_params(read_memory(offset_params, length_params)) {
+        Some(params) => super::call_contract(contract_id, func_name, Some(params)),
+        None => FAILED_DESERIALIZE,
+    }
+}
+
+// Converts
+
+pub unsafe fn parse_int(offset: *const u8, length: usize) -> (i32, i64) {
+    match core::str::from_utf8(read_memory(offset, length)) {
+        Ok(string) => value_result(string.parse::<i64>().map_err(|_| PARSE_ERROR)),
+        Err(_) => (UTF8_ERROR, 0),
+    }
+}
+
+pub unsafe fn parse_bool(offset_string: *const u8, length_string: usize) -> (i32, bool) {
+    match core::str::from_utf8(read_memory(offset_string, length_string)) {
+        Ok(string) => value_result(string.parse::<bool>().map_err(|_| PARSE_ERROR)),
+        Err(_) => (UTF8_ERROR, false),
+    }
+}
+
+pub unsafe fn to_bytes(value: i64) -> (i32, *const u8, usize) {
+    bytes_result(Ok(value.to_be_bytes().to_vec()))
+}
+
+pub unsafe fn to_int(offset: *const u8, length: usize) -> (i32, i64) {
+    match <[u8; 8]>::try_from(read_memory(offset, length)) {
+        Ok(bytes) => (0, i64::from_be_bytes(bytes)),
+        Err(_) => (CONVERTING_NUMERIC_TYPES, 0),
+    }
+}
+
+pub unsafe fn to_string_bool(value: bool) -> (i32, *const u8, usize) {
+    bytes_result(Ok(value.to_string().into_bytes()))
+}
+
+pub unsafe fn to_string_int(value: i64) -> (i32, *const u8, usize) {
+    bytes_result(Ok(value.to_string().into_bytes()))
+}
+
+// Crypto
+
+pub unsafe fn fast_hash(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    blake2b256(offset_bytes, length_bytes)
+}
+
+pub unsafe fn secure_hash(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    let mut hasher: Blake2b<U32> = Blake2b::new();
+    hasher.update(read_memory(offset_bytes, length_bytes));
+    let hash = hasher.finalize();
+
+    let mut hasher = Keccak256::new();
+    hasher.update(hash);
+    bytes_result(Ok(hasher.finalize().to_vec()))
+}
+
+pub unsafe fn blake2b256(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    let mut hasher: Blake2b<U32> = Blake2b::new();
+    hasher.update(read_memory(offset_bytes, length_bytes));
+    bytes_result(Ok(hasher.finalize().to_vec()))
+}
+
+pub unsafe fn keccak256(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    let mut hasher = Keccak256::new();
+    hasher.update(read_memory(offset_bytes, length_bytes));
+    bytes_result(Ok(hasher.finalize().to_vec()))
+}
+
+pub unsafe fn sha256(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    let mut hasher = Sha256::new();
+    hasher.update(read_memory(offset_bytes, length_bytes));
+    bytes_result(Ok(hasher.finalize().to_vec()))
+}
+
+/// Verifies a Curve25519 signature as produced by Waves Enterprise keys.
+pub unsafe fn sig_verify(
+    offset_message: *const u8,
+    length_message: usize,
+    offset_signature: *const u8,
+    length_signature: usize,
+    offset_public_key: *const u8,
+    length_public_key: usize,
+) -> (i32, bool) {
+    use curve25519_dalek::montgomery::MontgomeryPoint;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let message = read_memory(offset_message, length_message);
+    let signature = read_memory(offset_signature, length_signature);
+    let public_key = read_memory(offset_public_key, length_public_key);
+
+    let (mut signature, public_key) = match (
+        <[u8; 64]>::try_from(signature),
+        <[u8; 32]>::try_from(public_key),
+    ) {
+        (Ok(signature), Ok(public_key)) => (signature, public_key),
+        _ => return (0, false),
+    };
+
+    // The sign bit of the Edwards public key is carried in the signature
+    let sign = signature[63] & 0x80;
+    signature[63] &= 0x7f;
+
+    let result = MontgomeryPoint(public_key)
+        .to_edwards(sign >> 7)
+        .and_then(|point| VerifyingKey::from_bytes(&point.compress().to_bytes()).ok())
+        .map(|key| {
+            key.verify(message, &Signature::from_bytes(&signature))
+                .is_ok()
+        })
+        .unwrap_or(false);
+
+    (0, result)
+}
+
+// Lease
+
+pub unsafe fn lease_address(
+    offset_address: *const u8,
+    length_address: usize,
+    amount: i64,
+) -> (i32, *const u8, usize) {
+    let address = read_memory(offset_address, length_address);
+    bytes_result(with(|world| {
+        world.create_lease(Holder::Address(address.to_vec()), amount)
+    }))
+}
+
+pub unsafe fn lease_alias(
+    offset_alias: *const u8,
+    length_alias: usize,
+    amount: i64,
+) -> (i32, *const u8, usize) {
+    let alias = read_memory(offset_alias, length_alias);
+    bytes_result(with(|world| {
+        let recipient = Holder::from_raw(0, 2, alias)?;
+        world.create_lease(recipient, amount)
+    }))
+}
+
+pub unsafe fn cancel_lease(offset_lease_id: *const u8, length_lease_id: usize) -> i32 {
+    let lease_id = read_memory(offset_lease_id, length_lease_id);
+    unit_result(with(|world| world.cancel_lease(lease_id)))
+}
+
+// Memory
+
+pub unsafe fn binary_equals(
+    offset_left: *const u8,
+    length_left: usize,
+    offset_right: *const u8,
+    length_right: usize,
+) -> (i32, bool) {
+    let left = read_memory(offset_left, length_left);
+    let right = read_memory(offset_right, length_right);
+    (0, left == right)
+}
+
+pub unsafe fn string_equals(
+    offset_left: *const u8,
+    length_left: usize,
+    offset_right: *const u8,
+    length_right: usize,
+) -> (i32, bool) {
+    let left = core::str::from_utf8(read_memory(offset_left, length_left));
+    let right = core::str::from_utf8(read_memory(offset_right, length_right));
+    match (left, right) {
+        (Ok(left), Ok(right)) => (0, left == right),
+        _ => (UTF8_ERROR, false),
+    }
+}
+
+pub unsafe fn join(
+    offset_left: *const u8,
+    length_left: usize,
+    offset_right: *const u8,
+    length_right: usize,
+) -> (i32, *const u8, usize) {
+    let mut result = read_memory(offset_left, length_left).to_vec();
+    result.extend_from_slice(read_memory(offset_right, length_right));
+    bytes_result(Ok(result))
+}
+
+pub unsafe fn contains(
+    offset_bytes: *const u8,
+    length_bytes: usize,
+    offset_subbytes: *const u8,
+    length_subbytes: usize,
+) -> (i32, bool) {
+    let bytes = read_memory(offset_bytes, length_bytes);
+    let subbytes = read_memory(offset_subbytes, length_subbytes);
+    let result = subbytes.is_empty() || bytes.windows(subbytes.len()).any(|item| item == subbytes);
+    (0, result)
+}
+
+pub unsafe fn drop(
+    offset_bytes: *const u8,
+    length_bytes: usize,
+    n: i64,
+) -> (i32, *const u8, usize) {
+    match usize::try_from(n) {
+        Ok(n) if n <= length_bytes => (0, offset_bytes.add(n), length_bytes - n),
+        _ => (CONVERTING_NUMERIC_TYPES, core::ptr::null(), 0),
+    }
+}
+
+pub unsafe fn drop_right(
+    offset_bytes: *const u8,
+    length_bytes: usize,
+    n: i64,
+) -> (i32, *const u8, usize) {
+    match usize::try_from(n) {
+        Ok(n) if n <= length_bytes => (0, offset_bytes, length_bytes - n),
+        _ => (CONVERTING_NUMERIC_TYPES, core::ptr::null(), 0),
+    }
+}
+
+pub unsafe fn index_of(
+    offset_string: *const u8,
+    length_string: usize,
+    offset_substring: *const u8,
+    length_substring: usize,
+) -> (i32, i64) {
+    find(
+        offset_string,
+        length_string,
+        offset_substring,
+        length_substring,
+        false,
+    )
+}
+
+pub unsafe fn last_index_of(
+    offset_string: *const u8,
+    length_string: usize,
+    offset_substring: *const u8,
+    length_substring: usize,
+) -> (i32, i64) {
+    find(
+        offset_string,
+        length_string,
+        offset_substring,
+        length_substring,
+        true,
+    )
+}
+
+unsafe fn find(
+    offset_string: *const u8,
+    length_string: usize,
+    offset_substring: *const u8,
+    length_substring: usize,
+    is_last: bool,
+) -> (i32, i64) {
+    let string = core::str::from_utf8(read_memory(offset_string, length_string));
+    let substring = core::str::from_utf8(read_memory(offset_substring, length_substring));
+    let (string, substring) = match (string, substring) {
+        (Ok(string), Ok(substring)) => (string, substring),
+        _ => return (UTF8_ERROR, 0),
+    };
+
+    let result = if is_last {
+        string.rfind(substring)
+    } else {
+        string.find(substring)
+    };
+
+    match result {
+        Some(index) => (0, index as i64),
+        None => (0, -1),
+    }
+}
+
+pub unsafe fn take(
+    offset_bytes: *const u8,
+    length_bytes: usize,
+    n: i64,
+) -> (i32, *const u8, usize) {
+    match usize::try_from(n) {
+        Ok(n) if n <= length_bytes => (0, offset_bytes, n),
+        _ => (CONVERTING_NUMERIC_TYPES, core::ptr::null(), 0),
+    }
+}
+
+pub unsafe fn take_right(
+    offset_bytes: *const u8,
+    length_bytes: usize,
+    n: i64,
+) -> (i32, *const u8, usize) {
+    match usize::try_from(n) {
+        Ok(n) if n <= length_bytes => (0, offset_bytes.add(length_bytes - n), n),
+        _ => (CONVERTING_NUMERIC_TYPES, core::ptr::null(), 0),
+    }
+}
+
+// Storage
+
+unsafe fn address<'a>(offset_address: *const u8, length_address: usize) -> Option<&'a [u8]> {
+    match length_address {
+        0 => None,
+        _ => Some(read_memory(offset_address, length_address)),
+    }
+}
+
+pub unsafe fn contains_key(
+    offset_address: *const u8,
+    length_address: usize,
+    offset_key: *const u8,
+    length_key: usize,
+) -> (i32, bool) {
+    let address = address(offset_address, length_address);
+    let key = read_memory(offset_key, length_key);
+    (0, with(|world| world.contains_key(address, key)))
+}
+
+pub unsafe fn get_storage_int(
+    offset_address: *const u8,
+    length_address: usize,
+    offset_key: *const u8,
+    length_key: usize,
+) -> (i32, i64) {
+    let address = address(offset_address, length_address);
+    let key = read_memory(offset_key, length_key);
+    with(|world| match world.get_storage(address, key) {
+        Some(DataEntry::Integer(value)) => (0, *value),
+        Some(_) => (FAILED_DESERIALIZE, 0),
+        None => (0, 0),
+    })
+}
+
+pub unsafe fn get_storage_bool(
+    offset_address: *const u8,
+    length_address: usize,
+    offset_key: *const u8,
+    length_key: usize,
+) -> (i32, bool) {
+    let address = address(offset_address, length_address);
+    let key = read_memory(offset_key, length_key);
+    with(|world| match world.get_storage(address, key) {
+        Some(DataEntry::Boolean(value)) => (0, *value),
+        Some(_) => (FAILED_DESERIALIZE, false),
+        None => (0, false),
+    })
+}
+
+pub unsafe fn get_storage_binary(
+    offset_address: *const u8,
+    length_address: usize,
+    offset_key: *const u8,
+    length_key: usize,
+) -> (i32, *const u8, usize) {
+    let address = address(offset_address, length_address);
+    let key = read_memory(offset_key, length_key);
+    bytes_result(with(|world| match world.get_storage(address, key) {
+        Some(DataEntry::Binary(value)) => Ok(value.clone()),
+        Some(_) => Err(FAILED_DESERIALIZE),
+        None => Ok(vec![]),
+    }))
+}
+
+pub unsafe fn get_storage_string(
+    offset_address: *const u8,
+    length_address: usize,
+    offset_key: *const u8,
+    length_key: usize,
+) -> (i32, *const u8, usize) {
+    let address = address(offset_address, length_address);
+    let key = read_memory(offset_key, length_key);
+    bytes_result(with(|world| match world.get_storage(address, key) {
+        Some(DataEntry::String(value)) => Ok(value.clone().into_bytes()),
+        Some(_) => Err(FAILED_DESERIALIZE),
+        None => Ok(vec![]),
+    }))
+}
+
+pub unsafe fn set_storage_int(offset_key: *const u8, length_key: usize, value: i64) -> i32 {
+    let key = read_memory(offset_key, length_key);
+    with(|world| world.put_storage(key, DataEntry::Integer(value)));
+    0
+}
+
+pub unsafe fn set_storage_bool(offset_key: *const u8, length_key: usize, value: bool) -> i32 {
+    let key = read_memory(offset_key, length_key);
+    with(|world| world.put_storage(key, DataEntry::Boolean(value)));
+    0
+}
+
+pub unsafe fn set_storage_binary(
+    offset_key: *const u8,
+    length_key: usize,
+    offset_value: *const u8,
+    length_value: usize,
+) -> i32 {
+    let key = read_memory(offset_key, length_key);
+    let value = read_memory(offset_value, length_value);
+    with(|world| world.put_storage(key, DataEntry::Binary(value.to_vec())));
+    0
+}
+
+pub unsafe fn set_storage_string(
+    offset_key: *const u8,
+    length_key: usize,
+    offset_value: *const u8,
+    length_value: usize,
+) -> i32 {
+    let key = read_memory(offset_key, length_key);
+    let value = match core::str::from_utf8(read_memory(offset_value, length_value)) {
+        Ok(value) => value,
+        Err(_) => return UTF8_ERROR,
+    };
+    with(|world| world.put_storage(key, DataEntry::String(value.to_string())));
+    0
+}
+
+// Tx
+
+pub unsafe fn get_tx_sender() -> (i32, *const u8, usize) {
+    bytes_result(with(|world| world.tx_field(b"sender")))
+}
+
+pub unsafe fn get_payments() -> (i32, i32) {
+    (0, with(|world| world.payments().len() as i32))
+}
+
+pub unsafe fn get_payment_asset_id(number: i32) -> (i32, *const u8, usize) {
+    super::v1::get_payment_asset_id(number as i64)
+}
+
+pub unsafe fn get_payment_amount(number: i32) -> (i32, i64) {
+    super::v1::get_payment_amount(number as i64)
+}
+
+// Utils
+
+pub unsafe fn base_58(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    use base58::FromBase58;
+
+    match core::str::from_utf8(read_memory(offset_bytes, length_bytes)) {
+        Ok(value) => bytes_result(value.from_base58().map_err(|_| BASE58_ERROR)),
+        Err(_) => (UTF8_ERROR, core::ptr::null(), 0),
+    }
+}
+
+pub unsafe fn to_base_58_string(
+    offset_bytes: *const u8,
+    length_bytes: usize,
+) -> (i32, *const u8, usize) {
+    use base58::ToBase58;
+
+    let value = read_memory(offset_bytes, length_bytes);
+    bytes_result(Ok(value.to_base58().into_bytes()))
+}
+
+pub unsafe fn to_le_bytes(offset_bytes: *const u8, length_bytes: usize) -> (i32, *const u8, usize) {
+    let mut result = read_memory(offset_bytes, length_bytes).to_vec();
+    result.reverse();
+    bytes_result(Ok(result))
+}
+
+pub unsafe fn caller() -> (i32, *const u8, usize) {
+    let caller = with(|world| world.frame.caller.clone());
+    let (ptr, len) = write_memory(&caller);
+    (0, ptr, len)
+}
+
+pub unsafe fn require(offset_message: *const u8, length_message: usize) -> i32 {
+    let message = read_memory(offset_message, length_message);
+    unit_result(with(|world| world.require(message)))
+}
@@ -0,0 +1,89 @@
+use super::{
+    bytes_result, read_memory, unit_result, value_result, with,
+    world::{Holder, UTF8_ERROR},
+};
+
+// Asset
+
+pub unsafe fn get_balance(
+    offset_asset_id: *const u8,
+    length_asset_id: usize,
+    offset_holder: *const u8,
+    length_holder: usize,
+    type_: u32,
+    version: u32,
+) -> (i32, i64) {
+    let asset_id = read_memory(offset_asset_id, length_asset_id);
+    let holder = match length_holder {
+        0 => None,
+        _ => match Holder::from_raw(type_, version, read_memory(offset_holder, length_holder)) {
+            Ok(holder) => Some(holder),
+            Err(error) => return (error, 0),
+        },
+    };
+    (0, with(|world| world.get_balance(asset_id, holder)))
+}
+
+pub unsafe fn transfer(
+    offset_asset_id: *const u8,
+    length_asset_id: usize,
+    offset_recipient: *const u8,
+    length_recipient: usize,
+    type_: u32,
+    version: u32,
+    amount: i64,
+) -> i32 {
+    let asset_id = read_memory(offset_asset_id, length_asset_id);
+    let recipient = read_memory(offset_recipient, length_recipient);
+    unit_result(with(|world| {
+        let recipient = Holder::from_raw(type_, version, recipient)?;
+        world.transfer(asset_id, recipient, amount)
+    }))
+}
+
+pub unsafe fn issue(
+    offset_name: *const u8,
+    length_name: usize,
+    offset_description: *const u8,
+    length_description: usize,
+    quantity: i64,
+    decimals: i64,
+    is_reissuable: bool,
+) -> (i32, *const u8, usize) {
+    let name = read_memory(offset_name, length_name);
+    let description = read_memory(offset_description, length_description);
+    bytes_result(with(|world| {
+        world.issue(name, description, quantity, decimals, is_reissuable)
+    }))
+}
+
+// Block
+
+pub unsafe fn block(offset_field: *const u8, length_field: usize) -> (i32, *const u8, usize) {
+    let field = read_memory(offset_field, length_field);
+    bytes_result(with(|world| world.block_field(field)))
+}
+
+// Tx
+
+pub unsafe fn get_payments() -> (i32, i64) {
+    (0, with(|world| world.payments().len() as i64))
+}
+
+pub unsafe fn get_payment_asset_id(number: i64) -> (i32, *const u8, usize) {
+    bytes_result(with(|world| {
+        world.payment(number).map(|payment| payment.0.clone())
+    }))
+}
+
+pub unsafe fn get_payment_amount(number: i64) -> (i32, i64) {
+    value_result(with(|world| world.payment(number).map(|payment| payment.1)))
+}
+
+pub unsafe fn tx(offset_field: *const u8, length_field: usize) -> (i32, *const u8, usize) {
+    let field = read_memory(offset_field, length_field);
+    match core::str::from_utf8(field) {
+        Ok(_) => bytes_result(with(|world| world.tx_field(field))),
+        Err(_) => (UTF8_ERROR, core::ptr::null(), 0),
+    }
+}
@@ -0,0 +1,610 @@
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use std::{collections::HashMap, fmt, rc::Rc};
+
+/// Failed to parse (DataEntry arguments or Payments) (ExecutableError::FailedDeserialize)
+pub(crate) const FAILED_DESERIALIZE: i32 = 110;
+/// Exception thrown during contract execution (RuntimeError::Exception)
+pub(crate) const EXCEPTION: i32 = 300;
+/// Failed to retrieve UTF-8 string (RuntimeError::Utf8Error)
+pub(crate) const UTF8_ERROR: i32 = 302;
+/// Error converting Base58 string to bytes (RuntimeError::Base58Error)
+pub(crate) const BASE58_ERROR: i32 = 304;
+/// Error when converting numeric types (RuntimeError::ConvertingNumericTypes)
+pub(crate) const CONVERTING_NUMERIC_TYPES: i32 = 305;
+/// AssetHolder type not found (RuntimeError::AssetHolderTypeNotFound)
+pub(crate) const ASSET_HOLDER_TYPE_NOT_FOUND: i32 = 306;
+/// Address version not found (RuntimeError::AddressVersionNotFound)
+pub(crate) const ADDRESS_VERSION_NOT_FOUND: i32 = 307;
+/// Value parsing error (RuntimeError::ParseError)
+pub(crate) const PARSE_ERROR: i32 = 308;
+
+pub(crate) type Result<T> = core::result::Result<T, i32>;
+
+/// A value stored in the contract state or passed as a call argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataEntry {
+    Integer(i64),
+    Boolean(bool),
+    Binary(Vec<u8>),
+    String(String),
+}
+
+impl DataEntry {
+    /// Parses call arguments in the format used by `call_contract_params`.
+    pub fn deserialize_params(input: &[u8]) -> Option<Vec<DataEntry>> {
+        let mut params = vec![];
+        if input.is_empty() {
+            return Some(params);
+        }
+
+        let mut reader = Reader(input);
+        let count = u16::from_be_bytes(reader.take()?);
+        for _ in 0..count {
+            let length = u16::from_be_bytes(reader.take()?);
+            reader.bytes(length as usize)?;
+
+            let entry = match reader.take::<1>()?[0] {
+                0 => DataEntry::Integer(i64::from_be_bytes(reader.take()?)),
+                1 => DataEntry::Boolean(reader.take::<1>()?[0] != 0),
+                2 => {
+                    let length = u32::from_be_bytes(reader.take()?);
+                    DataEntry::Binary(reader.bytes(length as usize)?.to_vec())
+                }
+                3 => {
+                    let length = u32::from_be_bytes(reader.take()?);
+                    let bytes = reader.bytes(length as usize)?.to_vec();
+                    DataEntry::String(String::from_utf8(bytes).ok()?)
+                }
+                _ => return None,
+            };
+            params.push(entry);
+        }
+
+        Some(params)
+    }
+
+    /// Serializes call arguments in the format used by `call_contract_params`.
+    pub fn serialize_params(params: &[DataEntry]) -> Vec<u8> {
+        let mut result = (params.len() as u16).to_be_bytes().to_vec();
+
+        for param in params {
+            result.extend_from_slice(&0u16.to_be_bytes());
+            match param {
+                DataEntry::Integer(value) => {
+                    result.push(0);
+                    result.extend_from_slice(&value.to_be_bytes());
+                }
+                DataEntry::Boolean(value) => {
+                    result.push(1);
+                    result.push(*value as u8);
+                }
+                DataEntry::Binary(value) => {
+                    result.push(2);
+                    result.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                    result.extend_from_slice(value);
+                }
+                DataEntry::String(value) => {
+                    result.push(3);
+                    result.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                    result.extend_from_slice(value.as_bytes());
+                }
+            }
+        }
+
+        result
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        if self.0.len() < length {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(length);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.bytes(N)?.try_into().ok()
+    }
+}
+
+/// The owner of a balance: an account address, an alias or a contract.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Holder {
+    Address(Vec<u8>),
+    Alias(String),
+    Contract(Vec<u8>),
+}
+
+impl Holder {
+    /// Builds a holder from the `type`/`version` pair used by `wevm::v1::bindings`.
+    pub(crate) fn from_raw(type_: u32, version: u32, bytes: &[u8]) -> Result<Self> {
+        match (type_, version) {
+            (0, 1) => Ok(Holder::Address(bytes.to_vec())),
+            (0, 2) => match core::str::from_utf8(bytes) {
+                Ok(alias) => Ok(Holder::Alias(alias.to_string())),
+                Err(_) => Err(UTF8_ERROR),
+            },
+            (0, _) => Err(ADDRESS_VERSION_NOT_FOUND),
+            (1, _) => Ok(Holder::Contract(bytes.to_vec())),
+            _ => Err(ASSET_HOLDER_TYPE_NOT_FOUND),
+        }
+    }
+}
+
+/// Transaction fields available through `tx!`.
+#[derive(Clone, Debug, Default)]
+pub struct Tx {
+    pub tx_id: Vec<u8>,
+    pub sender: Vec<u8>,
+    pub sender_public_key: Vec<u8>,
+}
+
+/// Block fields available through `block!`.
+#[derive(Clone, Debug, Default)]
+pub struct Block {
+    pub height: i64,
+    pub timestamp: i64,
+}
+
+/// An asset issued by a contract with `issue!`.
+#[derive(Clone, Debug)]
+pub struct Asset {
+    pub issuer: Vec<u8>,
+    pub name: String,
+    pub description: String,
+    pub quantity: i64,
+    pub decimals: i64,
+    pub is_reissuable: bool,
+}
+
+/// A lease created by a contract with `lease!`.
+#[derive(Clone, Debug)]
+pub struct Lease {
+    pub sender: Vec<u8>,
+    pub recipient: Holder,
+    pub amount: i64,
+}
+
+/// A call made by the contract with `call_contract!`.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub caller: Vec<u8>,
+    pub contract_id: Vec<u8>,
+    pub func_name: String,
+    pub params: Vec<DataEntry>,
+    pub payments: Vec<(Vec<u8>, i64)>,
+}
+
+/// Handler executed in place of a called contract.
+/// Returns the error code of the call.
+pub type ContractHandler = Rc<dyn Fn(&Call) -> i32>;
+
+/// Execution context of the contract currently being run.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Frame {
+    pub contract_id: Vec<u8>,
+    pub caller: Vec<u8>,
+    pub payments: Vec<(Vec<u8>, i64)>,
+}
+
+/// In-memory state of the network as seen by the contract.
+#[derive(Clone, Default)]
+pub struct World {
+    pub(crate) frame: Frame,
+    tx: Tx,
+    block: Block,
+    storage: HashMap<Vec<u8>, HashMap<Vec<u8>, DataEntry>>,
+    balances: HashMap<(Holder, Vec<u8>), i64>,
+    aliases: HashMap<String, Vec<u8>>,
+    assets: HashMap<Vec<u8>, Asset>,
+    leases: HashMap<Vec<u8>, Lease>,
+    contracts: HashMap<Vec<u8>, ContractHandler>,
+    calls: Vec<Call>,
+    call_params: Vec<DataEntry>,
+    call_payments: Vec<(Vec<u8>, i64)>,
+    messages: Vec<String>,
+    nonce: u64,
+}
+
+impl fmt::Debug for World {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("World")
+            .field("frame", &self.frame)
+            .field("tx", &self.tx)
+            .field("block", &self.block)
+            .field("storage", &self.storage)
+            .field("balances", &self.balances)
+            .field("assets", &self.assets)
+            .field("leases", &self.leases)
+            .field("calls", &self.calls)
+            .finish_non_exhaustive()
+    }
+}
+
+impl World {
+    /// Sets the ContractId of the contract under test.
+    pub fn set_contract_id(&mut self, contract_id: &[u8]) -> &mut Self {
+        self.frame.contract_id = contract_id.to_vec();
+        self
+    }
+
+    /// Get the ContractId of the contract under test.
+    pub fn contract_id(&self) -> &[u8] {
+        &self.frame.contract_id
+    }
+
+    /// Sets the ContractId returned by `caller!`.
+    /// An empty slice means the contract was called from a transaction.
+    pub fn set_caller(&mut self, caller: &[u8]) -> &mut Self {
+        self.frame.caller = caller.to_vec();
+        self
+    }
+
+    /// Sets the transaction fields returned by `tx!`.
+    pub fn set_tx(&mut self, tx: Tx) -> &mut Self {
+        self.tx = tx;
+        self
+    }
+
+    /// Sets the block fields returned by `block!`.
+    pub fn set_block(&mut self, block: Block) -> &mut Self {
+        self.block = block;
+        self
+    }
+
+    /// Attaches a payment to the transaction and credits it to the contract,
+    /// as the node does before executing the contract.
+    pub fn add_payment(&mut self, asset_id: &[u8], amount: i64) -> &mut Self {
+        self.frame.payments.push((asset_id.to_vec(), amount));
+        let holder = Holder::Contract(self.frame.contract_id.clone());
+        *self.balance_mut(holder, asset_id) += amount;
+        self
+    }
+
+    /// Detaches the payments from the transaction, the credited balances are kept.
+    pub fn clear_payments(&mut self) -> &mut Self {
+        self.frame.payments.clear();
+        self
+    }
+
+    /// Sets the balance of the holder.
+    /// An empty `asset_id` stands for the system token.
+    pub fn set_balance(&mut self, holder: Holder, asset_id: &[u8], amount: i64) -> &mut Self {
+        *self.balance_mut(holder, asset_id) = amount;
+        self
+    }
+
+    /// Get the balance of the holder.
+    /// An empty `asset_id` stands for the system token.
+    pub fn balance(&self, holder: &Holder, asset_id: &[u8]) -> i64 {
+        let holder = self.resolve(holder.clone());
+        self.balances
+            .get(&(holder, asset_id.to_vec()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Binds an alias to an address, so both share the same balance.
+    pub fn register_alias(&mut self, alias: &str, address: &[u8]) -> &mut Self {
+        self.aliases.insert(alias.to_string(), address.to_vec());
+        self
+    }
+
+    /// Writes a value into the storage of the contract.
+    pub fn set_storage(&mut self, contract_id: &[u8], key: &str, value: DataEntry) -> &mut Self {
+        self.storage
+            .entry(contract_id.to_vec())
+            .or_default()
+            .insert(key.as_bytes().to_vec(), value);
+        self
+    }
+
+    /// Reads a value from the storage of the contract.
+    pub fn storage(&self, contract_id: &[u8], key: &str) -> Option<&DataEntry> {
+        self.storage
+            .get(contract_id)
+            .and_then(|storage| storage.get(key.as_bytes()))
+    }
+
+    /// Registers a handler executed when the contract is called with `call_contract!`.
+    /// Calls to contracts without a handler succeed and are only recorded.
+    pub fn register_contract(
+        &mut self,
+        contract_id: &[u8],
+        handler: impl Fn(&Call) -> i32 + 'static,
+    ) -> &mut Self {
+        self.contracts
+            .insert(contract_id.to_vec(), Rc::new(handler));
+        self
+    }
+
+    /// Get the calls made with `call_contract!`.
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// Get an asset issued with `issue!`.
+    pub fn asset(&self, asset_id: &[u8]) -> Option<&Asset> {
+        self.assets.get(asset_id)
+    }
+
+    /// Get an active lease created with `lease!`.
+    pub fn lease(&self, lease_id: &[u8]) -> Option<&Lease> {
+        self.leases.get(lease_id)
+    }
+
+    /// Get the messages passed to `require!`.
+    pub fn require_messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    // Asset
+
+    pub(crate) fn get_balance(&self, asset_id: &[u8], holder: Option<Holder>) -> i64 {
+        let holder = holder.unwrap_or_else(|| self.this());
+        self.balance(&holder, asset_id)
+    }
+
+    pub(crate) fn transfer(
+        &mut self,
+        asset_id: &[u8],
+        recipient: Holder,
+        amount: i64,
+    ) -> Result<()> {
+        let sender = self.this();
+        self.move_funds(sender, recipient, asset_id, amount)
+    }
+
+    pub(crate) fn issue(
+        &mut self,
+        name: &[u8],
+        description: &[u8],
+        quantity: i64,
+        decimals: i64,
+        is_reissuable: bool,
+    ) -> Result<Vec<u8>> {
+        if quantity < 0 || !(0..=8).contains(&decimals) {
+            return Err(EXCEPTION);
+        }
+
+        let asset = Asset {
+            issuer: self.frame.contract_id.clone(),
+            name: String::from_utf8(name.to_vec()).map_err(|_| UTF8_ERROR)?,
+            description: String::from_utf8(description.to_vec()).map_err(|_| UTF8_ERROR)?,
+            quantity,
+            decimals,
+            is_reissuable,
+        };
+
+        let asset_id = self.next_id(b"asset");
+        self.assets.insert(asset_id.clone(), asset);
+        *self.balance_mut(self.this(), &asset_id) += quantity;
+
+        Ok(asset_id)
+    }
+
+    pub(crate) fn burn(&mut self, asset_id: &[u8], amount: i64) -> Result<()> {
+        let this = self.this();
+        let balance = self.balance_mut(this, asset_id);
+        if amount < 0 || *balance < amount {
+            return Err(EXCEPTION);
+        }
+        *balance -= amount;
+
+        if let Some(asset) = self.assets.get_mut(asset_id) {
+            asset.quantity -= amount;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn reissue(
+        &mut self,
+        asset_id: &[u8],
+        amount: i64,
+        is_reissuable: bool,
+    ) -> Result<()> {
+        let contract_id = self.frame.contract_id.clone();
+        match self.assets.get_mut(asset_id) {
+            Some(asset) if asset.is_reissuable && asset.issuer == contract_id && amount >= 0 => {
+                asset.quantity += amount;
+                asset.is_reissuable = is_reissuable;
+            }
+            _ => return Err(EXCEPTION),
+        }
+
+        *self.balance_mut(self.this(), asset_id) += amount;
+        Ok(())
+    }
+
+    // Block
+
+    pub(crate) fn block_field(&self, field: &[u8]) -> Result<Vec<u8>> {
+        match field {
+            b"height" => Ok(self.block.height.to_be_bytes().to_vec()),
+            b"timestamp" => Ok(self.block.timestamp.to_be_bytes().to_vec()),
+            _ => Err(EXCEPTION),
+        }
+    }
+
+    // Call contract
+
+    pub(crate) fn push_call_arg(&mut self, value: DataEntry) {
+        self.call_params.push(value);
+    }
+
+    pub(crate) fn push_call_payment(&mut self, asset_id: &[u8], amount: i64) {
+        self.call_payments.push((asset_id.to_vec(), amount));
+    }
+
+    /// Records the call and transfers the attached payments to the called contract.
+    /// Returns the handler of the called contract, if any.
+    pub(crate) fn prepare_call(
+        &mut self,
+        contract_id: &[u8],
+        func_name: &[u8],
+        params: Option<Vec<DataEntry>>,
+    ) -> Result<(Call, Option<ContractHandler>)> {
+        let func_name = String::from_utf8(func_name.to_vec()).map_err(|_| UTF8_ERROR)?;
+        let params = params.unwrap_or_else(|| core::mem::take(&mut self.call_params));
+        let payments = core::mem::take(&mut self.call_payments);
+
+        for (asset_id, amount) in payments.iter() {
+            self.move_funds(
+                self.this(),
+                Holder::Contract(contract_id.to_vec()),
+                asset_id,
+                *amount,
+            )?;
+        }
+
+        let call = Call {
+            caller: self.frame.contract_id.clone(),
+            contract_id: contract_id.to_vec(),
+            func_name,
+            params,
+            payments,
+        };
+        self.calls.push(call.clone());
+
+        Ok((call, self.contracts.get(contract_id).cloned()))
+    }
+
+    // Lease
+
+    pub(crate) fn create_lease(&mut self, recipient: Holder, amount: i64) -> Result<Vec<u8>> {
+        let sender = self.frame.contract_id.clone();
+        let leased: i64 = self
+            .leases
+            .values()
+            .filter(|lease| lease.sender == sender)
+            .map(|lease| lease.amount)
+            .sum();
+
+        if amount <= 0 || self.balance(&self.this(), &[]) - leased < amount {
+            return Err(EXCEPTION);
+        }
+
+        let lease_id = self.next_id(b"lease");
+        let lease = Lease {
+            sender,
+            recipient: self.resolve(recipient),
+            amount,
+        };
+        self.leases.insert(lease_id.clone(), lease);
+
+        Ok(lease_id)
+    }
+
+    pub(crate) fn cancel_lease(&mut self, lease_id: &[u8]) -> Result<()> {
+        match self.leases.get(lease_id) {
+            Some(lease) if lease.sender == self.frame.contract_id => {
+                self.leases.remove(lease_id);
+                Ok(())
+            }
+            _ => Err(EXCEPTION),
+        }
+    }
+
+    // Storage
+
+    pub(crate) fn contains_key(&self, address: Option<&[u8]>, key: &[u8]) -> bool {
+        self.get_storage(address, key).is_some()
+    }
+
+    pub(crate) fn get_storage(&self, address: Option<&[u8]>, key: &[u8]) -> Option<&DataEntry> {
+        let address = address.unwrap_or(&self.frame.contract_id);
+        self.storage
+            .get(address)
+            .and_then(|storage| storage.get(key))
+    }
+
+    pub(crate) fn put_storage(&mut self, key: &[u8], value: DataEntry) {
+        self.storage
+            .entry(self.frame.contract_id.clone())
+            .or_default()
+            .insert(key.to_vec(), value);
+    }
+
+    // Tx
+
+    pub(crate) fn tx_field(&self, field: &[u8]) -> Result<Vec<u8>> {
+        match field {
+            b"txId" => Ok(self.tx.tx_id.clone()),
+            b"sender" => Ok(self.tx.sender.clone()),
+            b"senderPublicKey" => Ok(self.tx.sender_public_key.clone()),
+            _ => Err(EXCEPTION),
+        }
+    }
+
+    pub(crate) fn payments(&self) -> &[(Vec<u8>, i64)] {
+        &self.frame.payments
+    }
+
+    pub(crate) fn payment(&self, number: i64) -> Result<&(Vec<u8>, i64)> {
+        usize::try_from(number)
+            .ok()
+            .and_then(|number| self.frame.payments.get(number))
+            .ok_or(EXCEPTION)
+    }
+
+    // Utils
+
+    pub(crate) fn require(&mut self, message: &[u8]) -> Result<()> {
+        let message = String::from_utf8(message.to_vec()).map_err(|_| UTF8_ERROR)?;
+        self.messages.push(message);
+        Ok(())
+    }
+
+    fn this(&self) -> Holder {
+        Holder::Contract(self.frame.contract_id.clone())
+    }
+
+    fn resolve(&self, holder: Holder) -> Holder {
+        match holder {
+            Holder::Alias(alias) => match self.aliases.get(&alias) {
+                Some(address) => Holder::Address(address.clone()),
+                None => Holder::Alias(alias),
+            },
+            holder => holder,
+        }
+    }
+
+    fn balance_mut(&mut self, holder: Holder, asset_id: &[u8]) -> &mut i64 {
+        let holder = self.resolve(holder);
+        self.balances
+            .entry((holder, asset_id.to_vec()))
+            .or_default()
+    }
+
+    fn move_funds(
+        &mut self,
+        sender: Holder,
+        recipient: Holder,
+        asset_id: &[u8],
+        amount: i64,
+    ) -> Result<()> {
+        let balance = self.balance_mut(sender, asset_id);
+        if amount < 0 || *balance < amount {
+            return Err(EXCEPTION);
+        }
+        *balance -= amount;
+        *self.balance_mut(recipient, asset_id) += amount;
+        Ok(())
+    }
+
+    fn next_id(&mut self, domain: &[u8]) -> Vec<u8> {
+        self.nonce += 1;
+
+        let mut hasher: Blake2b<U32> = Blake2b::new();
+        hasher.update(domain);
+        hasher.update(&self.frame.contract_id);
+        hasher.update(self.nonce.to_be_bytes());
+        hasher.finalize().to_vec()
+    }
+}
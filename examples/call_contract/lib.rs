@@ -18,9 +18,10 @@ trait i_contract {
 // in case of an error, exits the function with an error code.
 #[action]
 fn _constructor() {
-    // Converting a string address to a byte address.
-    let contract = base58!("4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
-    let asset = base58!("DnK5Xfi2wXUJx9BjK9X6ZpFdTLdq2GtWH9pWrcxcmrhB");
+    // Converting string ids to a contract id and an asset id.
+    // Literals are decoded and checked during compilation.
+    let contract = base58!(contract => "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
+    let asset = base58!(asset => "DnK5Xfi2wXUJx9BjK9X6ZpFdTLdq2GtWH9pWrcxcmrhB");
 
     // Values that will serve as arguments for the function call.
     // The types used are aliases over Rust types.
@@ -38,7 +39,7 @@ fn _constructor() {
     };
 
     let payment_system_token: Payment = (SYSTEM_TOKEN, 4200000000);
-    let payment: Payment = (asset, 2400000000);
+    let payment: Payment = (asset.as_bytes(), 2400000000);
 
     call_contract! {
         i_contract(contract)::payment_fn()::payments(payment_system_token, payment)
@@ -50,8 +51,8 @@ fn _constructor() {
 // To do this, you only need to know the name of the function and pass the parameters with binary type
 #[action]
 fn call_with_binary_params(func_name: String, params: Binary) {
-    // Converting a string address to a byte address.
-    let contract = base58!("4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
+    // Converting a string id to a contract id.
+    let contract = base58!(contract => "4WVhw3QdiinpE5QXDG7QfqLiLanM7ewBw4ChX4qyGjs2");
 
     call_contract! {
         (contract)::call(func_name, params)
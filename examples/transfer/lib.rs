@@ -9,8 +9,8 @@ use we_cdk::*;
 // in case of an error, exits the function with an error code.
 #[action]
 fn _constructor() {
-    // Converting a string address to a byte address.
-    let address = base58!("3NqEjAkFVzem9CGa3bEPhakQc1Sm2G8gAFU");
+    // Converting a string address to an address, checked during compilation.
+    let address = base58!(address => "3NqEjAkFVzem9CGa3bEPhakQc1Sm2G8gAFU");
     let balance = get_balance!(this);
 
     let amount: Integer = 42;
@@ -19,5 +19,5 @@ fn _constructor() {
     // In this case, a balance check is performed.
     require!(balance > amount);
     // Transfer of funds to the `address`.
-    transfer!(address => address, amount);
+    transfer!(address, amount);
 }